@@ -0,0 +1,537 @@
+//! Central action registry and the keymap built on it
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::config::KeyList;
+use crate::types::Panel;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Action {
+    Quit,
+    Help,
+    Palette,
+    CommandLine,
+    RawCommand,
+    Console,
+    Panic,
+    CyclePanel,
+    Refresh,
+    ReloadContacts,
+    AddContact,
+    Users,
+    EditProfile,
+    ContactOptions,
+    ContactInfo,
+    ClearChat,
+    DeleteContact,
+    NextContact,
+    VerifyContact,
+    ShareProfile,
+    SetDisappearing,
+    ContactPrefs,
+    SetNotifications,
+    CycleNotifications,
+    ToggleBlock,
+    ToggleDnd,
+    SetAlias,
+    TagChat,
+    NextFolder,
+    PrevFolder,
+    FilterContacts,
+    ToggleFavorite,
+    TogglePin,
+    ToggleSection,
+    PrevContact,
+    OpenChat,
+    Compose,
+    ScrollUp,
+    ScrollDown,
+    HalfPageUp,
+    HalfPageDown,
+    ScrollTop,
+    ScrollBottom,
+    SearchChat,
+    SearchNext,
+    SearchPrev,
+    GlobalSearch,
+    SendFile,
+    PlayVoice,
+    RecordVoice,
+    RefreshAddress,
+    CreateAddress,
+    CopyLink,
+    CreateInvitation,
+    ToggleIncognito,
+    CancelInvitation,
+    NextLink,
+    PrevLink,
+    ExportQr,
+    Connect,
+    NextOption,
+    PrevOption,
+    RunOption,
+    Back,
+    FocusContacts,
+    FocusChat,
+    WidenContacts,
+    NarrowContacts,
+}
+
+/// Where a binding applies: `Anywhere` also works while typing, the
+/// panel contexts add to `Normal`, the modal ones stand alone
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Context {
+    Anywhere,
+    Normal,
+    Contacts,
+    Chat,
+    AddContact,
+    ContactOptions,
+}
+
+impl Context {
+    pub const ALL: [Context; 6] = [
+        Self::Anywhere, Self::Normal, Self::Contacts, Self::Chat, Self::AddContact, Self::ContactOptions,
+    ];
+    
+    /// Section name under `[keys]` in the config file
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Anywhere => "global",
+            Self::Normal => "normal",
+            Self::Contacts => "contacts",
+            Self::Chat => "chat",
+            Self::AddContact => "add_contact",
+            Self::ContactOptions => "contact_options",
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Anywhere => "Global",
+            Self::Normal => "Actions",
+            Self::Contacts => "Contact List",
+            Self::Chat => "Chat",
+            Self::AddContact => "Add Contact",
+            Self::ContactOptions => "Contact Options",
+        }
+    }
+    
+    /// Contexts active in normal mode, the panel's own first
+    pub fn normal(panel: Panel) -> &'static [Context] {
+        match panel {
+            Panel::Contacts => &[Self::Contacts, Self::Normal],
+            Panel::Chat => &[Self::Chat, Self::Normal],
+            Panel::Input => &[Self::Normal],
+        }
+    }
+}
+
+pub struct ActionInfo {
+    pub action: Action,
+    /// Name used in the config file
+    pub id: &'static str,
+    pub label: &'static str,
+    pub context: Context,
+    /// Default bindings; a space separates the keys of a chord
+    pub keys: &'static [&'static str],
+}
+
+macro_rules! action {
+    ($action:ident, $id:literal, $label:literal, $context:ident, [$($key:literal),*]) => {
+        ActionInfo { action: Action::$action, id: $id, label: $label, context: Context::$context, keys: &[$($key),*] }
+    };
+}
+
+pub const ACTIONS: &[ActionInfo] = &[
+    action!(Quit, "quit", "Quit application", Anywhere, ["ctrl-c", "ctrl-q"]),
+    action!(Palette, "palette", "Command palette", Anywhere, ["ctrl-p"]),
+    action!(ReloadContacts, "reload_contacts", "Reload contact list", Anywhere, ["ctrl-l"]),
+    action!(Panic, "panic", "Panic mode", Anywhere, ["f12"]),
+    action!(Help, "help", "Toggle help", Normal, ["?"]),
+    action!(CommandLine, "command_line", "Command line", Normal, [":"]),
+    action!(Console, "console", "Show command console", Normal, []),
+    action!(CyclePanel, "cycle_panel", "Switch panel", Normal, ["tab"]),
+    action!(WidenContacts, "widen_contacts", "Widen contact list", Normal, [">"]),
+    action!(NarrowContacts, "narrow_contacts", "Narrow contact list", Normal, ["<"]),
+    action!(NextFolder, "next_folder", "Next folder", Normal, ["]"]),
+    action!(PrevFolder, "prev_folder", "Previous folder", Normal, ["["]),
+    action!(Refresh, "refresh", "Refresh contacts & chat", Normal, ["r"]),
+    action!(AddContact, "add_contact", "Add contact", Normal, ["i"]),
+    action!(Users, "user_profiles", "Switch user profile", Normal, ["U"]),
+    action!(EditProfile, "edit_profile", "Edit own profile", Normal, ["E"]),
+    action!(ToggleDnd, "toggle_dnd", "Do not disturb on / off", Normal, ["D"]),
+    action!(ContactOptions, "contact_options", "Contact options", Normal, ["o"]),
+    action!(ContactInfo, "contact_info", "Contact info", Normal, []),
+    action!(ClearChat, "clear_chat", "Clear chat history", Normal, []),
+    action!(DeleteContact, "delete_contact", "Delete contact", Normal, []),
+    action!(SetAlias, "set_alias", "Set contact alias", Normal, []),
+    action!(VerifyContact, "verify_contact", "Verify security code", Normal, []),
+    action!(ShareProfile, "share_profile", "Share main profile with incognito contact", Normal, []),
+    action!(SetDisappearing, "set_disappearing", "Disappearing messages", Normal, []),
+    action!(ContactPrefs, "contact_prefs", "Chat preferences", Normal, []),
+    action!(SetNotifications, "set_notifications", "Notification level", Normal, []),
+    action!(ToggleBlock, "toggle_block", "Block / unblock contact", Normal, []),
+    action!(SendFile, "send_file", "Send file", Normal, ["f"]),
+    action!(PlayVoice, "play_voice", "Play / stop last voice message", Normal, ["p"]),
+    action!(RecordVoice, "record_voice", "Record voice message / stop & send", Normal, ["v"]),
+    action!(Quit, "quit", "Quit application", Normal, ["q"]),
+    action!(NextContact, "next_contact", "Next contact", Contacts, ["j", "down"]),
+    action!(PrevContact, "prev_contact", "Previous contact", Contacts, ["k", "up"]),
+    action!(OpenChat, "open_chat", "Open chat", Contacts, ["enter"]),
+    action!(FocusChat, "focus_chat", "Go to chat", Contacts, ["l", "right"]),
+    action!(FilterContacts, "filter_contacts", "Filter contact list", Contacts, ["s"]),
+    action!(ToggleFavorite, "toggle_favorite", "Favorite / unfavorite contact", Contacts, ["*"]),
+    action!(CycleNotifications, "cycle_notifications", "Notify: all / mentions / muted", Contacts, ["m"]),
+    action!(TogglePin, "toggle_pin", "Pin / unpin chat", Contacts, ["P"]),
+    action!(ToggleSection, "toggle_section", "Collapse / expand section", Contacts, ["space"]),
+    action!(TagChat, "tag_chat", "Tag contact", Contacts, ["t"]),
+    action!(RawCommand, "raw_command", "Raw simplex-chat command", Contacts, ["/"]),
+    action!(Compose, "compose", "Write a message", Chat, ["enter"]),
+    action!(FocusContacts, "focus_contacts", "Back to contact list", Chat, ["h", "left"]),
+    action!(ScrollDown, "scroll_down", "Scroll down", Chat, ["j", "down"]),
+    action!(ScrollUp, "scroll_up", "Scroll up", Chat, ["k", "up"]),
+    action!(HalfPageDown, "half_page_down", "Half page down", Chat, ["ctrl-d"]),
+    action!(HalfPageUp, "half_page_up", "Half page up", Chat, ["ctrl-u"]),
+    action!(ScrollTop, "scroll_top", "Oldest message", Chat, ["g g"]),
+    action!(ScrollBottom, "scroll_bottom", "Newest message", Chat, ["G"]),
+    action!(SearchChat, "search_chat", "Search this chat", Chat, ["/"]),
+    action!(SearchNext, "search_next", "Next older search hit", Chat, ["n"]),
+    action!(SearchPrev, "search_prev", "Next newer search hit", Chat, ["N"]),
+    action!(GlobalSearch, "search_all", "Search all chats", Normal, ["ctrl-f"]),
+    action!(RefreshAddress, "refresh_address", "Refresh address", AddContact, ["g"]),
+    action!(CreateAddress, "create_address", "Create new address", AddContact, ["c"]),
+    action!(CreateInvitation, "create_invitation", "Create one-time link", AddContact, ["n"]),
+    action!(ToggleIncognito, "toggle_incognito", "Toggle incognito for one-time links", AddContact, ["i"]),
+    action!(CancelInvitation, "cancel_invitation", "Cancel selected one-time link", AddContact, ["d"]),
+    action!(NextLink, "next_link", "Next link", AddContact, ["j", "down"]),
+    action!(PrevLink, "prev_link", "Previous link", AddContact, ["k", "up"]),
+    action!(CopyLink, "copy_link", "Copy shown link", AddContact, ["y"]),
+    action!(ExportQr, "export_qr", "Export shown QR code", AddContact, ["e"]),
+    action!(Connect, "connect", "Connect using pasted link", AddContact, ["enter"]),
+    action!(Back, "back", "Close", AddContact, ["esc"]),
+    action!(DeleteContact, "delete_contact", "Delete contact", ContactOptions, ["d"]),
+    action!(ClearChat, "clear_chat", "Clear chat history", ContactOptions, ["c"]),
+    action!(ContactInfo, "contact_info", "Contact info", ContactOptions, ["i"]),
+    action!(SetAlias, "set_alias", "Set contact alias", ContactOptions, ["a"]),
+    action!(VerifyContact, "verify_contact", "Verify security code", ContactOptions, ["v"]),
+    action!(ShareProfile, "share_profile", "Share main profile with incognito contact", ContactOptions, ["p"]),
+    action!(SetDisappearing, "set_disappearing", "Disappearing messages", ContactOptions, ["t"]),
+    action!(ContactPrefs, "contact_prefs", "Chat preferences", ContactOptions, ["e"]),
+    action!(SetNotifications, "set_notifications", "Notification level", ContactOptions, ["n"]),
+    action!(ToggleBlock, "toggle_block", "Block / unblock contact", ContactOptions, ["b"]),
+    action!(NextOption, "next_option", "Next option", ContactOptions, ["j", "down"]),
+    action!(PrevOption, "prev_option", "Previous option", ContactOptions, ["k", "up"]),
+    action!(RunOption, "run_option", "Run / confirm option", ContactOptions, ["enter"]),
+    action!(Back, "back", "Cancel / close", ContactOptions, ["esc", "x"]),
+];
+
+/// Buttons under the contact list, in order
+pub const ACTION_BAR: &[(Action, &str)] = &[
+    (Action::AddContact, "Add"),
+    (Action::ContactOptions, "Opts"),
+    (Action::Refresh, "Rfsh"),
+    (Action::Help, "Help"),
+];
+
+impl Action {
+    /// The first registry entry, used for labels
+    pub fn info(self) -> &'static ActionInfo {
+        ACTIONS.iter().find(|a| a.action == self).expect("every action is registered")
+    }
+    
+    /// Actions that only make sense inside their modal stay out of the palette
+    pub fn in_palette(self) -> bool {
+        !matches!(self.info().context, Context::AddContact | Context::ContactOptions)
+    }
+    
+    fn from_id(id: &str) -> Option<Self> {
+        ACTIONS.iter().find(|a| a.id == id).map(|a| a.action)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub mods: KeyModifiers,
+}
+
+impl KeyPress {
+    pub fn new(code: KeyCode, mods: KeyModifiers) -> Self {
+        // Shift is already part of the character
+        let mods = if matches!(code, KeyCode::Char(_)) { mods - KeyModifiers::SHIFT } else { mods };
+        Self { code, mods }
+    }
+    
+    /// Parses `ctrl-p`, `alt-x`, `f12`, `tab`, `G`, ...
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut mods = KeyModifiers::NONE;
+        let mut rest = spec;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            if let Some(r) = lower.strip_prefix("ctrl-") { mods |= KeyModifiers::CONTROL; rest = &rest[rest.len() - r.len()..]; }
+            else if let Some(r) = lower.strip_prefix("alt-") { mods |= KeyModifiers::ALT; rest = &rest[rest.len() - r.len()..]; }
+            else { break; }
+        }
+        let code = match rest.to_ascii_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            f if f.len() > 1 && f.starts_with('f') => KeyCode::F(f[1..].parse().ok()?),
+            _ => {
+                let mut chars = rest.chars();
+                let c = chars.next()?;
+                if chars.next().is_some() { return None; }
+                // Terminals report Ctrl-letters in lowercase
+                KeyCode::Char(if mods.contains(KeyModifiers::CONTROL) { c.to_ascii_lowercase() } else { c })
+            }
+        };
+        Some(Self { code, mods })
+    }
+    
+    pub fn label(&self) -> String {
+        let key = match self.code {
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Home => "Home".to_string(),
+            KeyCode::End => "End".to_string(),
+            KeyCode::PageUp => "PgUp".to_string(),
+            KeyCode::PageDown => "PgDn".to_string(),
+            KeyCode::F(n) => format!("F{}", n),
+            KeyCode::Char(c) if self.mods.contains(KeyModifiers::CONTROL) => c.to_ascii_uppercase().to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            _ => "?".to_string(),
+        };
+        let mut out = String::new();
+        if self.mods.contains(KeyModifiers::CONTROL) { out.push_str("Ctrl-"); }
+        if self.mods.contains(KeyModifiers::ALT) { out.push_str("Alt-"); }
+        out + &key
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Resolved {
+    Run(Action),
+    /// The keys so far start a chord
+    Pending,
+    Unbound,
+}
+
+struct Binding {
+    keys: Vec<KeyPress>,
+    action: Action,
+    context: Context,
+}
+
+fn parse_chord(spec: &str) -> Option<Vec<KeyPress>> {
+    let keys = spec.split_whitespace().map(KeyPress::parse).collect::<Option<Vec<_>>>()?;
+    if keys.is_empty() { None } else { Some(keys) }
+}
+
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = ACTIONS.iter().flat_map(|info| {
+            info.keys.iter().filter_map(move |spec| {
+                Some(Binding { keys: parse_chord(spec)?, action: info.action, context: info.context })
+            })
+        }).collect();
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    /// Defaults with the `[keys.<context>]` tables from the config applied;
+    /// an entry replaces all default keys of that action in that context
+    pub fn from_config(overrides: &HashMap<String, HashMap<String, KeyList>>) -> Result<Self> {
+        let mut keymap = Self::default();
+        for (section, table) in overrides {
+            let Some(context) = Context::ALL.iter().copied().find(|c| c.id() == section) else {
+                bail!("unknown keymap section [keys.{}]", section);
+            };
+            for (id, keys) in table {
+                let Some(action) = Action::from_id(id) else { bail!("unknown action '{}' in [keys.{}]", id, section) };
+                keymap.bindings.retain(|b| !(b.context == context && b.action == action));
+                for spec in keys.specs() {
+                    let Some(keys) = parse_chord(spec) else { bail!("invalid key '{}' for {} in [keys.{}]", spec, id, section) };
+                    keymap.bindings.push(Binding { keys, action, context });
+                }
+            }
+        }
+        Ok(keymap)
+    }
+    
+    /// Looks up a key sequence in the given contexts, earlier ones first
+    pub fn resolve(&self, keys: &[KeyPress], contexts: &[Context]) -> Resolved {
+        let mut pending = false;
+        for context in contexts {
+            for b in self.bindings.iter().filter(|b| b.context == *context && b.keys.starts_with(keys)) {
+                if b.keys.len() == keys.len() { return Resolved::Run(b.action); }
+                pending = true;
+            }
+        }
+        if pending { Resolved::Pending } else { Resolved::Unbound }
+    }
+    
+    /// Bindings of an action for display, e.g. "j / ↓"
+    pub fn keys_for(&self, action: Action) -> String {
+        self.keys_matching(|b| b.action == action)
+    }
+    
+    /// The first binding, for compact labels such as the action bar
+    pub fn first_key(&self, action: Action) -> Option<String> {
+        let b = self.bindings.iter().find(|b| b.action == action)?;
+        Some(chord_label(&b.keys))
+    }
+    
+    /// "[key]" for inline hints, naming the first binding
+    pub fn hint(&self, action: Action) -> String {
+        format!("[{}]", self.first_key(action).unwrap_or_else(|| "unbound".into()))
+    }
+    
+    /// Like `keys_for`, limited to one context
+    pub fn keys_in(&self, action: Action, context: Context) -> String {
+        self.keys_matching(|b| b.action == action && b.context == context)
+    }
+    
+    /// Actions bound in a context, in registry order, for the help screen
+    pub fn bound_in(&self, context: Context) -> Vec<Action> {
+        let mut actions: Vec<Action> = vec![];
+        let registry = ACTIONS.iter().map(|a| a.action);
+        let extra = self.bindings.iter().filter(|b| b.context == context).map(|b| b.action);
+        for action in registry.chain(extra) {
+            if !actions.contains(&action) && self.bindings.iter().any(|b| b.context == context && b.action == action) {
+                actions.push(action);
+            }
+        }
+        actions
+    }
+    
+    fn keys_matching(&self, filter: impl Fn(&Binding) -> bool) -> String {
+        self.bindings.iter()
+            .filter(|b| filter(b))
+            .map(|b| chord_label(&b.keys))
+            .collect::<Vec<_>>()
+            .join(" / ")
+    }
+}
+
+fn chord_label(keys: &[KeyPress]) -> String {
+    keys.iter().map(KeyPress::label).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn key(spec: &str) -> KeyPress {
+        KeyPress::parse(spec).unwrap()
+    }
+    
+    fn keys(specs: &str) -> Vec<KeyPress> {
+        parse_chord(specs).unwrap()
+    }
+    
+    fn configured(config: &str) -> Result<Keymap> {
+        Keymap::from_config(&toml::from_str(config).unwrap())
+    }
+    
+    #[test]
+    fn parses_key_specs() {
+        assert_eq!(key("ctrl-p"), KeyPress { code: KeyCode::Char('p'), mods: KeyModifiers::CONTROL });
+        assert_eq!(key("Ctrl-P"), key("ctrl-p"));
+        assert_eq!(key("ctrl-alt-x").mods, KeyModifiers::CONTROL | KeyModifiers::ALT);
+        assert_eq!(key("f12").code, KeyCode::F(12));
+        assert_eq!(key("space").code, KeyCode::Char(' '));
+        assert_eq!(key("G"), KeyPress::new(KeyCode::Char('G'), KeyModifiers::SHIFT));
+        assert_eq!(key("f").code, KeyCode::Char('f'));
+        for spec in ["", "ctrl-", "ab", "fx"] {
+            assert!(KeyPress::parse(spec).is_none(), "{}", spec);
+        }
+        assert!(parse_chord("  ").is_none());
+        assert!(parse_chord("g nope").is_none());
+    }
+    
+    #[test]
+    fn resolves_chords() {
+        let keymap = Keymap::default();
+        let chat = Context::normal(Panel::Chat);
+        assert_eq!(keymap.resolve(&keys("g"), chat), Resolved::Pending);
+        assert_eq!(keymap.resolve(&keys("g g"), chat), Resolved::Run(Action::ScrollTop));
+        assert_eq!(keymap.resolve(&keys("g x"), chat), Resolved::Unbound);
+    }
+    
+    #[test]
+    fn earlier_contexts_win() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.resolve(&keys("j"), Context::normal(Panel::Contacts)), Resolved::Run(Action::NextContact));
+        assert_eq!(keymap.resolve(&keys("j"), Context::normal(Panel::Chat)), Resolved::Run(Action::ScrollDown));
+        
+        let keymap = configured("[global]\npalette = \"ctrl-d\"").unwrap();
+        assert_eq!(keymap.resolve(&keys("ctrl-d"), &[Context::Anywhere, Context::Chat]), Resolved::Run(Action::Palette));
+        assert_eq!(keymap.resolve(&keys("ctrl-d"), &[Context::Chat]), Resolved::Run(Action::HalfPageDown));
+        // A whole binding in an earlier context beats a chord in a later one
+        let keymap = keymap_with_chord();
+        assert_eq!(keymap.resolve(&keys("space"), Context::normal(Panel::Contacts)), Resolved::Run(Action::ToggleSection));
+        assert_eq!(keymap.resolve(&keys("space"), Context::normal(Panel::Chat)), Resolved::Pending);
+        assert_eq!(keymap.resolve(&keys("space h"), Context::normal(Panel::Chat)), Resolved::Run(Action::Help));
+    }
+    
+    fn keymap_with_chord() -> Keymap {
+        configured("[normal]\nhelp = \"space h\"").unwrap()
+    }
+    
+    #[test]
+    fn overrides_replace_defaults_in_their_context() {
+        let keymap = configured("[chat]\nscroll_down = [\"J\", \"ctrl-n\"]").unwrap();
+        let chat = Context::normal(Panel::Chat);
+        assert_eq!(keymap.resolve(&keys("j"), chat), Resolved::Unbound);
+        assert_eq!(keymap.resolve(&keys("J"), chat), Resolved::Run(Action::ScrollDown));
+        assert_eq!(keymap.resolve(&keys("ctrl-n"), chat), Resolved::Run(Action::ScrollDown));
+        assert_eq!(keymap.keys_in(Action::ScrollDown, Context::Chat), "J / Ctrl-N");
+        assert_eq!(keymap.resolve(&keys("j"), Context::normal(Panel::Contacts)), Resolved::Run(Action::NextContact));
+    }
+    
+    #[test]
+    fn empty_list_unbinds() {
+        let keymap = configured("[global]\nquit = []").unwrap();
+        assert_eq!(keymap.resolve(&keys("ctrl-c"), &[Context::Anywhere]), Resolved::Unbound);
+        assert_eq!(keymap.resolve(&keys("q"), &[Context::Normal]), Resolved::Run(Action::Quit));
+        assert!(!keymap.bound_in(Context::Anywhere).contains(&Action::Quit));
+    }
+    
+    #[test]
+    fn rejects_invalid_config() {
+        let err = |config: &str| configured(config).err().unwrap().to_string();
+        assert_eq!(err("[nowhere]\nquit = \"q\""), "unknown keymap section [keys.nowhere]");
+        assert_eq!(err("[chat]\nfly = \"f\""), "unknown action 'fly' in [keys.chat]");
+        assert_eq!(err("[chat]\nscroll_top = \"g ctrl-\""), "invalid key 'g ctrl-' for scroll_top in [keys.chat]");
+    }
+}
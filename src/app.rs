@@ -4,53 +4,150 @@
 //! GitHub: https://github.com/cannatoshi/simplex-tui
 //! Licensed under AGPL-3.0
 
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::time::Instant;
 use chrono::Local;
 use ratatui::widgets::ListState;
 
-use crate::types::{Contact, ChatMessage, Panel, Mode, MessageStatus, ContactOption};
+use crate::actions::{Action, KeyPress, Keymap};
+use crate::audio::{AudioBackend, CommandBackend};
+use crate::chat_prefs::PrefsEditor;
+use crate::clipboard;
+use crate::colors;
+use crate::command::{CommandLine, Console};
+use crate::config::{Config, NotifyConfig};
+use crate::contact_list::{self, Row, Section};
+use crate::drafts::{ChatKey, Drafts};
+use crate::editor::Editor;
+use crate::hitmap::{Drag, Hit, HitMap};
+use crate::vi::Vi;
+use crate::link_preview;
+use crate::notify::Notifier;
+use crate::palette::Palette;
+use crate::profile::{self, ProfileEditor};
+use crate::qr;
+use crate::search::{self, GlobalSearch};
+use crate::state::{Blocked, Pin, UiState};
+use crate::tags::{self, Tags};
+use crate::thumbnail;
+use crate::timed;
+use crate::users::UserSwitcher;
+use crate::types::{ApiCommand, Contact, ChatMessage, LinkPreview, Panel, Mode, MessageStatus, ContactOption, SearchHit, ChatTag, Group, Verification, Invitation, Timer, NTFS_LEVELS, ContactRequest};
+
+const PREVIEW_DEBOUNCE_MS: u128 = 800;
 
 const DOUBLE_CLICK_MS: u128 = 400;
 
+const MIN_CONTACTS_WIDTH: u16 = 16;
+const MAX_CONTACTS_WIDTH: u16 = 60;
+
 pub struct App {
     pub running: bool,
     pub mode: Mode,
     pub panel: Panel,
     pub contacts: Vec<Contact>,
+    pub groups: Vec<Group>,
     pub contact_state: ListState,
+    /// Set when a section header rather than a contact is selected
+    pub selected_section: Option<Section>,
+    pub contact_filter: String,
+    pub user_id: Option<i64>,
+    pub users: UserSwitcher,
+    /// Active user's profile as last loaded, with its full preferences
+    pub own_profile: Option<serde_json::Value>,
+    pub profile_editor: Option<ProfileEditor>,
+    pub prefs_editor: Option<PrefsEditor>,
+    pub tags: Tags,
+    /// Tags for a chat waiting on new backend tags: (group, chat id, tags)
+    pending_tags: Option<(bool, i64, Vec<String>)>,
     pub messages: Vec<ChatMessage>,
     pub message_limit: usize,
     pub scroll: usize,
-    pub input: String,
-    pub cursor: usize,
+    pub composer: Editor,
+    pub drafts: Drafts,
+    /// Index into the chat's sent history while recalling with Up/Down
+    pub history_pos: Option<usize>,
+    pub vi: Option<Vi>,
+    /// Key bindings, defaults with the `[keys]` config applied
+    pub keymap: Keymap,
+    /// Keys typed so far of a chord such as `g g`
+    pub pending_keys: Vec<KeyPress>,
+    pub palette: Palette,
+    pub search_input: String,
+    pub search_query: Option<String>,
+    pub search_hits: Vec<usize>,
+    pub search_pos: usize,
+    /// Backend item to show once the chat history containing it is loaded
+    pub search_target: Option<i64>,
+    /// Older history was requested for backend hits; jump when it arrives
+    search_reload: bool,
+    pub global_search: GlobalSearch,
+    /// Message to scroll to once the next frame has laid out the chat
+    pub pending_jump: Option<usize>,
+    pub chat_view_height: usize,
+    /// Wrapped height per message with the key it was computed for
+    pub chat_heights: Vec<(u64, usize)>,
     pub show_help: bool,
+    /// First help line shown; clamped when drawn
+    pub help_scroll: usize,
+    /// Help lines that fit on screen, from the last frame
+    pub help_height: usize,
     pub tick: u64,
     pub status: String,
     pub connected: bool,
     pub current_contact: Option<String>,
-    pub cmd_tx: Option<mpsc::Sender<String>>,
-    pub last_input_y: Option<u16>,
+    pub cmd_tx: Option<mpsc::Sender<ApiCommand>>,
+    pub command: CommandLine,
+    pub console: Console,
+    /// Clickable regions of the last frame
+    pub hits: HitMap,
+    pub dragging: Option<Drag>,
+    /// Too narrow for both panes, only the focused one is drawn
+    pub compact: bool,
+    pub ui_state: UiState,
     pub invite_link: Option<String>,
     pub connect_input: String,
     pub pending_new_address: bool,
+    /// Pending one-time links we created
+    pub invitations: Vec<Invitation>,
+    /// Requests through our address waiting for `:accept` or `:reject`
+    pub contact_requests: Vec<ContactRequest>,
+    /// One-time link shown in Add Contact instead of the address
+    pub invitation_selected: Option<usize>,
+    /// New connections use a random profile: one-time links, pasted links
+    /// and accepted requests
+    pub invite_incognito: bool,
     
     pub contact_for_options: Option<String>,
     pub contact_info_data: Option<crate::types::ContactInfoData>,
+    pub verification: Option<Verification>,
     pub file_explorer: Option<ratatui_explorer::FileExplorer>,
-    pub btn_refresh: Option<ratatui::layout::Rect>,
-    pub btn_create: Option<ratatui::layout::Rect>,
-    pub btn_close: Option<ratatui::layout::Rect>,
-    pub btn_contact_options: [Option<ratatui::layout::Rect>; 4],
     pub option_selection: usize,
     pub confirm_action: Option<ContactOption>,
     
+    pub config: Config,
+    pub notifier: Notifier,
+    pub audio: Box<dyn AudioBackend>,
+    pub playing_voice: Option<u64>,
+    pub pending_voice: Option<u64>,
+    pub recording: Option<(PathBuf, Instant)>,
+    /// Stopped recording waiting for the recorder to finish, with its length
+    pub finishing_recording: Option<(PathBuf, u64)>,
+    pub compose_preview: Option<LinkPreview>,
+    pub preview_fetch: Option<(String, mpsc::Receiver<anyhow::Result<LinkPreview>>)>,
+    preview_dismissed: Option<String>,
+    preview_seen_input: (String, Option<Instant>),
+    
     last_click_time: Option<Instant>,
-    last_click_index: Option<usize>,
+    last_click: Option<Hit>,
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(config: Config, drafts: Drafts, keymap: Keymap) -> Self {
+        let audio = Box::new(CommandBackend::new(&config.audio));
+        let notifier = Notifier::new(config.notifications.clone());
+        let (ui_state, tags) = stored_state();
         let mut contact_state = ListState::default();
         contact_state.select(Some(0));
         
@@ -59,54 +156,967 @@ impl App {
             mode: Mode::Normal,
             panel: Panel::Contacts,
             contacts: vec![],
+            groups: vec![],
             contact_state,
+            selected_section: None,
+            contact_filter: String::new(),
+            user_id: None,
+            tags,
+            pending_tags: None,
+            users: UserSwitcher::default(),
+            own_profile: None,
+            profile_editor: None,
+            prefs_editor: None,
             messages: vec![],
             message_limit: 50,
             scroll: 0,
-            input: String::new(),
-            cursor: 0,
+            composer: Editor::default(),
+            drafts,
+            history_pos: None,
+            vi: config.editor.vi_mode.then(Vi::new),
+            keymap,
+            pending_keys: vec![],
+            palette: Palette::default(),
+            search_input: String::new(),
+            search_query: None,
+            search_hits: vec![],
+            search_pos: 0,
+            search_target: None,
+            search_reload: false,
+            global_search: GlobalSearch::default(),
+            pending_jump: None,
+            chat_view_height: 0,
+            chat_heights: vec![],
             show_help: false,
+            help_scroll: 0,
+            help_height: 0,
             tick: 0,
             status: "Connecting...".into(),
             connected: false,
             current_contact: None,
             cmd_tx: None,
-            last_input_y: None,
+            command: CommandLine::default(),
+            console: Console::default(),
+            hits: HitMap::default(),
+            dragging: None,
+            compact: false,
+            ui_state,
             invite_link: None,
             connect_input: String::new(),
             pending_new_address: false,
+            invitations: Vec::new(),
+            contact_requests: Vec::new(),
+            invitation_selected: None,
+            invite_incognito: false,
             
             contact_for_options: None,
             contact_info_data: None,
+            verification: None,
             file_explorer: None,
-            btn_refresh: None,
-            btn_create: None,
-            btn_close: None,
-            btn_contact_options: [None; 4],
             option_selection: 0,
             confirm_action: None,
             
+            config,
+            notifier,
+            audio,
+            playing_voice: None,
+            pending_voice: None,
+            recording: None,
+            finishing_recording: None,
+            compose_preview: None,
+            preview_fetch: None,
+            preview_dismissed: None,
+            preview_seen_input: (String::new(), None),
+            
             last_click_time: None,
-            last_click_index: None,
+            last_click: None,
+        }
+    }
+    
+    /// Runs a registered action, whether it came from a key, the palette or a click
+    pub fn run_action(&mut self, action: Action) {
+        let selected = self.selected_contact().map(|c| c.name.clone());
+        match action {
+            Action::Quit => self.running = false,
+            Action::Help => self.toggle_help(),
+            Action::Palette => {
+                self.palette.reset();
+                self.mode = Mode::Palette;
+            }
+            Action::CommandLine => self.start_command(""),
+            Action::RawCommand => self.start_command("/"),
+            Action::Console => {
+                self.console.visible = true;
+                self.mode = Mode::Console;
+            }
+            Action::Panic => self.set_panic(true),
+            Action::CyclePanel => self.cycle_panel(),
+            Action::Refresh => {
+                self.refresh_chat();
+                self.send_cmd("/contacts");
+            }
+            Action::ReloadContacts => self.send_cmd("/contacts"),
+            Action::AddContact => {
+                self.mode = Mode::AddContact;
+                self.invitation_selected = None;
+                self.request_address();
+                self.request_invitations();
+            }
+            Action::Users => self.open_users(),
+            Action::EditProfile => self.open_profile_editor(),
+            Action::ContactOptions => {
+                if let Some(name) = selected { self.open_contact_options(name); }
+            }
+            Action::ContactInfo | Action::ClearChat | Action::DeleteContact | Action::SetAlias | Action::VerifyContact | Action::ShareProfile | Action::SetDisappearing | Action::ContactPrefs
+            | Action::SetNotifications | Action::ToggleBlock => {
+                let option = match action {
+                    Action::ContactInfo => ContactOption::ContactInfo,
+                    Action::SetAlias => ContactOption::SetAlias,
+                    Action::VerifyContact => ContactOption::Verify,
+                    Action::ShareProfile => ContactOption::ShareProfile,
+                    Action::SetDisappearing => ContactOption::Disappearing,
+                    Action::ContactPrefs => ContactOption::Preferences,
+                    Action::SetNotifications => ContactOption::Notifications,
+                    Action::ToggleBlock => ContactOption::Block,
+                    Action::ClearChat => ContactOption::ClearChat,
+                    _ => ContactOption::DeleteContact,
+                };
+                // Goes through the options modal so destructive ones get confirmed
+                if self.mode != Mode::ContactOptions {
+                    let Some(name) = selected else { return };
+                    self.open_contact_options(name);
+                }
+                self.option_selection = ContactOption::all().iter().position(|o| *o == option).unwrap_or(0);
+                self.execute_selected_option();
+            }
+            Action::FilterContacts => {
+                self.panel = Panel::Contacts;
+                self.mode = Mode::ContactFilter;
+            }
+            Action::ToggleFavorite => self.toggle_favorite(),
+            Action::ToggleDnd => self.set_dnd(!self.ui_state.dnd),
+            Action::CycleNotifications => {
+                let Some(name) = self.selected_contact().filter(|_| self.selected_section.is_none()).map(|c| c.name.clone()) else { return };
+                self.set_notifications(&name, false, None);
+            }
+            Action::TogglePin => self.toggle_pin(),
+            Action::TagChat => {
+                if let Some(name) = selected { self.start_command(&format!("tag {} ", chat_arg(&name, false))); }
+            }
+            Action::NextFolder => self.switch_folder(true),
+            Action::PrevFolder => self.switch_folder(false),
+            Action::ToggleSection => self.toggle_section(None),
+            Action::OpenChat if self.selected_section.is_some() => self.toggle_section(None),
+            Action::NextContact => self.next_contact(),
+            Action::PrevContact => self.prev_contact(),
+            Action::OpenChat => {
+                self.select_contact();
+                if self.compact { self.panel = Panel::Chat; }
+            }
+            Action::FocusContacts => {
+                self.panel = Panel::Contacts;
+                self.mode = Mode::Normal;
+            }
+            Action::FocusChat => self.panel = Panel::Chat,
+            Action::WidenContacts => self.resize_contacts(self.ui_state.contacts_width + 2, true),
+            Action::NarrowContacts => self.resize_contacts(self.ui_state.contacts_width.saturating_sub(2), true),
+            Action::Compose => {
+                self.panel = Panel::Input;
+                self.mode = Mode::Input;
+            }
+            Action::ScrollUp => self.scroll_up(),
+            Action::ScrollDown => self.scroll_down(),
+            Action::HalfPageUp => self.scroll_half_page(false),
+            Action::HalfPageDown => self.scroll_half_page(true),
+            Action::ScrollTop => self.scroll_to_top(),
+            Action::ScrollBottom => self.auto_scroll(),
+            Action::SearchChat => self.start_chat_search(),
+            Action::SearchNext => self.next_search_hit(true),
+            Action::SearchPrev => self.next_search_hit(false),
+            Action::GlobalSearch => self.start_global_search(""),
+            Action::SendFile => {
+                if self.current_contact.is_none() { return; }
+                self.open_file_browser();
+            }
+            Action::PlayVoice => self.toggle_voice_playback(),
+            Action::RecordVoice => self.toggle_voice_recording(),
+            Action::RefreshAddress => {
+                self.request_address();
+                self.request_invitations();
+            }
+            Action::CreateAddress => self.create_address(),
+            Action::CreateInvitation => self.create_invitation(),
+            Action::ToggleIncognito => self.toggle_incognito(),
+            Action::CancelInvitation => self.cancel_invitation(),
+            Action::NextLink => self.select_link(self.invitation_selected.map_or(1, |i| i + 2)),
+            Action::PrevLink => match self.invitation_selected {
+                Some(0) | None => self.invitation_selected = None,
+                Some(i) => self.invitation_selected = Some(i - 1),
+            },
+            Action::CopyLink => match self.shown_link().map(str::to_string) {
+                Some(link) => {
+                    clipboard::copy(&link, &self.config.editor);
+                    self.status = "Copied link".into();
+                }
+                None => self.status = "No address loaded".into(),
+            },
+            Action::ExportQr => {
+                let name = if self.invitation_selected.is_some() { "simplex-invitation" } else { "simplex-address" };
+                self.start_command(&format!("qr ~/{}.png", name));
+            }
+            Action::Connect => {
+                if !self.connect_input.is_empty() { self.connect_to_invite(); }
+            }
+            Action::NextOption => {
+                self.confirm_action = None;
+                self.next_option();
+            }
+            Action::PrevOption => {
+                self.confirm_action = None;
+                self.prev_option();
+            }
+            Action::RunOption => self.execute_selected_option(),
+            Action::Back => match self.mode {
+                Mode::ContactOptions if self.confirm_action.is_some() => {
+                    self.confirm_action = None;
+                    self.status = "Action cancelled".into();
+                }
+                Mode::ContactOptions => self.close_contact_options(),
+                Mode::AddContact => {
+                    self.mode = Mode::Normal;
+                    self.connect_input.clear();
+                }
+                Mode::ContactInfo => {
+                    self.contact_info_data = None;
+                    self.mode = Mode::Normal;
+                }
+                _ => {}
+            },
         }
     }
     
     pub fn selected_contact(&self) -> Option<&Contact> {
+        if self.selected_section.is_some() { return None; }
         self.contact_state.selected().and_then(|i| self.contacts.get(i))
     }
     
-    pub fn next_contact(&mut self) {
-        let len = self.contacts.len();
-        if len == 0 { return; }
-        let i = self.contact_state.selected().map(|i| (i + 1) % len).unwrap_or(0);
+    /// The contact list as drawn, with section headers
+    pub fn contact_rows(&self) -> Vec<Row> {
+        let pinned: Vec<i64> = self.ui_state.pinned.iter()
+            .filter(|p| Some(p.user_id) == self.user_id)
+            .map(|p| p.contact_id)
+            .collect();
+        contact_list::rows(&self.contacts, &self.contact_filter, &pinned, &self.ui_state.collapsed, |c| self.in_folder(c))
+    }
+    
+    pub fn contact_tags(&self, contact: &Contact) -> Vec<String> {
+        self.tags.of(&tags::chat_key(&contact.name, false), &contact.tag_ids)
+    }
+    
+    /// Folder tabs: all chats, then one per tag
+    pub fn folders(&self) -> Vec<Option<String>> {
+        std::iter::once(None).chain(self.tags.names().into_iter().map(Some)).collect()
+    }
+    
+    /// A folder whose tag no longer exists shows all chats
+    pub fn active_folder(&self) -> Option<String> {
+        self.ui_state.folder.clone().filter(|f| self.tags.names().contains(f))
+    }
+    
+    fn in_folder(&self, contact: &Contact) -> bool {
+        self.active_folder().is_none_or(|f| self.contact_tags(contact).contains(&f))
+    }
+    
+    pub fn folder_unread(&self, folder: &Option<String>) -> usize {
+        self.contacts.iter()
+            .filter(|c| folder.as_ref().is_none_or(|f| self.contact_tags(c).contains(f)))
+            .map(|c| c.unread)
+            .sum()
+    }
+    
+    pub fn set_folder(&mut self, folder: Option<String>) {
+        self.ui_state.folder = folder;
+        self.contact_filter_changed();
+        self.save_ui_state();
+    }
+    
+    fn switch_folder(&mut self, forward: bool) {
+        let folders = self.folders();
+        let len = folders.len();
+        if len < 2 { return; }
+        let i = folders.iter().position(|f| *f == self.active_folder()).unwrap_or(0);
+        let next = if forward { (i + 1) % len } else { (i + len - 1) % len };
+        self.set_folder(folders[next].clone());
+    }
+    
+    pub fn on_active_user(&mut self, id: i64, name: &str) {
+        let switched = self.user_id.is_some_and(|old| old != id);
+        // Before the id changes, so the open draft stays with its profile
+        if switched { self.reset_chats(); }
+        self.user_id = Some(id);
+        self.send_cmd_as("tags", &format!("/_get tags {}", id));
+        self.send_cmd_as("requests", &format!("/_get chats {} pcc=on", id));
+        if switched {
+            self.send_cmd("/contacts");
+            self.send_cmd("/groups");
+            self.send_cmd("/sa");
+            self.status = format!("Switched to {}", name);
+        }
+        if self.mode == Mode::Users { self.send_cmd("/users"); }
+    }
+    
+    /// Forgets everything that belongs to the previous user profile
+    fn reset_chats(&mut self) {
+        self.stash_draft();
+        self.current_contact = None;
+        self.messages.clear();
+        self.scroll = 0;
+        self.contacts.clear();
+        self.contact_requests.clear();
+        self.groups.clear();
+        self.select_row(None);
+        self.search_query = None;
+        self.search_hits.clear();
+        self.search_target = None;
+        self.global_search = GlobalSearch::default();
+        self.pending_jump = None;
+        self.tags.backend = None;
+        self.pending_tags = None;
+        self.invite_link = None;
+        self.invitations.clear();
+        self.invitation_selected = None;
+        self.contact_info_data = None;
+        self.verification = None;
+    }
+    
+    pub fn open_file_browser(&mut self) {
+        match ratatui_explorer::FileExplorer::new() {
+            Ok(explorer) => {
+                self.file_explorer = Some(explorer);
+                self.mode = Mode::FileBrowser;
+            }
+            Err(e) => self.status = format!("Cannot browse files: {}", e),
+        }
+    }
+    
+    pub fn open_profile_editor(&mut self) {
+        match &self.own_profile {
+            Some(profile) => {
+                self.profile_editor = Some(ProfileEditor::new(profile));
+                self.mode = Mode::Profile;
+            }
+            None => {
+                self.send_cmd("/user");
+                self.status = "Profile not loaded yet, try again".into();
+            }
+        }
+    }
+    
+    pub fn close_profile_editor(&mut self) {
+        self.profile_editor = None;
+        self.mode = Mode::Normal;
+    }
+    
+    /// Loads a picture picked in the file browser into the profile editor
+    pub fn set_profile_image(&mut self, path: &std::path::Path) {
+        self.mode = Mode::Profile;
+        let Some(editor) = self.profile_editor.as_mut() else { return };
+        let uri = image::open(path).ok()
+            .and_then(|img| thumbnail::encode_square_data_uri(&img, profile::IMAGE_PX, profile::MAX_IMAGE_LEN));
+        match uri {
+            Some(uri) => {
+                editor.set_image(Some(uri));
+                self.status = format!("Picture: {}", path.display());
+            }
+            None => self.status = format!("Cannot use {} as a picture", path.display()),
+        }
+    }
+    
+    pub fn save_profile(&mut self) {
+        let (Some(editor), Some(id)) = (&self.profile_editor, self.user_id) else { return };
+        match editor.to_profile() {
+            Ok(profile) => {
+                self.send_cmd_as("users", &format!("/_profile {} {}", id, profile));
+                self.status = "Saving profile...".into();
+                self.close_profile_editor();
+            }
+            Err(e) => self.status = format!("{:#}", e),
+        }
+    }
+    
+    pub fn open_users(&mut self) {
+        self.mode = Mode::Users;
+        self.send_cmd("/users");
+    }
+    
+    /// Makes the profile selected in the switcher the active one
+    pub fn switch_user(&mut self) {
+        let Some(user) = self.users.selected_user() else { return };
+        if !user.active {
+            let cmd = format!("/_user {}", user.id);
+            self.send_cmd(&cmd);
+            self.status = "Switching profile...".into();
+        }
+        self.mode = Mode::Normal;
+    }
+    
+    /// Deletes the selected profile on the second call
+    pub fn delete_user(&mut self) {
+        let Some(user) = self.users.selected_user().cloned() else { return };
+        if user.active {
+            self.status = "Switch to another profile before deleting this one".into();
+        } else if user.hidden {
+            self.start_command("user delete ");
+            self.status = format!("Type the password of {} to delete it", user.name);
+        } else if !self.users.confirm_delete {
+            self.users.confirm_delete = true;
+            self.status = format!("Press d again to delete {} and all its chats", user.name);
+        } else {
+            let cmd = format!("/_delete user {} del_smp=on", user.id);
+            self.status = format!("Deleting {}...", user.name);
+            self.users.confirm_delete = false;
+            self.send_cmd_as("users", &cmd);
+        }
+    }
+    
+    pub fn toggle_user_muted(&mut self) {
+        let Some(user) = self.users.selected_user() else { return };
+        let cmd = format!("/_{} user {}", if user.muted { "unmute" } else { "mute" }, user.id);
+        self.send_cmd_as("users", &cmd);
+    }
+    
+    /// `:user` opens the switcher; `new`, `rename`, `hide`, `unhide`,
+    /// `delete` work on the profile selected there; anything else is a
+    /// profile name and optional password to switch to, hidden ones included
+    fn run_user_command(&mut self, args: &str) {
+        let (sub, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+        let rest = rest.trim();
+        let selected = self.users.selected_user().map(|u| (u.id, u.profile.clone()));
+        match (sub, selected) {
+            ("", _) => self.open_users(),
+            ("new", _) if !rest.is_empty() => {
+                self.send_cmd(&format!("/create user '{}'", rest.trim_matches('\'')));
+                self.status = format!("Creating profile {}...", rest);
+            }
+            ("rename", Some((id, mut profile))) if !rest.is_empty() => {
+                profile["displayName"] = serde_json::Value::from(rest);
+                self.send_cmd_as("users", &format!("/_profile {} {}", id, profile));
+                self.mode = Mode::Users;
+            }
+            ("hide" | "unhide", Some((id, _))) if !rest.is_empty() => {
+                self.send_cmd_as("users", &format!("/_{} user {} {}", sub, id, serde_json::Value::from(rest)));
+                self.mode = Mode::Users;
+            }
+            ("delete", Some((id, _))) => {
+                let pwd = if rest.is_empty() { String::new() } else { format!(" {}", serde_json::Value::from(rest)) };
+                self.send_cmd_as("users", &format!("/_delete user {} del_smp=on{}", id, pwd));
+                self.mode = Mode::Users;
+            }
+            ("new" | "rename" | "hide" | "unhide", _) => self.status = format!(":user {} needs an argument", sub),
+            ("delete", None) => self.status = "No profile selected; open :user first".into(),
+            (name, _) => {
+                let name = name.trim_matches('\'');
+                self.send_cmd(format!("/user '{}' {}", name, rest).trim_end());
+                self.status = format!("Switching to {}...", name);
+            }
+        }
+    }
+    
+    pub fn on_chat_tags(&mut self, tags: Option<Vec<ChatTag>>) {
+        self.tags.backend = tags;
+        if let Some((group, id, names)) = self.pending_tags.take() {
+            self.send_chat_tags(group, id, &names);
+        }
+    }
+    
+    /// `:tag [@contact|#group] <tag>` and `:untag ...`; the chat defaults to
+    /// the selected contact, or the open one outside the contact list
+    fn run_tag_command(&mut self, add: bool, args: &str) {
+        let (chat, tag) = split_chat_arg(args);
+        let Some((name, group)) = chat.or_else(|| self.command_target().map(|n| (n, false))) else {
+            self.status = "No contact selected".into();
+            return;
+        };
+        let id = if group {
+            self.groups.iter().find(|g| g.name == name).map(|g| (g.id, g.tag_ids.clone()))
+        } else {
+            self.contacts.iter().find(|c| c.name == name).map(|c| (c.id, c.tag_ids.clone()))
+        };
+        let Some((id, tag_ids)) = id else {
+            self.status = format!("No {} named {}", if group { "group" } else { "contact" }, name);
+            return;
+        };
+        let key = tags::chat_key(&name, group);
+        let mut current = self.tags.of(&key, &tag_ids);
+        if tag.is_empty() {
+            self.status = if current.is_empty() { format!("{} has no tags", name) } else { format!("Tags of {}: {}", name, current.join(", ")) };
+            return;
+        }
+        if add {
+            if current.iter().any(|t| t == tag) { return; }
+            current.push(tag.to_string());
+            self.status = format!("Tagged {}: {}", name, tag);
+        } else {
+            if !current.iter().any(|t| t == tag) {
+                self.status = format!("{} is not tagged {}", name, tag);
+                return;
+            }
+            current.retain(|t| t != tag);
+            self.status = format!("Removed tag {} from {}", tag, name);
+        }
+        
+        if self.tags.backend.is_none() {
+            if let Err(e) = self.tags.set_local(&key, current) {
+                self.status = format!("Cannot save tags: {:#}", e);
+            }
+            return;
+        }
+        // New tags are created first; the chat is tagged once their ids arrive
+        let missing: Vec<&String> = current.iter().filter(|t| self.tags.id(t).is_none()).collect();
+        if missing.is_empty() {
+            self.send_chat_tags(group, id, &current);
+            return;
+        }
+        for tag in missing {
+            self.send_cmd(&format!("/_create tag {}", serde_json::json!({ "text": tag })));
+        }
+        self.pending_tags = Some((group, id, current));
+    }
+    
+    /// The selected contact in the contact list, otherwise the open chat
+    fn command_target(&self) -> Option<String> {
+        let selected = self.selected_contact().filter(|_| self.panel == Panel::Contacts).map(|c| c.name.clone());
+        selected.or_else(|| self.current_contact.clone())
+    }
+    
+    /// `:alias [@contact] [alias]`; no alias clears it
+    fn run_alias_command(&mut self, args: &str) {
+        let (chat, alias) = split_chat_arg(args);
+        let Some(name) = chat.filter(|(_, group)| !group).map(|(n, _)| n).or_else(|| self.command_target()) else {
+            self.status = "No contact selected".into();
+            return;
+        };
+        let Some(contact) = self.contacts.iter().find(|c| c.name == name) else {
+            self.status = format!("No contact named {}", name);
+            return;
+        };
+        self.send_cmd(format!("/_set alias @{} {}", contact.id, alias).trim_end());
+        self.status = format!("Setting alias for {}...", name);
+    }
+    
+    pub fn on_security_code(&mut self, contact: Contact, code: String) {
+        self.verification = Some(Verification {
+            contact: contact.name.clone(),
+            contact_id: contact.id,
+            code,
+            input: String::new(),
+            matched: None,
+            clearing: false,
+        });
+        if let Some(c) = self.contacts.iter_mut().find(|c| c.id == contact.id) { c.verified = contact.verified; }
+        self.mode = Mode::Verify;
+        self.status = format!("Security code for {}", contact.name);
+    }
+    
+    /// Sends the typed code for comparison, or our own code once both sides
+    /// compared them by eye
+    pub fn submit_verification(&mut self, typed: bool) {
+        let Some(v) = self.verification.as_mut() else { return };
+        let source = if typed { &v.input } else { &v.code };
+        let code: String = source.chars().filter(|c| c.is_ascii_digit()).collect();
+        if code.is_empty() {
+            self.status = "Type or paste the contact's security code".into();
+            return;
+        }
+        v.clearing = false;
+        let cmd = format!("/_verify code @{} {}", v.contact_id, code);
+        self.send_cmd(&cmd);
+    }
+    
+    pub fn clear_verification(&mut self) {
+        let Some(v) = self.verification.as_mut() else { return };
+        v.clearing = true;
+        v.matched = None;
+        let cmd = format!("/_verify code @{}", v.contact_id);
+        self.send_cmd(&cmd);
+    }
+    
+    pub fn on_code_verified(&mut self, verified: bool, expected: &str) {
+        let Some(v) = self.verification.as_mut() else { return };
+        let name = v.contact.clone();
+        let id = v.contact_id;
+        let clearing = std::mem::take(&mut v.clearing);
+        if !clearing { v.matched = Some(verified); }
+        if !expected.is_empty() { v.code = expected.to_string(); }
+        if let Some(c) = self.contacts.iter_mut().find(|c| c.id == id) { c.verified = verified; }
+        self.status = match (clearing, verified) {
+            (true, _) => format!("{} is no longer marked verified", name),
+            (false, true) => format!("✓ {} verified", name),
+            (false, false) => "Security codes don't match".into(),
+        };
+    }
+    
+    /// Random profile name of an incognito contact, from `/_info`
+    pub fn on_incognito_profile(&mut self, contact_id: i64, name: String) {
+        if let Some(contact) = self.contacts.iter_mut().find(|c| c.id == contact_id) {
+            contact.incognito_name = Some(name);
+        }
+    }
+    
+    /// Sends our address to an incognito contact so they can reach the
+    /// main profile; the incognito connection itself stays as it is
+    pub fn share_main_profile(&mut self, name: &str) {
+        let Some(contact) = self.contacts.iter().find(|c| c.name == name) else { return };
+        if !contact.incognito {
+            self.status = format!("{} already knows your main profile", name);
+            return;
+        }
+        let Some(link) = self.invite_link.clone() else {
+            self.status = "No address to share; create one in Add Contact".into();
+            return;
+        };
+        let content = serde_json::json!([{ "msgContent": { "type": "text", "text": link } }]);
+        let cmd = format!("/_send @{} json {}", contact.id, content);
+        self.send_cmd(&cmd);
+        self.status = format!("Sent your address to {}", name);
+    }
+    
+    /// Keeps the unread count, which the backend doesn't track for us
+    pub fn on_contact_updated(&mut self, mut contact: Contact) {
+        let Some(old) = self.contacts.iter_mut().find(|c| c.id == contact.id) else { return };
+        contact.unread = old.unread;
+        contact.incognito_name = old.incognito_name.take();
+        self.status = if contact.alias != old.alias && contact.alias.is_empty() {
+            format!("Alias of {} cleared", contact.name)
+        } else if contact.alias != old.alias {
+            format!("{} is now shown as {}", contact.name, contact.alias)
+        } else if contact.prefs.state("timedMessages") != old.prefs.state("timedMessages") {
+            format!("Disappearing messages with {}: {}", contact.name, timed::describe(contact.prefs.state("timedMessages")))
+        } else {
+            format!("Preferences of {} updated", contact.name)
+        };
+        *old = contact;
+    }
+    
+    /// `:disappear [@contact] off|<time>` sets our timed-message preference
+    /// for the contact; the contact has to allow it too
+    fn run_disappear_command(&mut self, args: &str) {
+        let (target, rest) = split_chat_arg(args);
+        let name = match target {
+            Some((name, false)) => name,
+            Some((_, true)) => { self.status = "Disappearing messages are set per contact".into(); return; }
+            None => match self.command_target() { Some(name) => name, None => { self.status = ":disappear needs a contact".into(); return; } },
+        };
+        let Some(contact) = self.contacts.iter().find(|c| c.name == name) else {
+            self.status = format!("No contact named {}", name);
+            return;
+        };
+        let ttl = match timed::parse_ttl(rest) {
+            Ok(ttl) => ttl,
+            Err(e) => { self.status = format!("{:#}", e); return; }
+        };
+        let mut prefs = contact.prefs.user.clone();
+        if !prefs.is_object() { prefs = serde_json::json!({}); }
+        prefs["timedMessages"] = match ttl {
+            Some(ttl) => serde_json::json!({ "allow": "yes", "ttl": ttl }),
+            None => serde_json::json!({ "allow": "no" }),
+        };
+        let cmd = format!("/_set prefs @{} {}", contact.id, prefs);
+        self.send_cmd(&cmd);
+        self.status = format!("Setting disappearing messages for {}...", name);
+    }
+    
+    /// Sends our side of the chat preferences; the modal stays open and
+    /// shows the outcome once `contactPrefsUpdated` arrives
+    pub fn save_contact_prefs(&mut self) {
+        let Some(editor) = &self.prefs_editor else { return };
+        let Some(contact) = self.contacts.iter().find(|c| c.id == editor.contact_id) else { return };
+        let cmd = format!("/_set prefs @{} {}", contact.id, editor.to_prefs(contact));
+        self.status = format!("Saving preferences for {}...", contact.name);
+        self.send_cmd(&cmd);
+    }
+    
+    pub fn close_contact_prefs(&mut self) {
+        self.prefs_editor = None;
+        self.mode = Mode::Normal;
+    }
+    
+    /// Drops deleted items from the open chat; local echoes without an id
+    /// go once their own timer ran out
+    pub fn on_items_deleted(&mut self, chat: &str, ids: &[i64]) {
+        if self.current_contact.as_deref() != Some(chat) { return; }
+        let now = Local::now();
+        let before = self.messages.len();
+        self.messages.retain(|m| match m.id {
+            Some(id) => !ids.contains(&id),
+            None => m.timer.and_then(|t| t.delete_at).is_none_or(|at| at > now),
+        });
+        if self.messages.len() != before {
+            self.search_pos = 0;
+            self.refresh_search_hits();
+        }
+    }
+    
+    fn send_chat_tags(&mut self, group: bool, id: i64, names: &[String]) {
+        let ids: Vec<i64> = names.iter().filter_map(|n| self.tags.id(n)).collect();
+        let list = ids.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(",");
+        let chat = format!("{}{}", if group { '#' } else { '@' }, id);
+        self.send_cmd(format!("/_tags {} {}", chat, list).trim_end());
+        // The response only lists the user's tags, so update the chat here
+        if group {
+            if let Some(g) = self.groups.iter_mut().find(|g| g.id == id) { g.tag_ids = ids; }
+        } else if let Some(c) = self.contacts.iter_mut().find(|c| c.id == id) {
+            c.tag_ids = ids;
+        }
+    }
+    
+    fn selected_row(&self, rows: &[Row]) -> Option<usize> {
+        rows.iter().position(|r| match (r, self.selected_section) {
+            (Row::Section(s, _), Some(selected)) => *s == selected,
+            (Row::Contact(i), None) => self.contact_state.selected() == Some(*i),
+            _ => false,
+        })
+    }
+    
+    pub fn select_row(&mut self, row: Option<Row>) {
+        match row {
+            Some(Row::Section(s, _)) => self.selected_section = Some(s),
+            Some(Row::Contact(i)) => self.select_contact_at(i),
+            None => {
+                self.selected_section = None;
+                self.contact_state.select(None);
+            }
+        }
+    }
+    
+    pub fn select_contact_at(&mut self, i: usize) {
+        self.selected_section = None;
         self.contact_state.select(Some(i));
     }
     
-    pub fn prev_contact(&mut self) {
-        let len = self.contacts.len();
+    fn move_contact_selection(&mut self, down: bool) {
+        let rows = self.contact_rows();
+        let len = rows.len();
         if len == 0 { return; }
-        let i = self.contact_state.selected().map(|i| if i == 0 { len - 1 } else { i - 1 }).unwrap_or(0);
-        self.contact_state.select(Some(i));
+        let i = match self.selected_row(&rows) {
+            Some(i) if down => (i + 1) % len,
+            Some(i) => (i + len - 1) % len,
+            None => 0,
+        };
+        self.select_row(Some(rows[i]));
+    }
+    
+    pub fn next_contact(&mut self) { self.move_contact_selection(true); }
+    pub fn prev_contact(&mut self) { self.move_contact_selection(false); }
+    
+    /// Selects the first contact left by the filter
+    pub fn contact_filter_changed(&mut self) {
+        let first = self.contact_rows().into_iter().find(|r| matches!(r, Row::Contact(_)));
+        self.select_row(first);
+    }
+    
+    /// Collapses or expands `section`, or the one holding the selection
+    pub fn toggle_section(&mut self, section: Option<Section>) {
+        let section = section.or(self.selected_section).or_else(|| {
+            let i = self.contact_state.selected()?;
+            let rows = self.contact_rows();
+            let pos = rows.iter().position(|r| *r == Row::Contact(i))?;
+            rows[..pos].iter().rev().find_map(|r| match r { Row::Section(s, _) => Some(*s), _ => None })
+        });
+        let Some(section) = section else { return };
+        let collapsed = &mut self.ui_state.collapsed;
+        match collapsed.iter().position(|c| c == section.id()) {
+            Some(pos) => { collapsed.remove(pos); }
+            None => collapsed.push(section.id().to_string()),
+        }
+        self.selected_section = Some(section);
+        self.save_ui_state();
+    }
+    
+    /// Pins are kept per user profile by contact id, so they survive renames
+    pub fn toggle_pin(&mut self) {
+        let (Some(user_id), Some((name, contact_id))) = (self.user_id, self.selected_contact().map(|c| (c.name.clone(), c.id))) else { return };
+        let pin = Pin { user_id, contact_id };
+        let pinned = &mut self.ui_state.pinned;
+        self.status = match pinned.iter().position(|p| *p == pin) {
+            Some(pos) => { pinned.remove(pos); format!("Unpinned {}", name) }
+            None => { pinned.push(pin); format!("Pinned {}", name) }
+        };
+        self.save_ui_state();
+    }
+    
+    /// Flips the backend's favorite flag
+    pub fn toggle_favorite(&mut self) {
+        let Some(i) = self.contact_state.selected().filter(|_| self.selected_section.is_none()) else { return };
+        let Some(contact) = self.contacts.get_mut(i) else { return };
+        contact.settings.favorite = !contact.settings.favorite;
+        let settings = serde_json::to_string(&contact.settings).unwrap_or_default();
+        let cmd = format!("/_settings @{} {}", contact.id, settings);
+        self.status = if contact.settings.favorite {
+            format!("Added {} to favorites", contact.name)
+        } else {
+            format!("Removed {} from favorites", contact.name)
+        };
+        self.send_cmd(&cmd);
+    }
+    
+    /// `:notify [@contact|#group] all|mentions|off`; without a level the
+    /// chat moves on to the next one
+    fn run_notify_command(&mut self, args: &str) {
+        let (target, level) = split_chat_arg(args);
+        let Some((name, group)) = target.or_else(|| self.command_target().map(|n| (n, false))) else {
+            self.status = ":notify needs a chat".into();
+            return;
+        };
+        let level = match level {
+            "" => None,
+            "all" => Some("all"),
+            "mentions" => Some("mentions"),
+            "off" | "none" | "mute" | "muted" => Some("none"),
+            other => { self.status = format!("Unknown level {}, use all, mentions or off", other); return; }
+        };
+        self.set_notifications(&name, group, level);
+    }
+    
+    /// Stores the chat's notification level in the backend's chat settings;
+    /// None moves on to the next level
+    pub fn set_notifications(&mut self, name: &str, group: bool, level: Option<&str>) {
+        let target = if group {
+            self.groups.iter_mut().find(|g| g.name == name).map(|g| (g.id, &mut g.settings))
+        } else {
+            self.contacts.iter_mut().find(|c| c.name == name).map(|c| (c.id, &mut c.settings))
+        };
+        let Some((id, settings)) = target else {
+            self.status = format!("No chat named {}", name);
+            return;
+        };
+        settings.enable_ntfs = match level {
+            Some(level) => level.to_string(),
+            None => {
+                let i = NTFS_LEVELS.iter().position(|l| *l == settings.enable_ntfs).unwrap_or(0);
+                NTFS_LEVELS[(i + 1) % NTFS_LEVELS.len()].to_string()
+            }
+        };
+        let cmd = format!("/_settings {}{} {}", if group { '#' } else { '@' }, id, serde_json::to_string(settings).unwrap_or_default());
+        self.status = match settings.enable_ntfs.as_str() {
+            "none" => format!("Muted {}", name),
+            "mentions" => format!("{}: notify on mentions only", name),
+            _ => format!("{}: notify on all messages", name),
+        };
+        self.send_cmd(&cmd);
+    }
+    
+    /// Whether the contact named `name` in the active profile is blocked;
+    /// entries hold contact ids, so renaming a contact keeps the block
+    pub fn is_blocked(&self, name: &str) -> bool {
+        let Some(contact) = self.contacts.iter().find(|c| c.name == name) else { return false };
+        self.ui_state.blocked.iter().any(|b| Some(b.user_id) == self.user_id && b.contact_id == contact.id)
+    }
+    
+    /// A new request from someone blocked, recognized by their profile name
+    pub fn is_blocked_request(&self, request: &ContactRequest) -> bool {
+        self.ui_state.blocked.iter().any(|b| Some(b.user_id) == self.user_id && b.display_name == request.display_name)
+    }
+    
+    /// Blocking hides the contact's messages and rejects their requests;
+    /// nothing is sent to them. Unblocking also takes the profile name of a
+    /// blocked contact that was deleted since
+    pub fn set_blocked(&mut self, name: &str, blocked: bool) {
+        let Some(user_id) = self.user_id else { return };
+        let contact = self.contacts.iter().find(|c| c.name == name);
+        if blocked {
+            let Some(contact) = contact else {
+                self.status = format!("No contact named {}", name);
+                return;
+            };
+            if self.is_blocked(name) {
+                self.status = format!("{} is already blocked", name);
+                return;
+            }
+            let display_name = if contact.profile.display_name.is_empty() { name.to_string() } else { contact.profile.display_name.clone() };
+            self.ui_state.blocked.push(Blocked { user_id, contact_id: contact.id, display_name });
+            self.status = format!("Blocked {}", name);
+        } else {
+            let contact_id = contact.map(|c| c.id);
+            let before = self.ui_state.blocked.len();
+            self.ui_state.blocked.retain(|b| b.user_id != user_id || match contact_id {
+                Some(id) => b.contact_id != id,
+                None => b.display_name != name,
+            });
+            if self.ui_state.blocked.len() == before {
+                self.status = format!("{} is not blocked", name);
+                return;
+            }
+            self.status = format!("Unblocked {}", name);
+        }
+        if self.current_contact.as_deref() == Some(name) {
+            if blocked { self.messages.retain(|m| m.mine); } else { self.refresh_chat(); }
+        }
+        self.save_ui_state();
+    }
+    
+    /// Whether a message for a chat that is not open should alert, by the
+    /// chat's notification level
+    pub fn should_notify(&self, sender: &str, message: &ChatMessage) -> bool {
+        let Some(contact) = self.contacts.iter().find(|c| c.name == sender) else { return true };
+        match contact.settings.enable_ntfs.as_str() {
+            "none" => false,
+            "mentions" => self.mentions_me(&message.content),
+            _ => true,
+        }
+    }
+    
+    /// Announces a message for a chat that is not open; do not disturb
+    /// keeps it to the status bar, panic mode drops it altogether
+    pub fn notify_message(&mut self, sender: &str, message: &ChatMessage) {
+        if self.mode == Mode::Panic || !self.should_notify(sender, message) { return; }
+        self.status = format!("📩 {}", sender);
+        if self.ui_state.dnd { return; }
+        let label = self.contacts.iter().find(|c| c.name == sender).map_or(sender, |c| c.label());
+        let text = if !message.content.is_empty() {
+            message.content.as_str()
+        } else if message.voice.is_some() {
+            "Voice message"
+        } else {
+            "File"
+        };
+        self.notifier.message(label, text);
+    }
+    
+    /// Keeps the window title's count in step; muted chats do not count
+    pub fn update_unread_title(&mut self) {
+        if self.mode == Mode::Panic { return; }
+        let unread = self.contacts.iter().filter(|c| c.settings.enable_ntfs != "none").map(|c| c.unread).sum();
+        self.notifier.set_unread(unread);
+    }
+    
+    /// Panic mode also gives the window title back to the terminal, so
+    /// nothing on screen points to the app until it is left
+    pub fn set_panic(&mut self, on: bool) {
+        if on {
+            self.mode = Mode::Panic;
+            self.notifier.restore_title();
+        } else {
+            self.mode = Mode::Normal;
+            self.update_unread_title();
+        }
+    }
+    
+    pub fn set_dnd(&mut self, on: bool) {
+        self.ui_state.dnd = on;
+        self.status = if on { "Do not disturb: on" } else { "Do not disturb: off" }.into();
+        self.save_ui_state();
+    }
+    
+    fn mentions_me(&self, text: &str) -> bool {
+        let Some(name) = self.own_profile.as_ref().and_then(|p| p.get("displayName")).and_then(|n| n.as_str()) else { return false };
+        text.contains(&format!("@{}", name)) || text.contains(&format!("@'{}'", name))
+    }
+    
+    fn save_ui_state(&mut self) {
+        if let Err(e) = self.ui_state.save() {
+            self.status = format!("Cannot save UI state: {:#}", e);
+        }
     }
     
     pub fn cycle_panel(&mut self) {
@@ -118,19 +1128,144 @@ impl App {
         self.mode = if self.panel == Panel::Input { Mode::Input } else { Mode::Normal };
     }
     
+    /// Sets the contact pane width, remembering it when `save` is set
+    pub fn resize_contacts(&mut self, width: u16, save: bool) {
+        self.ui_state.contacts_width = width.clamp(MIN_CONTACTS_WIDTH, MAX_CONTACTS_WIDTH);
+        if save { self.save_ui_state(); }
+    }
+    
     pub fn send_cmd(&self, cmd: &str) {
         if let Some(tx) = &self.cmd_tx {
-            let _ = tx.send(cmd.to_string());
+            let _ = tx.send(ApiCommand::new(cmd));
         }
     }
     
+    /// Sends a command whose response is routed by its corrId
+    fn send_cmd_as(&self, corr_id: &str, cmd: &str) {
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(ApiCommand::with_id(corr_id.to_string(), cmd));
+        }
+    }
+    
+    /// Sends a command typed by the user and shows its response in the console
+    pub fn send_raw_cmd(&mut self, cmd: &str) {
+        let corr_id = self.console.push(cmd);
+        if let Some(tx) = &self.cmd_tx {
+            let _ = tx.send(ApiCommand::with_id(corr_id, cmd));
+        }
+        self.mode = Mode::Console;
+    }
+    
+    pub fn start_command(&mut self, text: &str) {
+        self.command.start(text);
+        self.mode = Mode::Command;
+    }
+    
+    /// Runs the command line: `/...` goes to simplex-chat unchanged,
+    /// anything else is a client command
+    pub fn run_command(&mut self) {
+        self.mode = Mode::Normal;
+        let line = self.command.submit();
+        let line = line.trim_start_matches(':');
+        if line.is_empty() { return; }
+        if line.starts_with('/') {
+            self.send_raw_cmd(line);
+            return;
+        }
+        
+        let (name, raw_arg) = line.split_once(' ').unwrap_or((line, ""));
+        let arg = raw_arg.trim().trim_matches(['\'', '"']).trim_start_matches('@');
+        let target = if arg.is_empty() { self.current_contact.clone() } else { Some(arg.to_string()) };
+        match (name, target) {
+            ("open" | "o", Some(contact)) => self.open_contact(&contact),
+            ("info", Some(contact)) => self.get_contact_info(&contact),
+            ("accept" | "ac", _) if !arg.is_empty() => self.accept_request(raw_arg),
+            ("reject" | "rc", _) if !arg.is_empty() => self.reject_request(arg),
+            ("refresh" | "r", _) => { self.refresh_chat(); self.send_cmd("/contacts"); }
+            ("console", _) => { self.console.visible = true; self.mode = Mode::Console; }
+            ("search", _) => self.start_global_search(arg),
+            ("tag" | "untag", _) => self.run_tag_command(name == "tag", raw_arg),
+            ("alias", _) => self.run_alias_command(raw_arg),
+            ("qr", _) => self.export_qr(raw_arg.trim()),
+            ("user" | "u", _) => self.run_user_command(raw_arg),
+            ("profile", _) => self.open_profile_editor(),
+            ("disappear", _) => self.run_disappear_command(raw_arg),
+            ("notify", _) => self.run_notify_command(raw_arg),
+            ("dnd", _) => match arg {
+                "" => self.set_dnd(!self.ui_state.dnd),
+                "on" => self.set_dnd(true),
+                "off" => self.set_dnd(false),
+                _ => self.status = ":dnd takes on or off".into(),
+            },
+            ("block" | "unblock", Some(contact)) => self.set_blocked(&contact, name == "block"),
+            ("theme", _) if arg.is_empty() => {
+                self.status = format!("Theme {} (available: {})", colors::theme_name(), colors::theme_names().join(", "));
+            }
+            ("theme", _) => match colors::load(arg) {
+                Ok(theme) => {
+                    colors::set_theme(theme);
+                    self.status = format!("Theme: {}", arg);
+                }
+                Err(e) => self.status = format!("{:#}", e),
+            },
+            ("help", _) => self.toggle_help(),
+            ("quit" | "q", _) => self.running = false,
+            ("open" | "o" | "info" | "accept" | "ac" | "reject" | "rc" | "block" | "unblock", _) => self.status = format!(":{} needs a contact name", name),
+            _ => self.status = format!("Unknown command: {}", name),
+        }
+    }
+    
+    /// Completes the command line with contact and group names, or theme
+    /// names after `:theme` and tag names after `:tag <chat>`
+    pub fn complete_command(&mut self) {
+        let line = self.command.input.text();
+        let line = line.trim_start_matches(':');
+        if line.starts_with("theme ") {
+            let themes = colors::theme_names();
+            self.command.complete(&themes.iter().map(String::as_str).collect::<Vec<_>>());
+            return;
+        }
+        if (line.starts_with("tag ") || line.starts_with("untag ")) && line.split(' ').count() > 2 {
+            let names = self.tags.names();
+            self.command.complete(&names.iter().map(String::as_str).collect::<Vec<_>>());
+            return;
+        }
+        let names: Vec<&str> = self.contacts.iter().map(|c| c.name.as_str())
+            .chain(self.groups.iter().map(|g| g.name.as_str()))
+            .collect();
+        self.command.complete(&names);
+    }
+    
+    pub fn open_contact(&mut self, name: &str) {
+        let Some(i) = self.contacts.iter().position(|c| c.name.eq_ignore_ascii_case(name)) else {
+            self.status = format!("No contact named {}", name);
+            return;
+        };
+        self.select_contact_at(i);
+        self.select_contact();
+        self.panel = Panel::Input;
+        self.mode = Mode::Input;
+    }
+    
     pub fn select_contact(&mut self) {
         if let Some(contact) = self.selected_contact() {
             let name = contact.name.clone();
+            self.stash_draft();
+            let draft = self.chat_key(&name).map(|key| self.drafts.draft(key).to_string()).unwrap_or_default();
+            self.composer.set_text(&draft);
+            self.history_pos = None;
+            self.compose_preview = None;
+            self.preview_fetch = None;
+            self.preview_dismissed = None;
             self.current_contact = Some(name.clone());
             self.message_limit = 50;
             self.messages.clear();
             self.scroll = 0;
+            self.search_query = None;
+            self.search_hits.clear();
+            self.search_target = None;
+            self.search_reload = false;
+            self.pending_jump = None;
             
             if let Some(c) = self.contacts.iter_mut().find(|c| c.name == name) {
                 c.unread = 0;
@@ -138,19 +1273,24 @@ impl App {
             
             self.status = format!("Opening {}...", name);
             self.send_cmd(&format!("/tail @'{}' 50", name));
+            if let Some(c) = self.contacts.iter().find(|c| c.name == name && c.incognito && c.incognito_name.is_none()) {
+                let cmd = format!("/_info @{}", c.id);
+                self.send_cmd_as("incognito", &cmd);
+            }
         }
     }
     
-    pub fn check_double_click(&mut self, contact_index: usize) -> bool {
+    /// True when the same region was clicked just before
+    pub fn check_double_click(&mut self, hit: &Hit) -> bool {
         let now = Instant::now();
-        let is_double = if let (Some(last_time), Some(last_idx)) = (self.last_click_time, self.last_click_index) {
-            last_idx == contact_index && now.duration_since(last_time).as_millis() < DOUBLE_CLICK_MS
+        let is_double = if let (Some(last_time), Some(last)) = (self.last_click_time, &self.last_click) {
+            last == hit && now.duration_since(last_time).as_millis() < DOUBLE_CLICK_MS
         } else {
             false
         };
         
         self.last_click_time = Some(now);
-        self.last_click_index = Some(contact_index);
+        self.last_click = Some(hit.clone());
         
         is_double
     }
@@ -183,9 +1323,10 @@ impl App {
         let options = ContactOption::all();
         let option = options.get(self.option_selection).copied().unwrap_or(ContactOption::Cancel);
         
-        if option.is_destructive() && self.confirm_action.is_none() {
+        if option.is_destructive() && self.confirm_action != Some(option) {
             self.confirm_action = Some(option);
-            self.status = format!("Press Enter again to confirm {}", option.label());
+            let key = self.keymap.first_key(Action::RunOption).unwrap_or_else(|| "Enter".into());
+            self.status = format!("Press {} again to confirm {}", key, option.label());
         } else {
             self.execute_option(option);
         }
@@ -203,6 +1344,47 @@ impl App {
                 ContactOption::ContactInfo => {
                     self.get_contact_info(contact_name);
                 }
+                ContactOption::Verify => {
+                    let id = self.contacts.iter().find(|c| &c.name == contact_name).map(|c| c.id);
+                    self.close_contact_options();
+                    if let Some(id) = id {
+                        self.send_cmd(&format!("/_get code @{}", id));
+                        self.status = format!("Loading security code for {}...", contact_name);
+                    }
+                }
+                ContactOption::SetAlias => {
+                    let alias = self.contacts.iter().find(|c| &c.name == contact_name).map(|c| c.alias.clone()).unwrap_or_default();
+                    self.close_contact_options();
+                    self.start_command(&format!("alias {} {}", chat_arg(contact_name, false), alias));
+                }
+                ContactOption::Disappearing => {
+                    let ttl = self.contacts.iter().find(|c| &c.name == contact_name).and_then(|c| c.prefs.timed_ttl());
+                    self.close_contact_options();
+                    let current = ttl.map_or("1d".to_string(), timed::format_ttl);
+                    self.start_command(&format!("disappear {} {}", chat_arg(contact_name, false), current));
+                }
+                ContactOption::Notifications => {
+                    let level = self.contacts.iter().find(|c| &c.name == contact_name).map(|c| c.settings.enable_ntfs.clone()).unwrap_or_default();
+                    self.close_contact_options();
+                    self.start_command(&format!("notify {} {}", chat_arg(contact_name, false), level));
+                }
+                ContactOption::Block => {
+                    let blocked = self.is_blocked(contact_name);
+                    self.close_contact_options();
+                    self.set_blocked(contact_name, !blocked);
+                }
+                ContactOption::Preferences => {
+                    let editor = self.contacts.iter().find(|c| &c.name == contact_name).map(PrefsEditor::new);
+                    self.close_contact_options();
+                    if let Some(editor) = editor {
+                        self.prefs_editor = Some(editor);
+                        self.mode = Mode::ContactPrefs;
+                    }
+                }
+                ContactOption::ShareProfile => {
+                    self.close_contact_options();
+                    self.share_main_profile(contact_name);
+                }
                 ContactOption::Cancel => {
                     self.close_contact_options();
                 }
@@ -249,6 +1431,7 @@ impl App {
     }
     
     pub fn on_contact_deleted(&mut self, name: &str) {
+        if let Some(key) = self.chat_key(name) { self.drafts.remove(key); }
         self.contacts.retain(|c| c.name != name);
         
         if self.contact_state.selected().unwrap_or(0) >= self.contacts.len() && !self.contacts.is_empty() {
@@ -269,27 +1452,90 @@ impl App {
     }
     
     pub fn send_message(&mut self) {
-        if self.input.is_empty() { return; }
+        if self.composer.text().trim().is_empty() { return; }
         
-        if let Some(contact) = self.selected_contact() {
-            let cmd = format!("@'{}' {}", contact.name, self.input);
+        // The open chat, not whatever is highlighted in the contact list
+        let contact = self.current_contact.as_ref()
+            .and_then(|name| self.contacts.iter().find(|c| &c.name == name))
+            .cloned();
+        if let Some(contact) = contact {
+            let text = self.composer.take();
+            self.history_pos = None;
+            if let Some(user) = self.user_id { self.drafts.push_sent((user, contact.id), &text); }
+            self.save_drafts();
+            let link = self.compose_preview.take().filter(|p| text.contains(&p.uri));
+            // Multi-line text has to go through the JSON API, the CLI syntax is line based
+            let cmd = if link.is_some() || text.contains('\n') {
+                let mut content = serde_json::json!({ "type": "text", "text": text });
+                if let Some(preview) = &link {
+                    content = serde_json::json!({ "type": "link", "text": text, "preview": preview.to_json() });
+                }
+                format!("/_send @{} json {}", contact.id, serde_json::json!([{ "msgContent": content }]))
+            } else {
+                format!("@'{}' {}", contact.name, text)
+            };
             self.send_cmd(&cmd);
             
             self.messages.push(ChatMessage {
+                id: None,
                 sender: "You".into(),
-                content: self.input.clone(),
+                content: text,
                 time: Local::now().format("%H:%M").to_string(),
                 mine: true,
                 status: MessageStatus::Sending,
+                voice: None,
+                link,
+                // Sent messages start their countdown right away
+                timer: contact.prefs.timed_ttl().map(|ttl| Timer { ttl, delete_at: Some(Local::now() + chrono::Duration::seconds(ttl)) }),
             });
             
-            self.input.clear();
-            self.cursor = 0;
+            self.preview_fetch = None;
+            self.preview_dismissed = None;
             self.auto_scroll();
             self.status = "Sending...".into();
         }
     }
     
+    /// Keeps the composer text as the open chat's draft
+    fn stash_draft(&mut self) {
+        if let Some(key) = self.current_contact.as_deref().and_then(|name| self.chat_key(name)) {
+            self.drafts.set_draft(key, self.composer.text());
+        }
+    }
+    
+    /// Drafts key of a contact in the active profile
+    fn chat_key(&self, name: &str) -> Option<ChatKey> {
+        Some((self.user_id?, self.contacts.iter().find(|c| c.name == name)?.id))
+    }
+    
+    pub fn save_drafts(&mut self) {
+        self.stash_draft();
+        if let Err(e) = self.drafts.save() {
+            self.status = format!("Drafts not saved: {}", e);
+        }
+    }
+    
+    /// Up/Down through sent messages, only from an empty composer or while
+    /// already browsing them
+    pub fn recall_history(&mut self, older: bool) {
+        let Some(key) = self.current_contact.as_deref().and_then(|name| self.chat_key(name)) else { return };
+        let history = self.drafts.history(key);
+        let browsing = self.history_pos
+            .filter(|&i| history.get(i).map(String::as_str) == Some(self.composer.text()));
+        if browsing.is_none() && !self.composer.is_empty() { return; }
+        
+        let pos = match (browsing, older) {
+            (None, true) if !history.is_empty() => Some(history.len() - 1),
+            (Some(i), true) if i > 0 => Some(i - 1),
+            (Some(i), false) if i + 1 < history.len() => Some(i + 1),
+            (Some(_), false) => None,
+            _ => return,
+        };
+        let text = pos.map(|i| history[i].clone()).unwrap_or_default();
+        self.composer.set_text(&text);
+        self.history_pos = pos;
+    }
+    
     pub fn request_address(&mut self) {
         self.send_cmd("/sa");
         self.status = "Loading address...".into();
@@ -302,6 +1548,108 @@ impl App {
         self.status = "Creating new address...".into();
     }
     
+    /// Lists pending one-time links; needs the user id from `/user`
+    pub fn request_invitations(&mut self) {
+        if let Some(id) = self.user_id {
+            self.send_cmd_as("invitations", &format!("/_get chats {} pcc=on", id));
+        }
+    }
+    
+    pub fn create_invitation(&mut self) {
+        self.send_cmd(if self.invite_incognito { "/c incognito" } else { "/c" });
+        self.status = "Creating one-time link...".into();
+    }
+    
+    /// The one-time link or address shown in Add Contact
+    pub fn shown_link(&self) -> Option<&str> {
+        match self.invitation_selected {
+            Some(i) => self.invitations.get(i).map(|inv| inv.link.as_str()),
+            None => self.invite_link.as_deref(),
+        }
+    }
+    
+    /// Selects a row of the Add Contact link list: 0 is the address
+    pub fn select_link(&mut self, row: usize) {
+        self.invitation_selected = row.checked_sub(1).map(|i| i.min(self.invitations.len().saturating_sub(1)));
+        if self.invitations.is_empty() { self.invitation_selected = None; }
+    }
+    
+    /// Flips incognito of the selected one-time link, or of new connections
+    /// when the address is selected
+    pub fn toggle_incognito(&mut self) {
+        match self.invitation_selected.and_then(|i| self.invitations.get(i)) {
+            Some(inv) => {
+                let cmd = format!("/_set incognito :{} {}", inv.conn_id, if inv.incognito { "off" } else { "on" });
+                self.send_cmd(&cmd);
+            }
+            None => {
+                self.invite_incognito = !self.invite_incognito;
+                self.status = format!("Connect incognito: {}", if self.invite_incognito { "on" } else { "off" });
+            }
+        }
+    }
+    
+    pub fn cancel_invitation(&mut self) {
+        match self.invitation_selected.and_then(|i| self.invitations.get(i)) {
+            Some(inv) => {
+                let cmd = format!("/_delete :{}", inv.conn_id);
+                self.send_cmd(&cmd);
+                self.status = "Cancelling one-time link...".into();
+            }
+            None => self.status = "Select a one-time link to cancel".into(),
+        }
+    }
+    
+    pub fn on_invitation(&mut self, invitation: Invitation) {
+        self.status = format!("One-time link ready{}", if invitation.incognito { " (incognito)" } else { "" });
+        self.invitations.retain(|i| i.conn_id != invitation.conn_id);
+        self.invitations.push(invitation);
+        self.invitation_selected = Some(self.invitations.len() - 1);
+    }
+    
+    /// Keeps the selected link selected when it is still pending
+    pub fn on_invitations(&mut self, invitations: Vec<Invitation>) {
+        let selected = self.invitation_selected.and_then(|i| self.invitations.get(i)).map(|i| i.conn_id);
+        self.invitations = invitations;
+        self.invitation_selected = selected.and_then(|id| self.invitations.iter().position(|i| i.conn_id == id));
+    }
+    
+    pub fn on_invitation_updated(&mut self, invitation: Invitation) {
+        self.status = format!("One-time link: incognito {}", if invitation.incognito { "on" } else { "off" });
+        if let Some(existing) = self.invitations.iter_mut().find(|i| i.conn_id == invitation.conn_id) {
+            *existing = invitation;
+        }
+    }
+    
+    pub fn on_invitation_deleted(&mut self, conn_id: i64) {
+        let Some(pos) = self.invitations.iter().position(|i| i.conn_id == conn_id) else { return };
+        self.invitations.remove(pos);
+        self.invitation_selected = match self.invitation_selected {
+            Some(i) if i > pos => Some(i - 1),
+            Some(i) if i == pos => pos.checked_sub(1).or((!self.invitations.is_empty()).then_some(0)),
+            other => other,
+        };
+        self.status = "One-time link cancelled".into();
+    }
+    
+    /// Writes the link shown in Add Contact as a PNG or SVG image
+    pub fn export_qr(&mut self, path: &str) {
+        let Some(link) = self.shown_link().map(str::to_string) else {
+            self.status = "No address loaded; open Add Contact first".into();
+            return;
+        };
+        let path = match (path.strip_prefix("~/"), std::env::var("HOME")) {
+            (Some(rest), Ok(home)) => PathBuf::from(home).join(rest),
+            _ if path.is_empty() => PathBuf::from("simplex-address.png"),
+            _ => PathBuf::from(path),
+        };
+        self.status = match qr::export(&link, &path) {
+            Ok(()) => format!("QR code saved to {}", path.display()),
+            Err(e) => format!("{:#}", e),
+        };
+        self.mode = Mode::AddContact;
+    }
+    
     pub fn finish_create_address(&mut self) {
         if self.pending_new_address {
             self.pending_new_address = false;
@@ -309,13 +1657,40 @@ impl App {
         }
     }
     
+    /// `:accept [incognito|-i] <name>`; without the flag the Add Contact
+    /// incognito toggle decides
+    pub fn accept_request(&mut self, args: &str) {
+        let (incognito, name) = match args.trim().split_once(' ') {
+            Some(("incognito" | "-i", rest)) => (true, rest),
+            _ => (self.invite_incognito, args),
+        };
+        let name = name.trim().trim_matches(['\'', '"']).trim_start_matches('@');
+        let Some(pos) = self.contact_requests.iter().position(|r| r.name == name) else {
+            self.status = format!("No contact request from {}", name);
+            return;
+        };
+        let request = self.contact_requests.remove(pos);
+        self.send_cmd(&format!("/_accept {}{}", if incognito { "incognito=on " } else { "" }, request.id));
+        self.status = if incognito { format!("Accepting {} incognito...", name) } else { format!("Accepting {}...", name) };
+    }
+    
+    pub fn reject_request(&mut self, name: &str) {
+        let Some(pos) = self.contact_requests.iter().position(|r| r.name == name) else {
+            self.status = format!("No contact request from {}", name);
+            return;
+        };
+        let request = self.contact_requests.remove(pos);
+        self.send_cmd(&format!("/_reject {}", request.id));
+        self.status = format!("Rejected {}", name);
+    }
+    
     pub fn connect_to_invite(&mut self) {
         if self.connect_input.is_empty() { return; }
         
         let link = self.connect_input.trim().to_string();
-        let cmd = format!("/c {}", link);
+        let cmd = format!("/c {}{}", if self.invite_incognito { "incognito " } else { "" }, link);
         self.send_cmd(&cmd);
-        self.status = "Connecting...".into();
+        self.status = if self.invite_incognito { "Connecting incognito...".into() } else { "Connecting...".into() };
         self.connect_input.clear();
         self.mode = Mode::Normal;
     }
@@ -332,17 +1707,549 @@ impl App {
         self.scroll = self.scroll.saturating_sub(1);
     }
     pub fn scroll_down(&mut self) { self.scroll = self.scroll.saturating_add(1); }
+    pub fn scroll_to_top(&mut self) { self.scroll = 0; }
+    pub fn toggle_help(&mut self) {
+        self.show_help = !self.show_help;
+        self.help_scroll = 0;
+    }
+    
+    /// Scrolls the help screen by `lines`, up when negative
+    pub fn scroll_help(&mut self, lines: isize) {
+        self.help_scroll = self.help_scroll.saturating_add_signed(lines);
+    }
+    
+    pub fn scroll_half_page(&mut self, down: bool) {
+        let half = (self.chat_view_height / 2).max(1);
+        self.scroll = if down { self.scroll.saturating_add(half) } else { self.scroll.saturating_sub(half) };
+    }
+    
+    /// Scrolls to a message on the next frame, after the chat is laid out
+    pub fn jump_to_message(&mut self, index: usize) {
+        self.pending_jump = Some(index);
+    }
+    
+    pub fn start_chat_search(&mut self) {
+        self.search_input.clear();
+        self.mode = Mode::ChatSearch;
+    }
+    
+    /// Searches the loaded messages, newest first, and asks the backend for
+    /// older matches in the same chat
+    pub fn run_chat_search(&mut self) {
+        self.mode = Mode::Normal;
+        let query = self.search_input.trim().to_string();
+        self.search_reload = false;
+        if query.is_empty() {
+            self.search_query = None;
+            self.search_hits.clear();
+            return;
+        }
+        self.search_query = Some(query.clone());
+        self.refresh_search_hits();
+        self.show_search_hit(0);
+        
+        if let Some(contact) = self.current_contact.as_ref().and_then(|n| self.contacts.iter().find(|c| &c.name == n)) {
+            self.send_cmd_as("search-chat", &format!("/_get chat @{} count={} search={}", contact.id, search::SEARCH_LIMIT, query));
+        }
+    }
+    
+    fn refresh_search_hits(&mut self) {
+        let Some(query) = &self.search_query else { return };
+        self.search_hits = self.messages.iter().enumerate().rev()
+            .filter(|(_, m)| !search::find_matches(&m.content, query).is_empty())
+            .map(|(i, _)| i)
+            .collect();
+    }
+    
+    fn show_search_hit(&mut self, pos: usize) {
+        let Some(query) = self.search_query.clone() else { return };
+        self.search_pos = pos.min(self.search_hits.len().saturating_sub(1));
+        match self.search_hits.get(self.search_pos).copied() {
+            Some(i) => {
+                self.jump_to_message(i);
+                self.status = format!("/{}: {} of {}", query, self.search_pos + 1, self.search_hits.len());
+            }
+            None => self.status = format!("/{}: no match", query),
+        }
+    }
+    
+    /// n goes to the next older hit, N back to newer ones
+    pub fn next_search_hit(&mut self, older: bool) {
+        let len = self.search_hits.len();
+        if len == 0 { return; }
+        self.show_search_hit(if older { (self.search_pos + 1) % len } else { (self.search_pos + len - 1) % len });
+    }
+    
+    /// Loads more of the open chat so hits outside the loaded history can
+    /// be shown; false when the limit is already reached
+    fn load_more_history(&mut self) -> bool {
+        if self.message_limit >= search::HISTORY_LIMIT { return false; }
+        let Some(name) = self.current_contact.clone() else { return false };
+        self.message_limit = search::HISTORY_LIMIT;
+        self.send_cmd(&format!("/tail @'{}' {}", name, self.message_limit));
+        true
+    }
+    
+    pub fn on_search_results(&mut self, global: bool, hits: Vec<SearchHit>) {
+        if global {
+            self.status = format!("{}: {} result{}", self.global_search.query, hits.len(), if hits.len() == 1 { "" } else { "s" });
+            let gs = &mut self.global_search;
+            gs.loading = false;
+            gs.selected = 0;
+            gs.hits = hits;
+            return;
+        }
+        let Some(query) = self.search_query.clone() else { return };
+        let missing = hits.iter().filter(|h| !self.messages.iter().any(|m| m.id == Some(h.item_id))).count();
+        if missing > 0 && self.load_more_history() {
+            self.search_reload = true;
+            self.status = format!("/{}: {} older match{}, loading history...", query, missing, if missing == 1 { "" } else { "es" });
+        }
+    }
+    
+    /// Called after the open chat's history was (re)loaded
+    pub fn on_history_loaded(&mut self) {
+        if let Some(target) = self.search_target {
+            match self.messages.iter().position(|m| m.id == Some(target)) {
+                Some(i) => {
+                    self.search_target = None;
+                    self.refresh_search_hits();
+                    let pos = self.search_hits.iter().position(|h| *h == i);
+                    match pos {
+                        Some(pos) => self.show_search_hit(pos),
+                        None => self.jump_to_message(i),
+                    }
+                }
+                None if self.load_more_history() => self.status = "Loading history...".into(),
+                None => {
+                    self.search_target = None;
+                    self.status = "Message is too old to show".into();
+                }
+            }
+            return;
+        }
+        // Indices shift when older messages are loaded
+        self.refresh_search_hits();
+        if std::mem::take(&mut self.search_reload) {
+            self.show_search_hit(0);
+        }
+    }
+    
+    pub fn start_global_search(&mut self, query: &str) {
+        self.mode = Mode::GlobalSearch;
+        if !query.is_empty() {
+            self.global_search.input = query.to_string();
+            self.run_global_search();
+        }
+    }
+    
+    pub fn run_global_search(&mut self) {
+        let gs = &mut self.global_search;
+        let query = gs.input.trim().to_string();
+        if query.is_empty() { return; }
+        gs.query = query.clone();
+        gs.hits.clear();
+        gs.selected = 0;
+        gs.loading = true;
+        self.send_cmd_as("search-all", &format!("/_get items count={} search={}", search::SEARCH_LIMIT, query));
+    }
+    
+    /// Opens the chat of the selected hit, scrolled to the message
+    pub fn open_global_hit(&mut self) {
+        let Some(hit) = self.global_search.selected_hit().cloned() else { return };
+        if hit.group {
+            self.status = format!("Group chats can't be opened yet ({})", hit.chat);
+            return;
+        }
+        self.open_contact(&hit.chat);
+        if self.current_contact.as_deref() != Some(hit.chat.as_str()) { return; }
+        self.search_query = Some(self.global_search.query.clone());
+        self.search_target = Some(hit.item_id);
+        self.panel = Panel::Chat;
+        self.mode = Mode::Normal;
+    }
+    
+    pub fn tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+        if self.playing_voice.is_some() && !self.audio.is_playing() {
+            self.playing_voice = None;
+        }
+        if self.finishing_recording.is_some() && self.audio.recording_finished() {
+            if let Some((path, duration)) = self.finishing_recording.take() {
+                self.send_voice(&path, duration);
+            }
+        }
+        self.update_compose_preview();
+    }
+    
+    /// Fetches a preview for the first URL in the composer once typing pauses
+    fn update_compose_preview(&mut self) {
+        if !self.config.link_preview.enabled { return; }
+        
+        if let Some((url, rx)) = &self.preview_fetch {
+            match rx.try_recv() {
+                Ok(Ok(preview)) => {
+                    self.status = "Link preview ready, [Ctrl-X] removes it".into();
+                    self.compose_preview = Some(preview);
+                    self.preview_fetch = None;
+                }
+                Ok(Err(e)) => {
+                    self.status = format!("No preview: {}", e);
+                    self.preview_dismissed = Some(url.clone());
+                    self.preview_fetch = None;
+                }
+                Err(mpsc::TryRecvError::Disconnected) => self.preview_fetch = None,
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
+        }
+        
+        if self.preview_seen_input.0 != self.composer.text() {
+            self.preview_seen_input = (self.composer.text().to_string(), Some(Instant::now()));
+            return;
+        }
+        let settled = self.preview_seen_input.1.is_some_and(|t| t.elapsed().as_millis() >= PREVIEW_DEBOUNCE_MS);
+        if !settled { return; }
+        self.preview_seen_input.1 = None;
+        
+        let url = link_preview::find_url(self.composer.text()).map(|u| u.to_string());
+        if self.compose_preview.as_ref().map(|p| &p.uri) != url.as_ref() {
+            self.compose_preview = None;
+        }
+        let Some(url) = url else { return; };
+        let known = self.compose_preview.is_some()
+            || self.preview_dismissed.as_ref() == Some(&url)
+            || self.preview_fetch.as_ref().is_some_and(|(u, _)| u == &url);
+        if !known {
+            self.preview_fetch = Some((url.clone(), link_preview::spawn_fetch(url, self.config.link_preview.clone())));
+        }
+    }
+    
+    pub fn remove_compose_preview(&mut self) {
+        let url = self.compose_preview.take().map(|p| p.uri)
+            .or_else(|| self.preview_fetch.take().map(|(u, _)| u));
+        if url.is_some() {
+            self.preview_dismissed = url;
+            self.status = "Link preview removed".into();
+        }
+    }
+    
+    /// Plays the newest voice message of the open chat, or stops playback
+    pub fn toggle_voice_playback(&mut self) {
+        let last = self.messages.iter().rposition(|m| m.voice.is_some());
+        self.toggle_voice_at(last);
+    }
+    
+    /// Plays the voice message at `index`, or stops it if it is playing
+    pub fn toggle_voice_at(&mut self, index: Option<usize>) {
+        let voice = index.and_then(|i| self.messages.get(i)).and_then(|m| m.voice.clone());
+        if let Some(playing) = self.playing_voice.take() {
+            self.audio.stop();
+            if voice.as_ref().is_none_or(|v| v.file_id == Some(playing)) {
+                self.status = "Playback stopped".into();
+                return;
+            }
+        }
+        
+        let Some(voice) = voice else {
+            self.status = "No voice message in this chat".into();
+            return;
+        };
+        let Some(file_id) = voice.file_id else { return; };
+        
+        match voice.path {
+            Some(path) => self.play_voice(file_id, &path),
+            None => {
+                self.pending_voice = Some(file_id);
+                self.send_cmd(&format!("/fr {} {}", file_id, self.config.audio.download_dir));
+                self.status = "Downloading voice message...".into();
+            }
+        }
+    }
+    
+    /// Runs the configured opener, or copies the link when there is none
+    pub fn open_link(&mut self, url: &str) {
+        let opener = &self.config.link_preview.open_command;
+        if opener.trim().is_empty() {
+            clipboard::copy(url, &self.config.editor);
+            self.status = format!("Copied {}", url);
+            return;
+        }
+        let mut parts = opener.split_whitespace().map(|p| p.replace("{url}", url));
+        let Some(program) = parts.next() else { return };
+        let spawned = std::process::Command::new(program)
+            .args(parts)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        self.status = match spawned {
+            Ok(mut child) => {
+                std::thread::spawn(move || child.wait());
+                format!("Opening {}", url)
+            }
+            Err(e) => format!("Cannot open link: {}", e),
+        };
+    }
+    
+    fn play_voice(&mut self, file_id: u64, path: &str) {
+        match self.audio.play(std::path::Path::new(path)) {
+            Ok(()) => {
+                self.playing_voice = Some(file_id);
+                self.status = "▶ Playing voice message".into();
+            }
+            Err(e) => self.status = format!("Playback failed: {}", e),
+        }
+    }
+    
+    pub fn on_file_received(&mut self, file_id: u64, path: String) {
+        for voice in self.messages.iter_mut().filter_map(|m| m.voice.as_mut()) {
+            if voice.file_id == Some(file_id) {
+                voice.path = Some(path.clone());
+            }
+        }
+        if self.pending_voice == Some(file_id) {
+            self.pending_voice = None;
+            self.play_voice(file_id, &path);
+        }
+    }
     
-    pub fn input_char(&mut self, c: char) { let mut chars: Vec<char> = self.input.chars().collect(); chars.insert(self.cursor, c); self.input = chars.into_iter().collect(); self.cursor += 1; }
-    pub fn input_backspace(&mut self) { if self.cursor > 0 { self.cursor -= 1; let mut chars: Vec<char> = self.input.chars().collect(); chars.remove(self.cursor); self.input = chars.into_iter().collect(); } }
-    pub fn input_delete(&mut self) { let len = self.input.chars().count(); if self.cursor < len { let mut chars: Vec<char> = self.input.chars().collect(); chars.remove(self.cursor); self.input = chars.into_iter().collect(); } }
-    pub fn cursor_left(&mut self) { self.cursor = self.cursor.saturating_sub(1); }
-    pub fn cursor_right(&mut self) { if self.cursor < self.input.chars().count() { self.cursor += 1; } }
-    pub fn cursor_home(&mut self) { self.cursor = 0; }
-    pub fn cursor_end(&mut self) { self.cursor = self.input.chars().count(); }
-    pub fn tick(&mut self) { self.tick = self.tick.wrapping_add(1); }
+    /// Starts recording, or stops and sends the recording to the open chat
+    pub fn toggle_voice_recording(&mut self) {
+        if self.finishing_recording.is_some() {
+            self.status = "Still finishing the last recording".into();
+            return;
+        }
+        if let Some((path, started)) = self.recording.take() {
+            if let Err(e) = self.audio.stop_recording() {
+                self.status = format!("Recording failed: {}", e);
+                return;
+            }
+            // Sent from the tick once the recorder has written the file
+            self.finishing_recording = Some((path, started.elapsed().as_secs().max(1)));
+            self.status = "Finishing recording...".into();
+            return;
+        }
+        
+        if self.current_contact.is_none() {
+            self.status = "Open a chat to record a voice message".into();
+            return;
+        }
+        
+        let name = format!("simplex-tui-voice-{}.{}", Local::now().format("%Y%m%d-%H%M%S"), self.config.audio.extension);
+        let path = std::env::temp_dir().join(name);
+        match self.audio.start_recording(&path) {
+            Ok(()) => {
+                self.recording = Some((path, Instant::now()));
+                self.status = "● Recording... press [v] to send".into();
+            }
+            Err(e) => self.status = format!("Recording failed: {}", e),
+        }
+    }
+    
+    fn send_voice(&mut self, path: &std::path::Path, duration: u64) {
+        let Some(contact) = self.current_contact.as_ref().and_then(|n| self.contacts.iter().find(|c| &c.name == n)) else { return; };
+        let composed = serde_json::json!([{
+            "filePath": path.to_string_lossy(),
+            "msgContent": { "type": "voice", "text": "", "duration": duration },
+        }]);
+        self.send_cmd(&format!("/_send @{} json {}", contact.id, composed));
+        self.status = "Sending voice message...".into();
+    }
+}
+
+/// UI state and local tags from the data directory; tests get defaults
+/// without a path, so they never read or write the user's files
+fn stored_state() -> (UiState, Tags) {
+    if cfg!(test) { return (UiState::default(), Tags::default()); }
+    (UiState::load(), Tags::load())
 }
 
 impl Default for App {
-    fn default() -> Self { Self::new() }
+    fn default() -> Self {
+        let mut config = Config::default();
+        // Keeps the bell and title escapes out of the test runner's output
+        if cfg!(test) { config.notifications = NotifyConfig { bell: false, title: false, ..NotifyConfig::default() }; }
+        Self::new(config, Drafts::default(), Keymap::default())
+    }
+}
+
+/// `@name` or `#name` for the command line, quoted when it has spaces
+pub fn chat_arg(name: &str, group: bool) -> String {
+    let sigil = if group { '#' } else { '@' };
+    if name.contains(' ') { format!("{}'{}'", sigil, name) } else { format!("{}{}", sigil, name) }
+}
+
+/// Splits a leading `@contact` or `#group`, possibly quoted, from the rest
+/// of a command's arguments
+fn split_chat_arg(args: &str) -> (Option<(String, bool)>, &str) {
+    let args = args.trim();
+    let Some(sigil) = args.chars().next().filter(|c| *c == '@' || *c == '#') else { return (None, args) };
+    let rest = &args[1..];
+    let (name, rest) = match rest.strip_prefix(['\'', '"']) {
+        Some(quoted) => quoted.split_once(['\'', '"']).unwrap_or((quoted, "")),
+        None => rest.split_once(' ').unwrap_or((rest, "")),
+    };
+    (Some((name.to_string(), sigil == '#')), rest.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    
+    use super::*;
+    use crate::audio::fake::FakeBackend;
+    use crate::types::VoiceClip;
+    
+    fn app_with_fake_audio() -> (App, FakeBackend, mpsc::Receiver<ApiCommand>) {
+        let mut app = App::default();
+        let fake = FakeBackend::default();
+        app.audio = Box::new(fake.clone());
+        let (tx, rx) = mpsc::channel();
+        app.cmd_tx = Some(tx);
+        app.contacts = vec![Contact::new(7, "alice".into())];
+        app.current_contact = Some("alice".into());
+        (app, fake, rx)
+    }
+    
+    fn voice_message(path: &str) -> ChatMessage {
+        ChatMessage {
+            id: Some(1),
+            sender: "alice".into(),
+            content: String::new(),
+            time: "12:00".into(),
+            mine: false,
+            status: MessageStatus::Delivered,
+            voice: Some(VoiceClip { duration: 3, file_id: Some(42), path: Some(path.into()) }),
+            link: None,
+            timer: None,
+        }
+    }
+    
+    #[test]
+    fn plays_and_stops_voice_message() {
+        let (mut app, fake, _rx) = app_with_fake_audio();
+        app.messages.push(voice_message("/tmp/voice.m4a"));
+        
+        app.toggle_voice_playback();
+        assert_eq!(fake.state().playing.as_deref(), Some(std::path::Path::new("/tmp/voice.m4a")));
+        assert_eq!(app.playing_voice, Some(42));
+        
+        app.toggle_voice_playback();
+        assert!(fake.state().playing.is_none());
+        assert_eq!(app.playing_voice, None);
+    }
+    
+    #[test]
+    fn tick_notices_playback_end() {
+        let (mut app, fake, _rx) = app_with_fake_audio();
+        app.messages.push(voice_message("/tmp/voice.m4a"));
+        app.toggle_voice_playback();
+        
+        fake.state().playing = None;
+        app.tick();
+        assert_eq!(app.playing_voice, None);
+    }
+    
+    #[test]
+    fn recording_is_sent_once_finished() {
+        let (mut app, fake, rx) = app_with_fake_audio();
+        
+        app.toggle_voice_recording();
+        assert!(fake.state().recording.is_some());
+        
+        app.toggle_voice_recording();
+        assert!(fake.state().finishing);
+        app.tick();
+        assert!(rx.try_recv().is_err(), "sent before the recorder finished");
+        
+        fake.state().finishing = false;
+        app.tick();
+        let cmd = rx.try_recv().expect("voice message sent").cmd;
+        assert!(cmd.starts_with("/_send @7 json "), "{}", cmd);
+        assert!(cmd.contains("\"type\":\"voice\""), "{}", cmd);
+        assert!(app.finishing_recording.is_none());
+    }
+    
+    #[test]
+    fn splits_leading_chat_argument() {
+        assert_eq!(split_chat_arg("@alice hello there"), (Some(("alice".into(), false)), "hello there"));
+        assert_eq!(split_chat_arg("  #team  1d "), (Some(("team".into(), true)), "1d"));
+        assert_eq!(split_chat_arg("@alice"), (Some(("alice".into(), false)), ""));
+    }
+    
+    #[test]
+    fn splits_quoted_chat_names() {
+        assert_eq!(split_chat_arg("@'Alice Smith' off"), (Some(("Alice Smith".into(), false)), "off"));
+        assert_eq!(split_chat_arg("#\"Book club\""), (Some(("Book club".into(), true)), ""));
+    }
+    
+    #[test]
+    fn leaves_arguments_without_chat() {
+        assert_eq!(split_chat_arg("1h"), (None, "1h"));
+        assert_eq!(split_chat_arg(""), (None, ""));
+    }
+    
+    #[test]
+    fn chat_arg_round_trips() {
+        for (name, group) in [("alice", false), ("Book club", true)] {
+            assert_eq!(split_chat_arg(&chat_arg(name, group)), (Some((name.to_string(), group)), ""));
+        }
+    }
+    
+    #[test]
+    fn recording_needs_open_chat() {
+        let (mut app, fake, _rx) = app_with_fake_audio();
+        app.current_contact = None;
+        app.toggle_voice_recording();
+        assert!(fake.state().recording.is_none());
+    }
+    
+    #[test]
+    fn blocks_by_contact_id() {
+        let mut app = App { user_id: Some(1), ..App::default() };
+        let mut eve = Contact::new(9, "eve".into());
+        eve.profile.display_name = "eve".into();
+        app.contacts = vec![eve];
+        app.set_blocked("eve", true);
+        assert!(app.is_blocked("eve"));
+        app.contacts[0].name = "eve_2".into();
+        assert!(app.is_blocked("eve_2"));
+        let request = ContactRequest { id: 3, name: "eve_1".into(), display_name: "eve".into() };
+        assert!(app.is_blocked_request(&request));
+        app.user_id = Some(2);
+        assert!(!app.is_blocked("eve_2"));
+        app.user_id = Some(1);
+        app.contacts.clear();
+        app.set_blocked("eve", false);
+        assert!(app.ui_state.blocked.is_empty());
+    }
+    
+    #[test]
+    fn panic_mode_silences_messages() {
+        let (mut app, _fake, _rx) = app_with_fake_audio();
+        app.contacts.push(Contact::new(8, "bob".into()));
+        let message = ChatMessage { sender: "bob".into(), content: "hi".into(), voice: None, ..voice_message("") };
+        app.run_action(Action::Panic);
+        app.status.clear();
+        app.notify_message("bob", &message);
+        assert!(app.status.is_empty());
+        app.set_panic(false);
+        assert_eq!(app.mode, Mode::Normal);
+        app.notify_message("bob", &message);
+        assert_eq!(app.status, "📩 bob");
+    }
+    
+    #[test]
+    fn pins_follow_contact_id_per_profile() {
+        let mut app = App { user_id: Some(1), ..App::default() };
+        app.contacts = vec![Contact::new(4, "bob".into()), Contact::new(9, "carol".into())];
+        app.contact_state.select(Some(1));
+        app.toggle_pin();
+        assert_eq!(app.contact_rows()[..2], [Row::Section(Section::Pinned, 1), Row::Contact(1)]);
+        app.contacts[1].name = "carol_2".into();
+        assert_eq!(app.contact_rows()[1], Row::Contact(1));
+        // Another profile with a contact of the same id and name
+        app.user_id = Some(2);
+        assert!(!app.contact_rows().contains(&Row::Section(Section::Pinned, 1)));
+    }
 }
@@ -0,0 +1,195 @@
+//! Voice message playback and recording
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::AudioConfig;
+
+/// Anything that can play and record voice messages. The app only talks to
+/// this trait, so a fake backend can stand in where there is no sound card.
+pub trait AudioBackend: Send {
+    fn play(&mut self, path: &Path) -> Result<()>;
+    fn stop(&mut self);
+    fn is_playing(&mut self) -> bool;
+    fn start_recording(&mut self, path: &Path) -> Result<()>;
+    /// Asks the recorder to finish the file without waiting for it
+    fn stop_recording(&mut self) -> Result<()>;
+    /// Whether a stopped recording is complete on disk; polled from the tick
+    fn recording_finished(&mut self) -> bool;
+}
+
+/// How long a stopped recorder gets to finalize its file before it is killed
+const FINISH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Default backend: shells out to the player/recorder commands from the config
+pub struct CommandBackend {
+    player: String,
+    recorder: String,
+    playback: Option<Child>,
+    recording: Option<Child>,
+    /// Stopped recorder still writing, with the time it gets killed
+    finishing: Option<(Child, Instant)>,
+}
+
+impl CommandBackend {
+    pub fn new(config: &AudioConfig) -> Self {
+        Self {
+            player: config.player.clone(),
+            recorder: config.recorder.clone(),
+            playback: None,
+            recording: None,
+            finishing: None,
+        }
+    }
+}
+
+fn spawn(template: &str, path: &Path, stdin: Stdio) -> Result<Child> {
+    let file = path.to_string_lossy();
+    let mut parts = template.split_whitespace().map(|p| p.replace("{file}", &file));
+    let Some(program) = parts.next() else { bail!("no audio command configured") };
+    Command::new(&program)
+        .args(parts)
+        .stdin(stdin)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("cannot run {}", program))
+}
+
+impl AudioBackend for CommandBackend {
+    fn play(&mut self, path: &Path) -> Result<()> {
+        self.stop();
+        self.playback = Some(spawn(&self.player, path, Stdio::null())?);
+        Ok(())
+    }
+    
+    fn stop(&mut self) {
+        if let Some(mut child) = self.playback.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+    
+    fn is_playing(&mut self) -> bool {
+        match &mut self.playback {
+            Some(child) => match child.try_wait() {
+                Ok(None) => true,
+                _ => { self.playback = None; false }
+            },
+            None => false,
+        }
+    }
+    
+    fn start_recording(&mut self, path: &Path) -> Result<()> {
+        if self.recording.is_some() || self.finishing.is_some() { bail!("already recording"); }
+        self.recording = Some(spawn(&self.recorder, path, Stdio::piped())?);
+        Ok(())
+    }
+    
+    fn stop_recording(&mut self) -> Result<()> {
+        let Some(mut child) = self.recording.take() else { bail!("not recording") };
+        
+        // ffmpeg finalizes the file on "q", other recorders just get killed
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(b"q\n");
+        }
+        self.finishing = Some((child, Instant::now() + FINISH_TIMEOUT));
+        Ok(())
+    }
+    
+    fn recording_finished(&mut self) -> bool {
+        let Some((child, deadline)) = &mut self.finishing else { return true };
+        match child.try_wait() {
+            Ok(None) if Instant::now() < *deadline => return false,
+            Ok(None) => { let _ = child.kill(); let _ = child.wait(); }
+            _ => {}
+        }
+        self.finishing = None;
+        true
+    }
+}
+
+impl Drop for CommandBackend {
+    fn drop(&mut self) {
+        self.stop();
+        for mut child in self.recording.take().into_iter().chain(self.finishing.take().map(|(c, _)| c)) {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+pub fn format_duration(secs: u64) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Backend without any sound: records what the app asked for so tests can
+/// drive playback and recording on a headless box
+#[cfg(test)]
+pub mod fake {
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+    
+    use anyhow::{bail, Result};
+    
+    use super::AudioBackend;
+    
+    #[derive(Debug, Default)]
+    pub struct FakeState {
+        pub playing: Option<PathBuf>,
+        pub recording: Option<PathBuf>,
+        /// Recorder told to stop but not done writing
+        pub finishing: bool,
+    }
+    
+    /// The state is shared so a test keeps a handle after boxing the backend
+    #[derive(Clone, Default)]
+    pub struct FakeBackend(pub Arc<Mutex<FakeState>>);
+    
+    impl FakeBackend {
+        pub fn state(&self) -> std::sync::MutexGuard<'_, FakeState> {
+            self.0.lock().unwrap()
+        }
+    }
+    
+    impl AudioBackend for FakeBackend {
+        fn play(&mut self, path: &Path) -> Result<()> {
+            self.state().playing = Some(path.to_path_buf());
+            Ok(())
+        }
+        
+        fn stop(&mut self) {
+            self.state().playing = None;
+        }
+        
+        fn is_playing(&mut self) -> bool {
+            self.state().playing.is_some()
+        }
+        
+        fn start_recording(&mut self, path: &Path) -> Result<()> {
+            let mut state = self.state();
+            if state.recording.is_some() { bail!("already recording"); }
+            state.recording = Some(path.to_path_buf());
+            Ok(())
+        }
+        
+        fn stop_recording(&mut self) -> Result<()> {
+            let mut state = self.state();
+            if state.recording.take().is_none() { bail!("not recording"); }
+            state.finishing = true;
+            Ok(())
+        }
+        
+        fn recording_finished(&mut self) -> bool {
+            !self.state().finishing
+        }
+    }
+}
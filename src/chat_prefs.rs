@@ -0,0 +1,72 @@
+//! Per-contact chat preferences: our side of each feature
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use crate::types::Contact;
+
+/// Chat features with a per-contact preference: backend key and label
+pub const FEATURES: [(&str, &str); 6] = [
+    ("timedMessages", "Disappearing messages"),
+    ("fullDelete", "Full deletion"),
+    ("reactions", "Message reactions"),
+    ("voice", "Voice messages"),
+    ("calls", "Audio/video calls"),
+    ("files", "Files and media"),
+];
+/// None follows the profile's setting
+const CHOICES: [Option<&str>; 4] = [None, Some("always"), Some("yes"), Some("no")];
+/// TTL used when disappearing messages are turned on here without one
+const DEFAULT_TTL: i64 = 86_400;
+
+pub struct PrefsEditor {
+    pub contact_id: i64,
+    /// Our choice per entry of `FEATURES`
+    pub values: [Option<String>; FEATURES.len()],
+    pub selected: usize,
+}
+
+impl PrefsEditor {
+    pub fn new(contact: &Contact) -> Self {
+        Self {
+            contact_id: contact.id,
+            values: FEATURES.map(|(key, _)| {
+                contact.prefs.user.get(key).and_then(|p| p.get("allow")).and_then(|a| a.as_str()).map(str::to_string)
+            }),
+            selected: 0,
+        }
+    }
+    
+    pub fn move_selection(&mut self, down: bool) {
+        let len = FEATURES.len();
+        self.selected = if down { (self.selected + 1) % len } else { (self.selected + len - 1) % len };
+    }
+    
+    pub fn cycle(&mut self, forward: bool) {
+        let value = &mut self.values[self.selected];
+        let pos = CHOICES.iter().position(|c| c.map(str::to_string) == *value).unwrap_or(0);
+        let len = CHOICES.len();
+        let next = if forward { (pos + 1) % len } else { (pos + len - 1) % len };
+        *value = CHOICES[next].map(str::to_string);
+    }
+    
+    /// Our preferences for `/_set prefs`, based on the contact's current
+    /// ones so disappearing message times survive
+    pub fn to_prefs(&self, contact: &Contact) -> serde_json::Value {
+        let mut prefs = contact.prefs.user.clone();
+        if !prefs.is_object() { prefs = serde_json::json!({}); }
+        for ((key, _), value) in FEATURES.iter().zip(&self.values) {
+            let Some(allow) = value else {
+                if let Some(map) = prefs.as_object_mut() { map.remove(*key); }
+                continue;
+            };
+            let mut pref = serde_json::json!({ "allow": allow });
+            if *key == "timedMessages" && allow != "no" {
+                pref["ttl"] = prefs[*key]["ttl"].as_i64().or_else(|| contact.prefs.state(key).ttl).unwrap_or(DEFAULT_TTL).into();
+            }
+            prefs[*key] = pref;
+        }
+        prefs
+    }
+}
@@ -0,0 +1,42 @@
+//! System clipboard access for the `+`/`*` registers
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::config::EditorConfig;
+
+fn command(template: &str) -> Option<Command> {
+    let mut parts = template.split_whitespace();
+    let mut cmd = Command::new(parts.next()?);
+    cmd.args(parts).stderr(Stdio::null());
+    Some(cmd)
+}
+
+/// Copies through the configured command, or OSC 52 so it also works over SSH
+pub fn copy(text: &str, config: &EditorConfig) {
+    if let Some(mut cmd) = command(&config.clipboard_copy) {
+        if let Ok(mut child) = cmd.stdin(Stdio::piped()).stdout(Stdio::null()).spawn() {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            let _ = child.wait();
+        }
+        return;
+    }
+    let mut out = std::io::stdout();
+    let _ = write!(out, "\x1b]52;c;{}\x07", STANDARD.encode(text));
+    let _ = out.flush();
+}
+
+/// Terminals do not answer clipboard reads reliably, so without a paste
+/// command the caller falls back to what was last copied or pasted
+pub fn paste(config: &EditorConfig) -> Option<String> {
+    let out = command(&config.clipboard_paste)?.stdin(Stdio::null()).output().ok()?;
+    if out.status.success() { Some(String::from_utf8_lossy(&out.stdout).into_owned()) } else { None }
+}
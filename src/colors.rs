@@ -1,44 +1,291 @@
-//! Color palette for SimpleX TUI
+//! Color themes for SimpleX TUI
 //! 
 //! Copyright (c) 2026 cannatoshi
 //! GitHub: https://github.com/cannatoshi/simplex-tui
 //! Licensed under AGPL-3.0
 
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{LazyLock, RwLock};
+
+use anyhow::{anyhow, bail, Context, Result};
 use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::config::{self, ThemeConfig};
 
-pub const BLUE: Color = Color::Rgb(0, 136, 255);
-pub const BLUE_LIGHT: Color = Color::Rgb(100, 180, 255);
-
-pub const BG: Color = Color::Rgb(12, 12, 16);
-pub const BG_SECONDARY: Color = Color::Rgb(18, 18, 24);
-pub const BG_HIGHLIGHT: Color = Color::Rgb(35, 45, 60);
-
-pub const TEXT: Color = Color::Rgb(195, 200, 210);
-pub const TEXT_MUTED: Color = Color::Rgb(100, 105, 115);
-pub const TEXT_DIM: Color = Color::Rgb(70, 75, 85);
-
-pub const SUCCESS: Color = Color::Rgb(80, 200, 120);
-pub const WARNING: Color = Color::Rgb(255, 180, 0);
-pub const DANGER: Color = Color::Rgb(220, 60, 60);
-pub const UNREAD: Color = Color::Rgb(255, 150, 0);
-
-pub const AVATAR_COLORS: [Color; 8] = [
-    Color::Rgb(99, 102, 241),
-    Color::Rgb(236, 72, 153),
-    Color::Rgb(34, 197, 94),
-    Color::Rgb(249, 115, 22),
-    Color::Rgb(168, 85, 247),
-    Color::Rgb(14, 165, 233),
-    Color::Rgb(234, 179, 8),
-    Color::Rgb(239, 68, 68),
+/// Built-in themes, also usable as `base` by theme files
+const BUILTIN: &[(&str, &str)] = &[
+    ("dark", include_str!("../themes/dark.toml")),
+    ("light", include_str!("../themes/light.toml")),
+    ("high-contrast", include_str!("../themes/high-contrast.toml")),
+    ("solarized", include_str!("../themes/solarized.toml")),
 ];
 
-pub const BORDER: Color = Color::Rgb(45, 50, 60);
-pub const BORDER_ACTIVE: Color = BLUE;
+/// How many colors the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum ColorDepth {
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Indexed,
+    #[serde(rename = "16")]
+    Ansi,
+}
+
+impl ColorDepth {
+    /// Guesses from `COLORTERM` and `TERM`; the Linux console and unknown
+    /// terminals get the 16 basic colors
+    fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default().to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" { return Self::TrueColor; }
+        let term = std::env::var("TERM").unwrap_or_default();
+        let indexed = ["xterm", "screen", "tmux", "rxvt", "alacritty", "kitty", "foot", "wezterm"];
+        if term.contains("256") || indexed.iter().any(|t| term.starts_with(t)) { Self::Indexed } else { Self::Ansi }
+    }
+}
+
+macro_rules! theme {
+    ($($field:ident),* $(,)?) => {
+        #[derive(Debug, Clone)]
+        pub struct Theme {
+            pub name: String,
+            $($field: Color,)*
+            avatars: Vec<Color>,
+            /// Overrides for 16-color terminals, base theme first
+            ansi16: Vec<ThemeFile>,
+        }
+        
+        /// A theme as written on disk; unset colors come from `base`
+        #[derive(Debug, Clone, Default, Deserialize)]
+        #[serde(default, deny_unknown_fields)]
+        struct ThemeFile {
+            base: Option<String>,
+            $($field: Option<String>,)*
+            avatars: Option<Vec<String>>,
+            ansi16: Option<Box<ThemeFile>>,
+        }
+        
+        impl ThemeFile {
+            fn apply(&self, theme: &mut Theme) -> Result<()> {
+                $(if let Some(c) = &self.$field { theme.$field = parse_color(c)?; })*
+                if let Some(list) = &self.avatars {
+                    theme.avatars = list.iter().map(|c| parse_color(c)).collect::<Result<_>>()?;
+                }
+                Ok(())
+            }
+        }
+        
+        impl Theme {
+            fn blank(name: &str) -> Self {
+                Self { name: name.to_string(), $($field: Color::Reset,)* avatars: vec![], ansi16: vec![] }
+            }
+            
+            fn map(&mut self, f: impl Fn(Color) -> Color) {
+                $(self.$field = f(self.$field);)*
+                self.avatars.iter_mut().for_each(|c| *c = f(*c));
+            }
+        }
+        
+        $(pub fn $field() -> Color { ACTIVE.read().unwrap_or_else(|e| e.into_inner()).theme.$field })*
+    };
+}
+
+theme!(
+    accent, accent_light,
+    bg, bg_secondary, bg_highlight,
+    text, text_muted, text_dim,
+    success, warning, danger, unread,
+    border, border_active,
+);
+
+/// The theme in use, already reduced to what the terminal can show
+struct Active {
+    theme: Theme,
+    depth: ColorDepth,
+}
+
+static ACTIVE: LazyLock<RwLock<Active>> = LazyLock::new(|| {
+    let theme = parse("dark", BUILTIN[0].1, None, 0).expect("built-in theme is valid");
+    RwLock::new(Active { theme, depth: ColorDepth::TrueColor })
+});
+
+fn parse_color(s: &str) -> Result<Color> {
+    Color::from_str(s.trim()).map_err(|_| anyhow!("invalid color '{}'", s))
+}
+
+fn themes_dir() -> Option<PathBuf> {
+    config::config_dir().map(|d| d.join("themes"))
+}
+
+/// `name` from the themes directory, falling back to the built-ins
+pub fn load(name: &str) -> Result<Theme> {
+    load_nested(name, 0)
+}
+
+fn load_nested(name: &str, depth: usize) -> Result<Theme> {
+    if depth > 4 { bail!("theme '{}' inherits too deeply", name); }
+    let path = themes_dir().map(|d| d.join(format!("{}.toml", name)));
+    match path.as_ref().and_then(|p| fs::read_to_string(p).ok().map(|txt| (p, txt))) {
+        Some((path, txt)) => parse(name, &txt, Some(path), depth),
+        None => {
+            let (_, txt) = BUILTIN.iter().find(|(n, _)| *n == name).ok_or_else(|| anyhow!("unknown theme '{}'", name))?;
+            parse(name, txt, None, depth)
+        }
+    }
+}
+
+/// `path` is None for built-ins; user themes without a `base` start from
+/// the default dark theme
+fn parse(name: &str, txt: &str, path: Option<&Path>, depth: usize) -> Result<Theme> {
+    let context = || match path {
+        Some(p) => format!("invalid theme {}", p.display()),
+        None => format!("invalid built-in theme {}", name),
+    };
+    let file: ThemeFile = toml::from_str(txt).with_context(context)?;
+    let mut theme = match (&file.base, path) {
+        (Some(base), _) => load_nested(base, depth + 1)?,
+        (None, Some(_)) => parse("dark", BUILTIN[0].1, None, depth + 1)?,
+        (None, None) => Theme::blank(name),
+    };
+    theme.name = name.to_string();
+    file.apply(&mut theme).with_context(context)?;
+    if let Some(ansi) = &file.ansi16 {
+        // Check the overrides now rather than on the first 16-color start
+        ansi.apply(&mut theme.clone()).with_context(context)?;
+        theme.ansi16.push((**ansi).clone());
+    }
+    Ok(theme)
+}
+
+/// Built-in and user theme names, sorted
+pub fn theme_names() -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN.iter().map(|(n, _)| n.to_string()).collect();
+    if let Some(entries) = themes_dir().and_then(|d| fs::read_dir(d).ok()) {
+        names.extend(entries.flatten().filter_map(|e| {
+            let path = e.path();
+            if path.extension()? != "toml" { return None; }
+            Some(path.file_stem()?.to_string_lossy().into_owned())
+        }));
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Loads the configured theme; called before the terminal is set up so
+/// a broken theme file is reported plainly
+pub fn init(config: &ThemeConfig) -> Result<()> {
+    let depth = match config.colors {
+        ColorDepth::Auto => ColorDepth::detect(),
+        depth => depth,
+    };
+    ACTIVE.write().unwrap_or_else(|e| e.into_inner()).depth = depth;
+    set_theme(load(&config.name)?);
+    Ok(())
+}
+
+/// Switches the theme for everything drawn from now on
+pub fn set_theme(mut theme: Theme) {
+    let mut active = ACTIVE.write().unwrap_or_else(|e| e.into_inner());
+    let depth = active.depth;
+    theme.map(|c| reduce(c, depth));
+    if depth == ColorDepth::Ansi {
+        for ansi in std::mem::take(&mut theme.ansi16) {
+            // Already validated in `parse`
+            let _ = ansi.apply(&mut theme);
+        }
+    }
+    active.theme = theme;
+}
+
+pub fn theme_name() -> String {
+    ACTIVE.read().unwrap_or_else(|e| e.into_inner()).theme.name.clone()
+}
+
+/// Reduces a color outside the theme, such as image thumbnails, to the
+/// terminal's depth
+pub fn adapt(color: Color) -> Color {
+    reduce(color, ACTIVE.read().unwrap_or_else(|e| e.into_inner()).depth)
+}
+
+fn reduce(color: Color, depth: ColorDepth) -> Color {
+    match (depth, color) {
+        (ColorDepth::Indexed, Color::Rgb(r, g, b)) => Color::Indexed(nearest_256(r, g, b)),
+        (ColorDepth::Ansi, Color::Rgb(r, g, b)) => nearest_16(r, g, b),
+        (ColorDepth::Ansi, Color::Indexed(i)) if i >= 16 => {
+            let (r, g, b) = indexed_rgb(i);
+            nearest_16(r, g, b)
+        }
+        _ => color,
+    }
+}
+
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Closest entry of the xterm 6x6x6 cube or gray ramp
+fn nearest_256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |v: u8| CUBE.iter().enumerate().min_by_key(|(_, c)| (**c as i32 - v as i32).abs()).map(|(i, _)| i as u8).unwrap_or(0);
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+    let avg = ((r as u32 + g as u32 + b as u32) / 3) as u8;
+    let gray = 232 + (avg.saturating_sub(3) / 10).min(23);
+    if distance((r, g, b), indexed_rgb(gray)) < distance((r, g, b), indexed_rgb(cube)) { gray } else { cube }
+}
+
+fn indexed_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI[i as usize].1,
+        16..=231 => {
+            let i = i - 16;
+            (CUBE[(i / 36) as usize], CUBE[(i / 6 % 6) as usize], CUBE[(i % 6) as usize])
+        }
+        _ => {
+            let v = 8 + 10 * (i - 232);
+            (v, v, v)
+        }
+    }
+}
+
+/// The 16 basic colors with xterm's default values
+const ANSI: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn nearest_16(r: u8, g: u8, b: u8) -> Color {
+    ANSI.iter().min_by_key(|(_, rgb)| distance((r, g, b), *rgb)).map(|(c, _)| *c).unwrap_or(Color::Reset)
+}
 
 pub fn avatar_color(name: &str) -> Color {
+    let active = ACTIVE.read().unwrap_or_else(|e| e.into_inner());
+    let avatars = &active.theme.avatars;
+    if avatars.is_empty() { return active.theme.accent; }
     let hash: usize = name.bytes().map(|b| b as usize).sum();
-    AVATAR_COLORS[hash % AVATAR_COLORS.len()]
+    avatars[hash % avatars.len()]
 }
 
 pub fn get_initials(name: &str) -> String {
@@ -0,0 +1,255 @@
+//! Command line: client commands, raw simplex-chat passthrough and completion
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::path::Path;
+
+use crate::editor::Editor;
+
+const HISTORY_LIMIT: usize = 100;
+const CONSOLE_LIMIT: usize = 50;
+
+/// Client commands typed after `:`, with a short description
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("open", "Open the chat with a contact"),
+    ("info", "Show contact info"),
+    ("accept", "Accept a contact request"),
+    ("reject", "Reject a contact request"),
+    ("refresh", "Reload contacts and the open chat"),
+    ("console", "Show command responses"),
+    ("search", "Search messages in all chats"),
+    ("tag", "Tag a contact or group, or list its tags"),
+    ("untag", "Remove a tag"),
+    ("alias", "Set or clear a contact's local alias"),
+    ("profile", "Edit your own profile"),
+    ("disappear", "Set disappearing messages for a contact: off or a time like 1h"),
+    ("notify", "Notify on all messages, mentions only or off for a chat"),
+    ("block", "Hide a contact's messages and reject their requests"),
+    ("unblock", "Show a blocked contact's messages again"),
+    ("dnd", "Do not disturb: on, off or toggle"),
+    ("user", "Switch, create, rename, hide or delete user profiles"),
+    ("qr", "Export the shown address or link QR code as .png or .svg"),
+    ("theme", "Switch color theme"),
+    ("help", "Toggle the help screen"),
+    ("quit", "Quit application"),
+];
+
+/// simplex-chat commands offered when completing a `/` line
+const RAW_COMMANDS: &[&str] = &[
+    "/_send", "/ac", "/accept", "/ad", "/address", "/c", "/connect", "/clear", "/contacts",
+    "/d", "/da", "/fr", "/freceive", "/g", "/groups", "/help", "/info", "/rc", "/reject",
+    "/sa", "/show_address", "/tail", "/user", "/users", "/version",
+];
+
+#[derive(Default)]
+pub struct CommandLine {
+    pub input: Editor,
+    history: Vec<String>,
+    history_pos: Option<usize>,
+    completion: Option<Completion>,
+}
+
+/// Candidates for the word being completed; repeated Tab cycles them
+struct Completion {
+    prefix: String,
+    candidates: Vec<String>,
+    index: usize,
+}
+
+impl CommandLine {
+    pub fn start(&mut self, text: &str) {
+        self.input.set_text(text);
+        self.history_pos = None;
+        self.completion = None;
+    }
+    
+    /// Takes the typed line and remembers it for Up/Down
+    pub fn submit(&mut self) -> String {
+        let line = self.input.take().trim().to_string();
+        if !line.is_empty() {
+            self.history.retain(|h| h != &line);
+            self.history.push(line.clone());
+            if self.history.len() > HISTORY_LIMIT { self.history.remove(0); }
+        }
+        line
+    }
+    
+    pub fn recall(&mut self, older: bool) {
+        let len = self.history.len();
+        let pos = match (self.history_pos, older) {
+            (None, true) if len > 0 => Some(len - 1),
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < len => Some(i + 1),
+            _ => None,
+        };
+        let text = pos.map(|i| self.history[i].clone()).unwrap_or_default();
+        self.input.set_text(&text);
+        self.history_pos = pos;
+    }
+    
+    /// Any edit ends a completion cycle
+    pub fn edited(&mut self) { self.completion = None; }
+    
+    /// Completes the last word of the line against command names, contact
+    /// and group names or file paths
+    pub fn complete(&mut self, names: &[&str]) {
+        let text = self.input.text().to_string();
+        if let Some(c) = self.completion.as_mut() {
+            if text == format!("{}{}", c.prefix, c.candidates[c.index]) {
+                c.index = (c.index + 1) % c.candidates.len();
+                let next = format!("{}{}", c.prefix, c.candidates[c.index]);
+                self.input.set_text(&next);
+                return;
+            }
+        }
+        
+        let start = text.rfind(' ').map(|i| i + 1).unwrap_or(0);
+        let (prefix, word) = text.split_at(start);
+        let candidates = candidates(prefix, word, names);
+        let Some(first) = candidates.first() else { self.completion = None; return };
+        
+        if candidates.len() == 1 {
+            let done = if first.ends_with('/') { first.clone() } else { format!("{} ", first) };
+            self.input.set_text(&format!("{}{}", prefix, done));
+            self.completion = None;
+            return;
+        }
+        self.input.set_text(&format!("{}{}", prefix, first));
+        self.completion = Some(Completion { prefix: prefix.to_string(), candidates, index: 0 });
+    }
+    
+    /// "2/5" while cycling through completions
+    pub fn completion_hint(&self) -> Option<String> {
+        self.completion.as_ref().map(|c| format!("{}/{}", c.index + 1, c.candidates.len()))
+    }
+}
+
+fn quote(name: &str) -> String {
+    if name.contains(char::is_whitespace) { format!("'{}'", name) } else { name.to_string() }
+}
+
+fn candidates(prefix: &str, word: &str, names: &[&str]) -> Vec<String> {
+    let mut out: Vec<String> = if prefix.is_empty() {
+        if word.starts_with('/') {
+            RAW_COMMANDS.iter().filter(|c| c.starts_with(word)).map(|c| c.to_string()).collect()
+        } else {
+            let word = word.trim_start_matches(':');
+            COMMANDS.iter().filter(|(c, _)| c.starts_with(word)).map(|(c, _)| c.to_string()).collect()
+        }
+    } else if word.contains('/') || word.starts_with('~') || word.starts_with('.') {
+        path_candidates(word)
+    } else {
+        let bare = word.trim_start_matches(['@', '#', '\'']).to_lowercase();
+        let sigil = &word[..word.len() - word.trim_start_matches(['@', '#']).len()];
+        names.iter()
+            .filter(|n| n.to_lowercase().starts_with(&bare))
+            .map(|n| format!("{}{}", sigil, quote(n)))
+            .collect()
+    };
+    out.sort();
+    out.dedup();
+    out
+}
+
+fn path_candidates(word: &str) -> Vec<String> {
+    let (dir, file) = match word.rfind('/') {
+        Some(i) => (&word[..=i], &word[i + 1..]),
+        None => ("", word),
+    };
+    let expanded = match (dir.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+        _ => dir.to_string(),
+    };
+    let read_from = if expanded.is_empty() { "." } else { expanded.as_str() };
+    let Ok(entries) = std::fs::read_dir(Path::new(read_from)) else { return vec![] };
+    entries.flatten().filter_map(|e| {
+        let name = e.file_name().to_string_lossy().into_owned();
+        if !name.starts_with(file) || (name.starts_with('.') && !file.starts_with('.')) { return None; }
+        let slash = if e.path().is_dir() { "/" } else { "" };
+        Some(format!("{}{}{}", dir, name, slash))
+    }).collect()
+}
+
+/// One raw command and, once it arrived, the backend's response
+pub struct ConsoleEntry {
+    pub corr_id: String,
+    pub command: String,
+    pub response: Option<serde_json::Value>,
+}
+
+#[derive(Default)]
+pub struct Console {
+    pub entries: Vec<ConsoleEntry>,
+    pub json: bool,
+    pub scroll: usize,
+    pub visible: bool,
+    next_id: u64,
+}
+
+impl Console {
+    /// Records a command and returns the correlation id to send it with
+    pub fn push(&mut self, command: &str) -> String {
+        self.next_id += 1;
+        let corr_id = format!("raw-{}", self.next_id);
+        self.entries.push(ConsoleEntry { corr_id: corr_id.clone(), command: command.to_string(), response: None });
+        if self.entries.len() > CONSOLE_LIMIT { self.entries.remove(0); }
+        self.visible = true;
+        self.scroll = usize::MAX;
+        corr_id
+    }
+    
+    pub fn respond(&mut self, corr_id: &str, response: serde_json::Value) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.corr_id == corr_id) {
+            entry.response = Some(response);
+            self.scroll = usize::MAX;
+        }
+    }
+}
+
+/// Indented `key: value` lines, easier to read than the raw JSON
+pub fn pretty(value: &serde_json::Value) -> Vec<String> {
+    let mut out = vec![];
+    pretty_into(value, 0, &mut out);
+    out
+}
+
+fn pretty_into(value: &serde_json::Value, indent: usize, out: &mut Vec<String>) {
+    use serde_json::Value;
+    let pad = "  ".repeat(indent);
+    match value {
+        Value::Object(map) => {
+            for (key, v) in map {
+                match v {
+                    Value::Object(m) if m.is_empty() => {}
+                    Value::Array(a) if a.is_empty() => {}
+                    Value::Object(_) | Value::Array(_) => {
+                        out.push(format!("{}{}:", pad, key));
+                        pretty_into(v, indent + 1, out);
+                    }
+                    _ => out.push(format!("{}{}: {}", pad, key, scalar(v))),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for v in items {
+                match v {
+                    Value::Object(_) | Value::Array(_) => {
+                        out.push(format!("{}-", pad));
+                        pretty_into(v, indent + 1, out);
+                    }
+                    _ => out.push(format!("{}- {}", pad, scalar(v))),
+                }
+            }
+        }
+        _ => out.push(format!("{}{}", pad, scalar(value))),
+    }
+}
+
+fn scalar(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,203 @@
+//! User configuration
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::colors::ColorDepth;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub audio: AudioConfig,
+    pub link_preview: LinkPreviewConfig,
+    pub editor: EditorConfig,
+    pub theme: ThemeConfig,
+    pub drafts: DraftsConfig,
+    pub notifications: NotifyConfig,
+    /// Key overrides per context, e.g. `[keys.chat] scroll_top = "g g"`;
+    /// text prompts and most dialogs keep fixed keys
+    pub keys: HashMap<String, HashMap<String, KeyList>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    /// Player command, `{file}` is replaced with the voice file path
+    pub player: String,
+    /// Recorder command, `{file}` is replaced with the output path
+    pub recorder: String,
+    /// Extension of recorded files
+    pub extension: String,
+    /// Directory passed to `/fr` when a voice message has to be downloaded first
+    pub download_dir: String,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            player: "ffplay -nodisp -autoexit -loglevel quiet {file}".into(),
+            recorder: "ffmpeg -loglevel quiet -f alsa -i default -c:a aac {file}".into(),
+            extension: "m4a".into(),
+            download_dir: "./".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LinkPreviewConfig {
+    /// Off by default: generating a preview contacts the linked site
+    pub enabled: bool,
+    /// Substituted for `{proxy}` in the command, Tor by default
+    pub proxy: String,
+    /// Fetch command printing the body to stdout, with `{url}` and `{proxy}`
+    pub command: String,
+    /// Opens a clicked link, e.g. `xdg-open {url}`; copied when empty
+    pub open_command: String,
+}
+
+impl Default for LinkPreviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            proxy: "socks5h://127.0.0.1:9050".into(),
+            command: "curl -sLg --proto =http,https --proto-redir =http,https --max-time 15 --max-filesize 2000000 --proxy {proxy} {url}".into(),
+            open_command: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    /// Modal vi-style editing in the composer
+    pub vi_mode: bool,
+    /// Command receiving copied text on stdin, OSC 52 when empty
+    pub clipboard_copy: String,
+    /// Command printing the clipboard, e.g. `wl-paste -n`
+    pub clipboard_paste: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DraftsConfig {
+    /// Keep drafts and sent history between runs; off keeps them in memory only
+    pub persist: bool,
+    /// Encrypt stored drafts with a passphrase asked for at startup
+    pub encrypt: bool,
+}
+
+impl Default for DraftsConfig {
+    fn default() -> Self {
+        Self { persist: true, encrypt: false }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Built-in theme or a file in `themes/` next to this config
+    pub name: String,
+    /// "auto", "truecolor", "256" or "16"
+    pub colors: ColorDepth,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self { name: "dark".into(), colors: ColorDepth::Auto }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    /// Ring the terminal bell
+    pub bell: bool,
+    /// Desktop notification escape sequence for the terminal
+    pub osc: Osc,
+    /// Unread count in the window title
+    pub title: bool,
+    /// Run for each notification, `{sender}` and `{message}` are replaced
+    pub command: String,
+    /// How much of the message notifications reveal
+    pub redact: Redact,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self { bell: true, osc: Osc::Off, title: true, command: String::new(), redact: Redact::Off }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Osc {
+    Off,
+    /// iTerm2, WezTerm, kitty, foot, Windows Terminal
+    Osc9,
+    /// rxvt-unicode, VTE terminals with the notify extension
+    Osc777,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Redact {
+    /// Sender and text
+    Off,
+    /// Sender only
+    Message,
+    /// Neither
+    All,
+}
+
+/// One key spec or several; an empty list unbinds the action
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    pub fn specs(&self) -> &[String] {
+        match self {
+            Self::One(s) => std::slice::from_ref(s),
+            Self::Many(v) => v,
+        }
+    }
+}
+
+/// `$XDG_CONFIG_HOME/simplex-tui`, falling back to `~/.config/simplex-tui`
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+    Some(base.join("simplex-tui"))
+}
+
+/// `$XDG_DATA_HOME/simplex-tui`, falling back to `~/.local/share/simplex-tui`
+pub fn data_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")))?;
+    Some(base.join("simplex-tui"))
+}
+
+impl Config {
+    /// Missing file means defaults, a broken one is an error
+    pub fn load() -> Result<Self> {
+        let Some(path) = config_dir().map(|d| d.join("config.toml")) else { return Ok(Self::default()) };
+        match fs::read_to_string(&path) {
+            Ok(txt) => toml::from_str(&txt).with_context(|| format!("invalid config {}", path.display())),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Contact list sections, filtering and pinned chats
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use crate::types::Contact;
+
+/// Groups of the contact list, in display order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    Pinned,
+    Unread,
+    Chats,
+}
+
+impl Section {
+    pub const ALL: [Section; 3] = [Section::Pinned, Section::Unread, Section::Chats];
+    
+    /// Name remembered in the UI state when collapsed
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Pinned => "pinned",
+            Self::Unread => "unread",
+            Self::Chats => "chats",
+        }
+    }
+    
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Pinned => "Pinned",
+            Self::Unread => "Unread",
+            Self::Chats => "Chats",
+        }
+    }
+}
+
+/// A line of the contact list
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Row {
+    /// Header with the number of contacts in the section
+    Section(Section, usize),
+    /// Index into the contacts
+    Contact(usize),
+}
+
+fn section_of(contact: &Contact, pinned: &[i64]) -> Section {
+    if pinned.contains(&contact.id) {
+        Section::Pinned
+    } else if contact.unread > 0 {
+        Section::Unread
+    } else {
+        Section::Chats
+    }
+}
+
+/// Visible rows for the current filter and the contacts `keep` accepts;
+/// `pinned` holds contact ids. Favorites come first within a section and
+/// pinned chats keep the order they were pinned in. A lone "Chats" section
+/// gets no header, and a filter expands every section so matches are never
+/// hidden.
+pub fn rows(contacts: &[Contact], filter: &str, pinned: &[i64], collapsed: &[String], keep: impl Fn(&Contact) -> bool) -> Vec<Row> {
+    let filter = filter.trim().to_lowercase();
+    let matching: Vec<usize> = (0..contacts.len())
+        .filter(|&i| keep(&contacts[i]))
+        .filter(|&i| filter.is_empty() || [&contacts[i].name, &contacts[i].alias].iter().any(|n| n.to_lowercase().contains(&filter)))
+        .collect();
+    
+    let mut sections: Vec<(Section, Vec<usize>)> = Section::ALL.iter().map(|s| {
+        let mut members: Vec<usize> = matching.iter().copied().filter(|&i| section_of(&contacts[i], pinned) == *s).collect();
+        match s {
+            Section::Pinned => members.sort_by_key(|&i| pinned.iter().position(|p| *p == contacts[i].id)),
+            _ => members.sort_by_key(|&i| !contacts[i].settings.favorite),
+        }
+        (*s, members)
+    }).filter(|(_, members)| !members.is_empty()).collect();
+    
+    if let [(Section::Chats, members)] = sections.as_mut_slice() {
+        if !collapsed.iter().any(|c| c == Section::Chats.id()) {
+            return members.iter().map(|&i| Row::Contact(i)).collect();
+        }
+    }
+    
+    let mut rows = vec![];
+    for (section, members) in sections {
+        rows.push(Row::Section(section, members.len()));
+        if filter.is_empty() && collapsed.iter().any(|c| c == section.id()) { continue; }
+        rows.extend(members.into_iter().map(Row::Contact));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn contact(id: i64, name: &str, unread: usize, favorite: bool) -> Contact {
+        let mut c = Contact::new(id, name.into());
+        c.unread = unread;
+        c.settings.favorite = favorite;
+        c
+    }
+    
+    fn all(_: &Contact) -> bool { true }
+    
+    #[test]
+    fn lone_chats_section_has_no_header() {
+        let contacts = [contact(1, "a", 0, false), contact(2, "b", 0, false)];
+        assert_eq!(rows(&contacts, "", &[], &[], all), vec![Row::Contact(0), Row::Contact(1)]);
+    }
+    
+    #[test]
+    fn sections_in_order_with_favorites_first() {
+        let contacts = [contact(1, "a", 0, false), contact(2, "b", 2, false), contact(3, "c", 0, true), contact(4, "d", 0, false)];
+        let pinned = [4];
+        assert_eq!(rows(&contacts, "", &pinned, &[], all), vec![
+            Row::Section(Section::Pinned, 1), Row::Contact(3),
+            Row::Section(Section::Unread, 1), Row::Contact(1),
+            Row::Section(Section::Chats, 2), Row::Contact(2), Row::Contact(0),
+        ]);
+    }
+    
+    #[test]
+    fn pinned_keep_pinning_order() {
+        let contacts = [contact(1, "a", 0, false), contact(2, "b", 0, false)];
+        let pinned = [2, 1];
+        assert_eq!(rows(&contacts, "", &pinned, &[], all), vec![Row::Section(Section::Pinned, 2), Row::Contact(1), Row::Contact(0)]);
+    }
+    
+    #[test]
+    fn collapsed_sections_hide_members_unless_filtering() {
+        let contacts = [contact(1, "alice", 1, false), contact(2, "bob", 0, false)];
+        let collapsed = ["unread".to_string()];
+        assert_eq!(rows(&contacts, "", &[], &collapsed, all), vec![
+            Row::Section(Section::Unread, 1),
+            Row::Section(Section::Chats, 1), Row::Contact(1),
+        ]);
+        assert_eq!(rows(&contacts, "ALI", &[], &collapsed, all), vec![Row::Section(Section::Unread, 1), Row::Contact(0)]);
+    }
+    
+    #[test]
+    fn filter_matches_alias_and_keep_applies() {
+        let mut contacts = [contact(1, "alice", 0, false), contact(2, "bob", 0, false)];
+        contacts[1].alias = "Robert".into();
+        assert_eq!(rows(&contacts, "rob", &[], &[], all), vec![Row::Contact(1)]);
+        assert_eq!(rows(&contacts, "", &[], &[], |c| c.name != "alice"), vec![Row::Contact(1)]);
+    }
+}
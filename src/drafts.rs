@@ -0,0 +1,291 @@
+//! Per-conversation drafts and sent-message history
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use aes_gcm::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+use crate::config::{self, DraftsConfig};
+
+const HISTORY_LIMIT: usize = 50;
+const MAGIC: &[u8] = b"STD1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Conversation {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    draft: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    history: Vec<String>,
+}
+
+/// User profile id and contact id; names repeat across profiles
+pub type ChatKey = (i64, i64);
+
+/// Drafts and history per user profile and contact, stored in
+/// `drafts.json`, or encrypted in `drafts.enc` when `encrypt` is set in
+/// the config. Without a path they are kept in memory only.
+#[derive(Default)]
+pub struct Drafts {
+    /// Keyed `user/contact`, as JSON object keys have to be strings; older
+    /// versions keyed by contact name until `adopt_names` moves them
+    chats: HashMap<String, Conversation>,
+    path: Option<PathBuf>,
+    cipher: Option<([u8; SALT_LEN], Aes256Gcm)>,
+    dirty: bool,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    let params = scrypt::Params::new(15, 8, 1, 32).map_err(|e| anyhow!("{}", e))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key).map_err(|e| anyhow!("{}", e))?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+fn key((user, contact): ChatKey) -> String {
+    format!("{}/{}", user, contact)
+}
+
+fn is_chat_key(key: &str) -> bool {
+    key.split_once('/').is_some_and(|(user, contact)| user.parse::<i64>().is_ok() && contact.parse::<i64>().is_ok())
+}
+
+/// Encrypts `plain` as magic, salt, nonce and ciphertext
+fn seal(plain: &[u8], salt: &[u8; SALT_LEN], cipher: &Aes256Gcm) -> Result<Vec<u8>> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let body = cipher.encrypt(&nonce, plain).map_err(|_| anyhow!("cannot encrypt drafts"))?;
+    Ok([MAGIC, salt.as_slice(), nonce.as_slice(), &body].concat())
+}
+
+/// Decrypts what `seal` wrote and returns the salt and cipher to reuse
+fn unseal(data: &[u8], passphrase: &str) -> Result<([u8; SALT_LEN], Aes256Gcm, Vec<u8>)> {
+    if data.len() < MAGIC.len() + SALT_LEN + NONCE_LEN || !data.starts_with(MAGIC) {
+        bail!("not an encrypted drafts file");
+    }
+    let (salt, rest) = data[MAGIC.len()..].split_at(SALT_LEN);
+    let (nonce, body) = rest.split_at(NONCE_LEN);
+    let cipher = derive_key(passphrase, salt)?;
+    let plain = cipher.decrypt(Nonce::from_slice(nonce), body).map_err(|_| anyhow!("wrong passphrase"))?;
+    Ok((salt.try_into()?, cipher, plain))
+}
+
+/// Reads a passphrase from the terminal without echoing it
+fn prompt_passphrase(prompt: &str) -> Result<String> {
+    eprint!("{}", prompt);
+    std::io::stderr().flush()?;
+    enable_raw_mode()?;
+    let mut passphrase = String::new();
+    let result = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Enter => break Ok(()),
+                KeyCode::Backspace => { passphrase.pop(); }
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break Err(anyhow!("cancelled")),
+                KeyCode::Char(c) => passphrase.push(c),
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e.into()),
+        }
+    };
+    disable_raw_mode()?;
+    eprintln!();
+    result.map(|_| passphrase)
+}
+
+impl Drafts {
+    /// Reads the stored drafts, asking for the passphrase first when they
+    /// are encrypted
+    pub fn open(config: &DraftsConfig) -> Result<Self> {
+        let Some(dir) = config::data_dir().filter(|_| config.persist) else { return Ok(Self::default()) };
+        if config.encrypt { Self::open_encrypted(dir.join("drafts.enc")) } else { Self::load_plain(dir.join("drafts.json")) }
+    }
+    
+    fn load_plain(path: PathBuf) -> Result<Self> {
+        let chats = match fs::read_to_string(&path) {
+            Ok(txt) => serde_json::from_str(&txt).with_context(|| format!("invalid drafts {}", path.display()))?,
+            Err(_) => HashMap::new(),
+        };
+        Ok(Self { chats, path: Some(path), cipher: None, dirty: false })
+    }
+    
+    /// An empty passphrase keeps this run's drafts in memory and leaves the
+    /// stored ones alone; a wrong one is an error so nothing gets overwritten
+    fn open_encrypted(path: PathBuf) -> Result<Self> {
+        let exists = path.exists();
+        let passphrase = prompt_passphrase("Drafts passphrase (empty keeps drafts in memory only): ")?;
+        if passphrase.is_empty() { return Ok(Self::default()); }
+        if !exists && prompt_passphrase("Repeat the passphrase: ")? != passphrase {
+            bail!("passphrases do not match");
+        }
+        Self::load(path, &passphrase)
+    }
+    
+    fn load(path: PathBuf, passphrase: &str) -> Result<Self> {
+        let (cipher, chats) = match fs::read(&path) {
+            Ok(data) => {
+                let (salt, cipher, plain) = unseal(&data, passphrase)
+                    .with_context(|| format!("cannot decrypt {}", path.display()))?;
+                ((salt, cipher), serde_json::from_slice(&plain)?)
+            }
+            Err(_) => {
+                let mut salt = [0u8; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                ((salt, derive_key(passphrase, &salt)?), HashMap::new())
+            }
+        };
+        Ok(Self { chats, path: Some(path), cipher: Some(cipher), dirty: false })
+    }
+    
+    pub fn draft(&self, chat: ChatKey) -> &str {
+        self.chats.get(&key(chat)).map(|c| c.draft.as_str()).unwrap_or("")
+    }
+    
+    pub fn set_draft(&mut self, chat: ChatKey, text: &str) {
+        if self.draft(chat) == text { return; }
+        self.chats.entry(key(chat)).or_default().draft = text.to_string();
+        self.dirty = true;
+    }
+    
+    /// Sent messages for a chat, oldest first
+    pub fn history(&self, chat: ChatKey) -> &[String] {
+        self.chats.get(&key(chat)).map(|c| c.history.as_slice()).unwrap_or(&[])
+    }
+    
+    pub fn push_sent(&mut self, chat: ChatKey, text: &str) {
+        let conv = self.chats.entry(key(chat)).or_default();
+        conv.draft.clear();
+        conv.history.retain(|h| h != text);
+        conv.history.push(text.to_string());
+        if conv.history.len() > HISTORY_LIMIT { conv.history.remove(0); }
+        self.dirty = true;
+    }
+    
+    pub fn remove(&mut self, chat: ChatKey) {
+        if self.chats.remove(&key(chat)).is_some() { self.dirty = true; }
+    }
+    
+    /// Moves drafts an older version stored by contact name over to the
+    /// ids of `user`'s contacts; names other profiles know are left for them
+    pub fn adopt_names<'a>(&mut self, user: i64, contacts: impl IntoIterator<Item = (&'a str, i64)>) {
+        for (name, id) in contacts {
+            if is_chat_key(name) || self.chats.contains_key(&key((user, id))) { continue; }
+            if let Some(conv) = self.chats.remove(name) {
+                self.chats.insert(key((user, id)), conv);
+                self.dirty = true;
+            }
+        }
+    }
+    
+    /// Writes the store if anything changed, readable by the owner only;
+    /// encrypted drafts are never written in the clear
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty { return Ok(()); }
+        let Some(path) = &self.path else { return Ok(()) };
+        self.chats.retain(|_, c| !c.draft.is_empty() || !c.history.is_empty());
+        let json = serde_json::to_vec(&self.chats)?;
+        let data = match &self.cipher {
+            Some((salt, cipher)) => seal(&json, salt, cipher)?,
+            None => json,
+        };
+        
+        if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+        opts.open(path)
+            .and_then(|mut f| f.write_all(&data))
+            .with_context(|| format!("cannot write {}", path.display()))?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn sealed_drafts_round_trip() {
+        let salt = [7u8; SALT_LEN];
+        let cipher = derive_key("correct horse", &salt).unwrap();
+        let data = seal(b"{\"alice\":{}}", &salt, &cipher).unwrap();
+        assert!(data.starts_with(MAGIC));
+        
+        let (read_salt, _, plain) = unseal(&data, "correct horse").unwrap();
+        assert_eq!(read_salt, salt);
+        assert_eq!(plain, b"{\"alice\":{}}");
+    }
+    
+    #[test]
+    fn wrong_passphrase_is_an_error() {
+        let salt = [1u8; SALT_LEN];
+        let data = seal(b"secret", &salt, &derive_key("right", &salt).unwrap()).unwrap();
+        let err = unseal(&data, "wrong").err().unwrap();
+        assert_eq!(err.to_string(), "wrong passphrase");
+    }
+    
+    #[test]
+    fn rejects_foreign_files() {
+        assert!(unseal(b"{\"alice\":{}}", "any").is_err());
+        assert!(unseal(MAGIC, "any").is_err());
+    }
+    
+    #[test]
+    fn saves_nothing_without_a_path() {
+        let mut drafts = Drafts::default();
+        drafts.push_sent((1, 5), "hi");
+        drafts.save().unwrap();
+        assert_eq!(drafts.history((1, 5)), ["hi"]);
+    }
+    
+    #[test]
+    fn plain_drafts_round_trip() {
+        let path = std::env::temp_dir().join(format!("simplex-tui-drafts-{}.json", std::process::id()));
+        let mut drafts = Drafts { path: Some(path.clone()), ..Drafts::default() };
+        drafts.set_draft((1, 5), "later");
+        drafts.save().unwrap();
+        let drafts = Drafts::load_plain(path.clone()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(drafts.draft((1, 5)), "later");
+    }
+    
+    #[test]
+    fn adopts_drafts_stored_by_name() {
+        let mut drafts = Drafts::default();
+        drafts.chats.insert("alice".into(), Conversation { draft: "old".into(), history: vec!["sent".into()] });
+        drafts.chats.insert("bob".into(), Conversation { draft: "other".into(), history: vec![] });
+        drafts.adopt_names(1, [("alice", 5), ("carol", 6)]);
+        assert_eq!(drafts.draft((1, 5)), "old");
+        assert_eq!(drafts.history((1, 5)), ["sent"]);
+        // Names not in this profile wait for another one
+        drafts.adopt_names(2, [("bob", 5)]);
+        assert_eq!(drafts.draft((2, 5)), "other");
+        assert!(!drafts.chats.contains_key("alice") && !drafts.chats.contains_key("bob"));
+    }
+    
+    #[test]
+    fn same_contact_id_in_another_profile_is_separate() {
+        let mut drafts = Drafts::default();
+        drafts.set_draft((1, 5), "draft");
+        drafts.push_sent((1, 5), "sent");
+        drafts.set_draft((1, 5), "draft");
+        assert_eq!(drafts.draft((2, 5)), "");
+        assert!(drafts.history((2, 5)).is_empty());
+        assert_eq!(drafts.draft((1, 5)), "draft");
+    }
+}
@@ -0,0 +1,452 @@
+//! Multi-line text editor used by the message composer
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const UNDO_LIMIT: usize = 200;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum EditKind { Insert, Delete, Other }
+
+#[derive(Clone, Debug)]
+struct Snapshot { text: String, cursor: usize }
+
+/// Text buffer with a grapheme-aligned cursor (byte offset), a kill buffer
+/// and undo/redo history. Knows nothing about keys or rendering.
+#[derive(Clone, Debug, Default)]
+pub struct Editor {
+    text: String,
+    cursor: usize,
+    kill: String,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    last_edit: Option<EditKind>,
+}
+
+fn is_word(g: &str) -> bool {
+    g.chars().any(|c| c.is_alphanumeric() || c == '_')
+}
+
+impl Editor {
+    pub fn text(&self) -> &str { &self.text }
+    pub fn is_empty(&self) -> bool { self.text.is_empty() }
+    pub fn cursor(&self) -> usize { self.cursor }
+    
+    /// Places the cursor at a byte offset, clamped to the text and moved
+    /// back to the start of the grapheme it falls in
+    pub fn set_cursor(&mut self, pos: usize) {
+        self.cursor = if pos >= self.text.len() {
+            self.text.len()
+        } else {
+            self.text.grapheme_indices(true).map(|(i, _)| i).take_while(|&i| i <= pos).last().unwrap_or(0)
+        };
+        self.moved();
+    }
+    
+    /// Clears the buffer and returns what was in it, history is reset
+    pub fn take(&mut self) -> String {
+        self.cursor = 0;
+        self.undo.clear();
+        self.redo.clear();
+        self.last_edit = None;
+        std::mem::take(&mut self.text)
+    }
+    
+    /// Replaces the buffer, e.g. with a restored draft; history is reset
+    pub fn set_text(&mut self, text: &str) {
+        self.take();
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+    }
+    
+    fn checkpoint(&mut self, kind: EditKind) {
+        // Runs of typing or deleting collapse into one undo step
+        if kind != EditKind::Other && self.last_edit == Some(kind) { return; }
+        self.undo.push(Snapshot { text: self.text.clone(), cursor: self.cursor });
+        if self.undo.len() > UNDO_LIMIT { self.undo.remove(0); }
+        self.redo.clear();
+        self.last_edit = Some(kind);
+    }
+    
+    fn moved(&mut self) { self.last_edit = None; }
+    
+    pub fn undo(&mut self) -> bool {
+        let Some(snap) = self.undo.pop() else { return false };
+        self.redo.push(Snapshot { text: std::mem::replace(&mut self.text, snap.text), cursor: self.cursor });
+        self.cursor = snap.cursor;
+        self.last_edit = None;
+        true
+    }
+    
+    pub fn redo(&mut self) -> bool {
+        let Some(snap) = self.redo.pop() else { return false };
+        self.undo.push(Snapshot { text: std::mem::replace(&mut self.text, snap.text), cursor: self.cursor });
+        self.cursor = snap.cursor;
+        self.last_edit = None;
+        true
+    }
+    
+    // ---- insertion ----
+    
+    pub fn insert_char(&mut self, c: char) {
+        let kind = if c.is_whitespace() { EditKind::Other } else { EditKind::Insert };
+        self.checkpoint(kind);
+        self.text.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.snap_cursor();
+    }
+    
+    pub fn newline(&mut self) { self.insert_char('\n'); }
+    
+    /// Inserts pasted text as one undo step, normalizing line endings
+    pub fn insert_str(&mut self, s: &str) {
+        if s.is_empty() { return; }
+        self.checkpoint(EditKind::Other);
+        let s = s.replace("\r\n", "\n").replace('\r', "\n");
+        self.text.insert_str(self.cursor, &s);
+        self.cursor += s.len();
+        self.last_edit = None;
+    }
+    
+    // ---- grapheme helpers ----
+    
+    pub fn prev_boundary(&self, from: usize) -> usize {
+        self.text[..from].grapheme_indices(true).next_back().map(|(i, _)| i).unwrap_or(0)
+    }
+    
+    pub fn next_boundary(&self, from: usize) -> usize {
+        self.text[from..].graphemes(true).next().map(|g| from + g.len()).unwrap_or(self.text.len())
+    }
+    
+    /// Keeps the cursor on a grapheme boundary after a combining mark was typed
+    fn snap_cursor(&mut self) {
+        if self.cursor == 0 { return; }
+        let end = self.next_boundary(self.prev_boundary(self.cursor));
+        if end > self.cursor { self.cursor = end; }
+    }
+    
+    pub fn word_left_pos(&self, from: usize) -> usize {
+        let mut pos = from;
+        let graphemes: Vec<(usize, &str)> = self.text[..pos].grapheme_indices(true).collect();
+        let mut iter = graphemes.iter().rev().peekable();
+        while let Some((i, g)) = iter.peek() { if is_word(g) { break; } pos = *i; iter.next(); }
+        while let Some((i, g)) = iter.peek() { if !is_word(g) { break; } pos = *i; iter.next(); }
+        pos
+    }
+    
+    pub fn word_right_pos(&self, from: usize) -> usize {
+        let mut pos = from;
+        let mut iter = self.text[pos..].graphemes(true).peekable();
+        while let Some(g) = iter.peek() { if is_word(g) { break; } pos += g.len(); iter.next(); }
+        while let Some(g) = iter.peek() { if !is_word(g) { break; } pos += g.len(); iter.next(); }
+        pos
+    }
+    
+    /// Start of the next word, skipping the rest of the current one
+    pub fn word_start_pos(&self, from: usize) -> usize {
+        let mut pos = from;
+        let mut iter = self.text[pos..].graphemes(true).peekable();
+        while let Some(g) = iter.peek() { if !is_word(g) { break; } pos += g.len(); iter.next(); }
+        while let Some(g) = iter.peek() { if is_word(g) { break; } pos += g.len(); iter.next(); }
+        pos
+    }
+    
+    pub fn line_start_at(&self, from: usize) -> usize {
+        self.text[..from].rfind('\n').map(|i| i + 1).unwrap_or(0)
+    }
+    
+    pub fn line_end_at(&self, from: usize) -> usize {
+        self.text[from..].find('\n').map(|i| from + i).unwrap_or(self.text.len())
+    }
+    
+    fn line_start(&self) -> usize { self.line_start_at(self.cursor) }
+    fn line_end(&self) -> usize { self.line_end_at(self.cursor) }
+    
+    // ---- motion ----
+    
+    pub fn move_left(&mut self) { self.cursor = self.prev_boundary(self.cursor); self.moved(); }
+    pub fn move_right(&mut self) { self.cursor = self.next_boundary(self.cursor); self.moved(); }
+    pub fn move_word_left(&mut self) { self.cursor = self.word_left_pos(self.cursor); self.moved(); }
+    pub fn move_word_right(&mut self) { self.cursor = self.word_right_pos(self.cursor); self.moved(); }
+    pub fn move_line_start(&mut self) { self.cursor = self.line_start(); self.moved(); }
+    pub fn move_line_end(&mut self) { self.cursor = self.line_end(); self.moved(); }
+    pub fn move_start(&mut self) { self.cursor = 0; self.moved(); }
+    pub fn move_end(&mut self) { self.cursor = self.text.len(); self.moved(); }
+    
+    /// Same display column on the line above or below `from`, if there is one
+    pub fn vertical_pos(&self, from: usize, down: bool) -> Option<usize> {
+        let start = self.line_start_at(from);
+        let col = self.text[start..from].width();
+        if down {
+            let end = self.line_end_at(from);
+            if end == self.text.len() { return None; }
+            let next_start = end + 1;
+            Some(self.column_pos(next_start, self.line_end_at(next_start), col))
+        } else {
+            if start == 0 { return None; }
+            Some(self.column_pos(self.line_start_at(start - 1), start - 1, col))
+        }
+    }
+    
+    /// Moves to the same column on the previous line; false on the first line
+    pub fn move_up(&mut self) -> bool {
+        let Some(pos) = self.vertical_pos(self.cursor, false) else { return false };
+        self.cursor = pos;
+        self.moved();
+        true
+    }
+    
+    /// Moves to the same column on the next line; false on the last line
+    pub fn move_down(&mut self) -> bool {
+        let Some(pos) = self.vertical_pos(self.cursor, true) else { return false };
+        self.cursor = pos;
+        self.moved();
+        true
+    }
+    
+    /// Last grapheme boundary in `start..end` at or left of display column `col`
+    fn column_pos(&self, start: usize, end: usize, col: usize) -> usize {
+        let mut width = 0;
+        for (i, g) in self.text[start..end].grapheme_indices(true) {
+            width += g.width();
+            if width > col { return start + i; }
+        }
+        end
+    }
+    
+    // ---- deletion ----
+    
+    /// Removes a byte range as one undo step and returns it
+    pub fn remove(&mut self, from: usize, to: usize) -> String {
+        let to = to.min(self.text.len());
+        let removed = self.delete_range(from.min(to), to, EditKind::Other);
+        self.last_edit = None;
+        removed
+    }
+    
+    fn delete_range(&mut self, from: usize, to: usize, kind: EditKind) -> String {
+        if from >= to { return String::new(); }
+        self.checkpoint(kind);
+        let removed: String = self.text.drain(from..to).collect();
+        self.cursor = from;
+        removed
+    }
+    
+    pub fn backspace(&mut self) {
+        let from = self.prev_boundary(self.cursor);
+        self.delete_range(from, self.cursor, EditKind::Delete);
+    }
+    
+    pub fn delete(&mut self) {
+        let to = self.next_boundary(self.cursor);
+        self.delete_range(self.cursor, to, EditKind::Delete);
+    }
+    
+    pub fn delete_word_left(&mut self) {
+        let from = self.word_left_pos(self.cursor);
+        let killed = self.delete_range(from, self.cursor, EditKind::Other);
+        if !killed.is_empty() { self.kill = killed; }
+    }
+    
+    pub fn delete_word_right(&mut self) {
+        let to = self.word_right_pos(self.cursor);
+        let killed = self.delete_range(self.cursor, to, EditKind::Other);
+        if !killed.is_empty() { self.kill = killed; }
+    }
+    
+    /// Ctrl-K: kills to end of line, or the newline itself at the end of a line
+    pub fn kill_to_line_end(&mut self) {
+        let end = self.line_end();
+        let end = if end == self.cursor { self.next_boundary(end) } else { end };
+        let killed = self.delete_range(self.cursor, end, EditKind::Other);
+        if !killed.is_empty() { self.kill = killed; }
+    }
+    
+    pub fn kill_to_line_start(&mut self) {
+        let start = self.line_start();
+        let killed = self.delete_range(start, self.cursor, EditKind::Other);
+        if !killed.is_empty() { self.kill = killed; }
+    }
+    
+    pub fn yank(&mut self) {
+        let kill = self.kill.clone();
+        self.insert_str(&kill);
+    }
+    
+    // ---- layout ----
+    
+    /// Soft-wraps the text to `width` cells and returns the visual lines
+    /// together with the cursor's (row, column) in cells
+    pub fn layout(&self, width: usize) -> (Vec<String>, (usize, usize)) {
+        let (lines, pos) = self.layout_positions(width, &[self.cursor]);
+        (lines, pos[0])
+    }
+    
+    /// Like `layout`, but locates any number of byte offsets
+    pub fn layout_positions(&self, width: usize, offsets: &[usize]) -> (Vec<String>, Vec<(usize, usize)>) {
+        let width = width.max(1);
+        let mut lines = vec![String::new()];
+        let mut line_w = 0;
+        let mut found = vec![None; offsets.len()];
+        let mark = |found: &mut Vec<Option<(usize, usize)>>, i: usize, at: (usize, usize)| {
+            for (slot, off) in found.iter_mut().zip(offsets) {
+                if *off == i && slot.is_none() { *slot = Some(at); }
+            }
+        };
+        
+        for (i, g) in self.text.grapheme_indices(true) {
+            if g == "\n" || g == "\r\n" {
+                mark(&mut found, i, (lines.len() - 1, line_w));
+                lines.push(String::new());
+                line_w = 0;
+                continue;
+            }
+            let gw = g.width();
+            if line_w + gw > width {
+                lines.push(String::new());
+                line_w = 0;
+            }
+            mark(&mut found, i, (lines.len() - 1, line_w));
+            lines.last_mut().unwrap().push_str(g);
+            line_w += gw;
+        }
+        if line_w >= width && offsets.contains(&self.text.len()) { lines.push(String::new()); line_w = 0; }
+        let end = (lines.len() - 1, line_w);
+        (lines, found.into_iter().map(|f| f.unwrap_or(end)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn editor(text: &str, cursor: usize) -> Editor {
+        let mut ed = Editor::default();
+        ed.set_text(text);
+        ed.set_cursor(cursor);
+        ed
+    }
+    
+    #[test]
+    fn vertical_moves_keep_display_column() {
+        // Cursor after two wide characters is at column 4
+        let mut ed = editor("你好x\nabcdef", "你好".len());
+        assert!(ed.move_down());
+        assert_eq!(&ed.text()[ed.line_start_at(ed.cursor())..ed.cursor()], "abcd");
+        assert!(ed.move_up());
+        assert_eq!(ed.cursor(), "你好".len());
+    }
+    
+    #[test]
+    fn vertical_move_into_wide_character_stops_before_it() {
+        let mut ed = editor("abc\n你好", 3);
+        assert!(ed.move_down());
+        assert_eq!(&ed.text()[ed.line_start_at(ed.cursor())..ed.cursor()], "你");
+        let mut ed = editor("a\n😀b", 1);
+        assert!(ed.move_down());
+        assert_eq!(ed.cursor(), 2);
+    }
+    
+    #[test]
+    fn vertical_move_clamps_to_short_line() {
+        let mut ed = editor("abcdef\nab", 5);
+        assert!(ed.move_down());
+        assert_eq!(ed.cursor(), ed.text().len());
+        assert!(!ed.move_down());
+    }
+    
+    #[test]
+    fn set_cursor_snaps_to_grapheme_start() {
+        // "e" plus a combining acute accent is one grapheme of three bytes
+        let mut ed = editor("ae\u{301}b你", 0);
+        ed.set_cursor(2);
+        assert_eq!(ed.cursor(), 1);
+        ed.set_cursor(3);
+        assert_eq!(ed.cursor(), 1);
+        ed.set_cursor(6);
+        assert_eq!(ed.cursor(), 5);
+        ed.set_cursor(100);
+        assert_eq!(ed.cursor(), ed.text().len());
+        ed.backspace();
+        assert_eq!(ed.text(), "ae\u{301}b");
+    }
+    
+    #[test]
+    fn word_motions_step_over_graphemes() {
+        let text = "he\u{301}llo, 👍🏽 wörld";
+        let mut ed = editor(text, 0);
+        ed.move_word_right();
+        assert_eq!(&text[..ed.cursor()], "he\u{301}llo");
+        ed.move_word_right();
+        assert_eq!(ed.cursor(), text.len());
+        ed.move_word_left();
+        assert_eq!(&text[ed.cursor()..], "wörld");
+        ed.move_word_left();
+        assert_eq!(ed.cursor(), 0);
+        assert_eq!(&text[ed.word_start_pos(0)..], "wörld");
+    }
+    
+    #[test]
+    fn kill_and_yank() {
+        let mut ed = editor("one two\nthree", 4);
+        ed.kill_to_line_end();
+        assert_eq!(ed.text(), "one \nthree");
+        // At the end of a line the newline itself goes
+        ed.kill_to_line_end();
+        assert_eq!(ed.text(), "one three");
+        ed.move_end();
+        ed.delete_word_left();
+        assert_eq!(ed.text(), "one ");
+        ed.move_start();
+        ed.yank();
+        assert_eq!(ed.text(), "threeone ");
+        ed.set_cursor(5);
+        ed.kill_to_line_start();
+        ed.move_end();
+        ed.yank();
+        assert_eq!(ed.text(), "one three");
+    }
+    
+    #[test]
+    fn undo_groups_typing_and_deleting() {
+        let mut ed = Editor::default();
+        for c in "hi there".chars() { ed.insert_char(c); }
+        ed.backspace();
+        ed.backspace();
+        assert_eq!(ed.text(), "hi the");
+        assert!(ed.undo());
+        assert_eq!(ed.text(), "hi there");
+        // Typing after a space is a step of its own
+        assert!(ed.undo());
+        assert_eq!(ed.text(), "hi ");
+        assert!(ed.undo());
+        assert!(ed.undo());
+        assert_eq!(ed.text(), "");
+        assert!(!ed.undo());
+        assert!(ed.redo());
+        assert_eq!(ed.text(), "hi");
+        // Moving starts a new step
+        ed.move_end();
+        ed.insert_char('!');
+        ed.move_left();
+        ed.insert_char('?');
+        assert!(ed.undo());
+        assert_eq!(ed.text(), "hi!");
+        assert!(ed.redo());
+        assert_eq!(ed.text(), "hi?!");
+    }
+    
+    #[test]
+    fn paste_normalizes_newlines_in_one_step() {
+        let mut ed = editor("ab", 1);
+        ed.insert_str("x\r\ny\rz");
+        assert_eq!(ed.text(), "ax\ny\nzb");
+        assert_eq!(ed.cursor(), 6);
+        assert!(ed.undo());
+        assert_eq!(ed.text(), "ab");
+    }
+}
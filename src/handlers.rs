@@ -7,9 +7,15 @@
 use std::time::Duration;
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyModifiers, MouseEventKind, MouseButton};
+use ratatui::layout::Rect;
 
+use crate::actions::{Action, Context, KeyPress, Resolved};
 use crate::app::App;
+use crate::clipboard;
+use crate::hitmap::{Drag, Hit};
+use crate::profile::Field;
 use crate::types::{Mode, Panel};
+use crate::vi::{ViMode, ViOutcome};
 
 pub fn handle_events(app: &mut App) -> Result<()> {
     if !event::poll(Duration::from_millis(50))? { return Ok(()); }
@@ -17,273 +23,551 @@ pub fn handle_events(app: &mut App) -> Result<()> {
     match event::read()? {
         Event::Key(key) => handle_key(app, key.code, key.modifiers),
         Event::Mouse(mouse) => handle_mouse(app, mouse),
+        Event::Paste(text) => handle_paste(app, &text),
         _ => {}
     }
     Ok(())
 }
 
 fn handle_mouse(app: &mut App, mouse: event::MouseEvent) {
-    let x = mouse.column;
-    let y = mouse.row;
+    let (x, y) = (mouse.column, mouse.row);
+    let hit = app.hits.at(x, y);
     
     match mouse.kind {
         MouseEventKind::Down(MouseButton::Left) => {
-            match app.mode {
-                Mode::ContactOptions => {
-                        for (i, btn_opt) in app.btn_contact_options.iter().enumerate() {
-                            if let Some(btn) = btn_opt {
-                                if x >= btn.x && x < btn.x + btn.width && y >= btn.y && y < btn.y + btn.height {
-                                    app.option_selection = i;
-                                    app.execute_selected_option();
-                                    return;
-                                }
-                            }
-                        }
-                }
-                Mode::AddContact => {
-                        if let Some(btn) = app.btn_refresh {
-                            if x >= btn.x && x < btn.x + btn.width && y >= btn.y && y < btn.y + btn.height {
-                                app.request_address();
-                                return;
-                            }
-                        }
-                        if let Some(btn) = app.btn_create {
-                            if x >= btn.x && x < btn.x + btn.width && y >= btn.y && y < btn.y + btn.height {
-                                app.create_address();
-                                return;
-                            }
-                        }
-                        if let Some(btn) = app.btn_close {
-                            if x >= btn.x && x < btn.x + btn.width && y >= btn.y && y < btn.y + btn.height {
-                                app.mode = Mode::Normal;
-                                app.connect_input.clear();
-                                return;
-                            }
-                        }
-                }
-                Mode::Normal | Mode::Input => {
-                    let term_height = crossterm::terminal::size().map(|(_, h)| h).unwrap_or(24);
-                    
-                    if x < 32 {
-                        if y >= term_height - 3 && y < term_height - 1 {
-                            if x < 10 {
-                                app.mode = Mode::AddContact;
-                                app.request_address();
-                                return;
-                            } else if x < 20 {
-                                app.refresh_chat();
-                                app.send_cmd("/contacts");
-                                return;
-                            } else if x < 32 {
-                                app.show_help = !app.show_help;
-                                return;
-                            }
-                        }
-                        
-                        if y > 2 && y < term_height - 3 {
-                            let idx = ((y - 3) / 2) as usize;
-                            if idx < app.contacts.len() {
-                                if app.check_double_click(idx) {
-                                    let contact_name = app.contacts[idx].name.clone();
-                                    app.open_contact_options(contact_name);
-                                } else {
-                                    app.contact_state.select(Some(idx));
-                                    app.select_contact();
-                                }
-                            }
-                        }
-                    }
-                    
-                    if let Some(input_y) = app.last_input_y {
-                        if y >= input_y && y < input_y + 3 && x > 32 {
-                            app.panel = Panel::Input;
-                            app.mode = Mode::Input;
-                        }
-                    }
+            if let Some((rect, hit)) = hit { handle_click(app, rect, hit, y); }
+        }
+        MouseEventKind::Drag(MouseButton::Left) => match app.dragging {
+            Some(Drag::Divider { left }) => app.resize_contacts(x.saturating_sub(left), false),
+            Some(Drag::Scrollbar { track, total, visible }) => scroll_to(app, track, total, visible, y),
+            None => {}
+        },
+        MouseEventKind::Up(MouseButton::Left) => {
+            if let Some(Drag::Divider { .. }) = app.dragging.take() {
+                app.resize_contacts(app.ui_state.contacts_width, true);
+            }
+        }
+        MouseEventKind::ScrollUp | MouseEventKind::ScrollDown => {
+            let down = mouse.kind == MouseEventKind::ScrollDown;
+            match hit.map(|(_, hit)| hit) {
+                Some(Hit::Folder(_)) => app.run_action(if down { Action::NextFolder } else { Action::PrevFolder }),
+                Some(Hit::Contact(_) | Hit::Section(_)) if down => app.next_contact(),
+                Some(Hit::Contact(_) | Hit::Section(_)) => app.prev_contact(),
+                Some(Hit::PaletteItem(_)) => app.palette.move_selection(down),
+                Some(Hit::SearchResult(_)) => app.global_search.move_selection(down),
+                Some(Hit::User(_)) => app.users.move_selection(down),
+                Some(Hit::LinkRow(_)) => app.run_action(if down { Action::NextLink } else { Action::PrevLink }),
+                Some(Hit::Action(Action::Help)) => app.scroll_help(if down { 3 } else { -3 }),
+                Some(Hit::Backdrop) => {}
+                _ if down => app.scroll_down(),
+                _ => app.scroll_up(),
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_click(app: &mut App, rect: Rect, hit: Hit, y: u16) {
+    let double = app.check_double_click(&hit);
+    match hit {
+        Hit::Action(action) => app.run_action(action),
+        Hit::Contact(i) if double => {
+            if let Some(contact) = app.contacts.get(i) {
+                let name = contact.name.clone();
+                app.open_contact_options(name);
+            }
+        }
+        Hit::Contact(i) => {
+            app.select_contact_at(i);
+            app.run_action(Action::OpenChat);
+        }
+        Hit::Message(i) => {
+            app.panel = Panel::Chat;
+            app.mode = Mode::Normal;
+            if double {
+                if let Some(msg) = app.messages.get(i) {
+                    clipboard::copy(&msg.content, &app.config.editor);
+                    app.status = "Message copied".into();
                 }
+            }
+        }
+        Hit::Section(section) => app.toggle_section(Some(section)),
+        Hit::Folder(i) => {
+            if let Some(folder) = app.folders().get(i).cloned() { app.set_folder(folder); }
+        }
+        Hit::Voice(i) => app.toggle_voice_at(Some(i)),
+        Hit::Link(url) => app.open_link(&url),
+        Hit::ContactOption(i) => {
+            app.option_selection = i;
+            app.execute_selected_option();
+        }
+        Hit::PaletteItem(i) => {
+            app.palette.selected = i;
+            app.mode = Mode::Normal;
+            if let Some(action) = app.palette.selected_action() {
+                app.run_action(action);
+            }
+        }
+        Hit::LinkRow(row) => app.select_link(row),
+        Hit::PrefRow(i) => {
+            let Some(editor) = app.prefs_editor.as_mut() else { return };
+            editor.selected = i;
+            if double { editor.cycle(true); }
+        }
+        Hit::ProfileRow(i) => {
+            let Some(editor) = app.profile_editor.as_mut() else { return };
+            editor.selected = i;
+            match editor.field() {
+                Field::Preference(_) if double => editor.cycle(true),
+                Field::Picture if double => app.open_file_browser(),
                 _ => {}
             }
         }
-        MouseEventKind::ScrollUp => app.scroll_up(),
-        MouseEventKind::ScrollDown => app.scroll_down(),
-        _ => {}
+        Hit::User(i) => {
+            app.users.selected = i;
+            app.users.confirm_delete = false;
+            if double { app.switch_user(); }
+        }
+        Hit::SearchResult(i) => {
+            app.global_search.selected = i;
+            if double { app.open_global_hit(); }
+        }
+        Hit::Scrollbar { total, visible } => {
+            app.dragging = Some(Drag::Scrollbar { track: rect, total, visible });
+            scroll_to(app, rect, total, visible, y);
+        }
+        Hit::Divider => {
+            app.dragging = Some(Drag::Divider { left: rect.x.saturating_sub(app.ui_state.contacts_width) });
+        }
+        Hit::Backdrop => {}
     }
 }
 
+/// Maps a row of the scrollbar track onto the scroll range
+fn scroll_to(app: &mut App, track: Rect, total: usize, visible: usize, y: u16) {
+    let row = y.saturating_sub(track.y).min(track.height.saturating_sub(1)) as usize;
+    let range = total.saturating_sub(visible);
+    app.scroll = row * range / (track.height as usize).saturating_sub(1).max(1);
+}
+
 fn handle_key(app: &mut App, code: KeyCode, mods: KeyModifiers) {
-    match code {
-        KeyCode::F(12) => { app.mode = Mode::Panic; return; }
-        _ => {}
+    let key = KeyPress::new(code, mods);
+    
+    // Global bindings work in every mode, even while typing
+    if let Resolved::Run(action) = app.keymap.resolve(&[key], &[Context::Anywhere]) {
+        // Nothing but Esc brings the chats back from panic mode
+        if app.mode == Mode::Panic && !matches!(action, Action::Quit | Action::Panic) { return; }
+        app.pending_keys.clear();
+        app.run_action(action);
+        return;
     }
     
-    if mods.contains(KeyModifiers::CONTROL) {
-        match code {
-            KeyCode::Char('c') | KeyCode::Char('q') => app.running = false,
-            KeyCode::Char('l') => app.send_cmd("/contacts"),
-            _ => {}
-        }
+    // The composer and prompts have their own Ctrl bindings
+    if mods.contains(KeyModifiers::CONTROL) && !matches!(app.mode, Mode::Input | Mode::Command | Mode::Normal) {
         return;
     }
     
     match app.mode {
         Mode::Panic => handle_panic(app, code),
-        Mode::AddContact => handle_add_contact(app, code),
-        Mode::ContactOptions => handle_contact_options(app, code),
+        Mode::AddContact => handle_add_contact(app, key),
+        Mode::ContactOptions => handle_contact_options(app, key),
         Mode::ContactInfo => handle_contact_info(app, code),
         Mode::FileBrowser => handle_file_browser(app, code),
-        Mode::Normal => handle_normal(app, code),
-        Mode::Input => handle_input(app, code),
+        Mode::ChatSearch => handle_chat_search(app, code),
+        Mode::Command => handle_command(app, code, mods),
+        Mode::Console => handle_console(app, code),
+        Mode::Palette => handle_palette(app, code),
+        Mode::GlobalSearch => handle_global_search(app, code),
+        Mode::ContactFilter => handle_contact_filter(app, code),
+        Mode::Verify => handle_verify(app, code),
+        Mode::Users => handle_users(app, code),
+        Mode::Profile => handle_profile(app, code),
+        Mode::ContactPrefs => handle_contact_prefs(app, code),
+        Mode::Normal => handle_normal(app, key),
+        Mode::Input => handle_input(app, code, mods),
     }
 }
 
 fn handle_panic(app: &mut App, code: KeyCode) {
     match code {
-        KeyCode::Esc => app.mode = Mode::Normal,
+        KeyCode::Esc => app.set_panic(false),
         KeyCode::Char('1') => app.status = "Screen locked".into(),
-        KeyCode::Char('2') => app.running = false,
+        KeyCode::Char('2') => app.run_action(Action::Quit),
         KeyCode::Char('3') => app.status = "NUKE initiated...".into(),
         KeyCode::Char('4') => app.status = "SSH stealth mode".into(),
         _ => {}
     }
 }
 
-fn handle_add_contact(app: &mut App, code: KeyCode) {
-    match code {
-        KeyCode::Esc => {
-            app.mode = Mode::Normal;
-            app.connect_input.clear();
+fn handle_add_contact(app: &mut App, key: KeyPress) {
+    // Letters go into the link field once it has text
+    let typing = !app.connect_input.is_empty() && matches!(key.code, KeyCode::Char(_));
+    if !typing {
+        if let Resolved::Run(action) = app.keymap.resolve(&[key], &[Context::AddContact]) {
+            app.run_action(action);
+            return;
+        }
+    }
+    match key.code {
+        KeyCode::Backspace => { app.connect_input.pop(); }
+        KeyCode::Char(c) if !c.is_control() => app.connect_input.push(c),
+        _ => {}
+    }
+}
+
+fn handle_contact_options(app: &mut App, key: KeyPress) {
+    if let Resolved::Run(action) = app.keymap.resolve(&[key], &[Context::ContactOptions]) {
+        app.run_action(action);
+    }
+}
+
+fn handle_normal(app: &mut App, key: KeyPress) {
+    if app.show_help {
+        let page = app.help_height.max(1) as isize;
+        match key.code {
+            KeyCode::Esc => { app.show_help = false; return; }
+            KeyCode::Char('j') | KeyCode::Down => { app.scroll_help(1); return; }
+            KeyCode::Char('k') | KeyCode::Up => { app.scroll_help(-1); return; }
+            KeyCode::PageDown | KeyCode::Char(' ') => { app.scroll_help(page); return; }
+            KeyCode::PageUp => { app.scroll_help(-page); return; }
+            KeyCode::Home => { app.help_scroll = 0; return; }
+            KeyCode::End => { app.help_scroll = usize::MAX; return; }
+            _ => {}
         }
+    }
+    
+    app.pending_keys.push(key);
+    match app.keymap.resolve(&app.pending_keys, Context::normal(app.panel)) {
+        Resolved::Run(action) => {
+            app.pending_keys.clear();
+            app.run_action(action);
+        }
+        Resolved::Pending => {}
+        Resolved::Unbound => {
+            // A key that breaks a chord still counts on its own
+            let retry = app.pending_keys.len() > 1;
+            app.pending_keys.clear();
+            if retry { handle_normal(app, key); }
+        }
+    }
+}
+
+fn handle_palette(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Esc => app.mode = Mode::Normal,
         KeyCode::Enter => {
-            if !app.connect_input.is_empty() {
-                app.connect_to_invite();
+            app.mode = Mode::Normal;
+            if let Some(action) = app.palette.selected_action() {
+                app.run_action(action);
             }
         }
-        KeyCode::Backspace => { app.connect_input.pop(); }
+        KeyCode::Up | KeyCode::BackTab => app.palette.move_selection(false),
+        KeyCode::Down | KeyCode::Tab => app.palette.move_selection(true),
+        KeyCode::Backspace => {
+            app.palette.query.pop();
+            app.palette.selected = 0;
+        }
         KeyCode::Char(c) => {
-            if app.connect_input.is_empty() {
-                match c {
-                    'g' => { app.request_address(); return; }
-                    'c' => { app.create_address(); return; }
-                    _ => {}
-                }
-            }
-            if !c.is_control() {
-                app.connect_input.push(c);
-            }
+            app.palette.query.push(c);
+            app.palette.selected = 0;
         }
         _ => {}
     }
 }
 
-fn handle_contact_options(app: &mut App, code: KeyCode) {
+/// Enter searches when the query was edited, otherwise opens the selected hit
+fn handle_global_search(app: &mut App, code: KeyCode) {
+    let gs = &mut app.global_search;
     match code {
-        KeyCode::Esc => {
-            if app.confirm_action.is_some() {
-                app.confirm_action = None;
-                app.status = "Action cancelled".into();
-            } else {
-                app.close_contact_options();
+        KeyCode::Esc => app.mode = Mode::Normal,
+        KeyCode::Enter if gs.input.trim() != gs.query || gs.hits.is_empty() => app.run_global_search(),
+        KeyCode::Enter => app.open_global_hit(),
+        KeyCode::Up | KeyCode::BackTab => gs.move_selection(false),
+        KeyCode::Down | KeyCode::Tab => gs.move_selection(true),
+        KeyCode::Backspace => { gs.input.pop(); }
+        KeyCode::Char(c) => gs.input.push(c),
+        _ => {}
+    }
+}
+
+fn handle_input(app: &mut App, code: KeyCode, mods: KeyModifiers) {
+    if let Some(vi) = app.vi.as_mut() {
+        match vi.handle_key(&mut app.composer, code, mods, &app.config.editor) {
+            ViOutcome::Handled => return,
+            ViOutcome::PassThrough => {}
+            ViOutcome::Send => {
+                vi.mode = ViMode::Insert;
+                app.send_message();
+                return;
+            }
+            ViOutcome::Leave => {
+                vi.mode = ViMode::Insert;
+                app.mode = Mode::Normal;
+                app.panel = Panel::Chat;
+                return;
             }
         }
-        KeyCode::Up | KeyCode::Char('k') => {
-            app.confirm_action = None;
-            app.prev_option();
+    }
+    
+    let ctrl = mods.contains(KeyModifiers::CONTROL);
+    let alt = mods.contains(KeyModifiers::ALT);
+    let ed = &mut app.composer;
+    
+    if ctrl {
+        match code {
+            KeyCode::Char('a') => ed.move_line_start(),
+            KeyCode::Char('e') => ed.move_line_end(),
+            KeyCode::Char('b') => ed.move_left(),
+            KeyCode::Char('f') => ed.move_right(),
+            KeyCode::Char('w') | KeyCode::Backspace => ed.delete_word_left(),
+            KeyCode::Char('k') => ed.kill_to_line_end(),
+            KeyCode::Char('u') => ed.kill_to_line_start(),
+            KeyCode::Char('y') => ed.yank(),
+            KeyCode::Char('z') => { ed.undo(); }
+            KeyCode::Char('r') => { ed.redo(); }
+            KeyCode::Char('j') => ed.newline(),
+            KeyCode::Left => ed.move_word_left(),
+            KeyCode::Right => ed.move_word_right(),
+            KeyCode::Home => ed.move_start(),
+            KeyCode::End => ed.move_end(),
+            KeyCode::Delete => ed.delete_word_right(),
+            KeyCode::Char('x') => app.remove_compose_preview(),
+            _ => {}
         }
-        KeyCode::Down | KeyCode::Char('j') => {
-            app.confirm_action = None;
-            app.next_option();
+        return;
+    }
+    
+    if alt {
+        match code {
+            KeyCode::Enter => ed.newline(),
+            KeyCode::Char('b') | KeyCode::Left => ed.move_word_left(),
+            KeyCode::Char('f') | KeyCode::Right => ed.move_word_right(),
+            KeyCode::Char('d') | KeyCode::Delete => ed.delete_word_right(),
+            KeyCode::Backspace => ed.delete_word_left(),
+            _ => {}
         }
-        KeyCode::Enter => {
-            app.execute_selected_option();
+        return;
+    }
+    
+    match code {
+        KeyCode::Esc => {
+            app.mode = Mode::Normal;
+            app.panel = Panel::Chat;
         }
-        KeyCode::Char('d') => {
-            app.option_selection = 0;
-            app.execute_selected_option();
+        KeyCode::Tab => app.cycle_panel(),
+        KeyCode::Enter if mods.contains(KeyModifiers::SHIFT) => ed.newline(),
+        KeyCode::Enter => app.send_message(),
+        KeyCode::Backspace => ed.backspace(),
+        KeyCode::Delete => ed.delete(),
+        KeyCode::Left => ed.move_left(),
+        KeyCode::Right => ed.move_right(),
+        // Past the first or last line, Up/Down walk the sent history
+        KeyCode::Up if !ed.move_up() => app.recall_history(true),
+        KeyCode::Down if !ed.move_down() => app.recall_history(false),
+        KeyCode::Home => ed.move_line_start(),
+        KeyCode::End => ed.move_line_end(),
+        KeyCode::Char(c) => ed.insert_char(c),
+        _ => {}
+    }
+}
+
+fn handle_chat_search(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Esc => app.mode = Mode::Normal,
+        KeyCode::Enter => app.run_chat_search(),
+        KeyCode::Backspace => { app.search_input.pop(); }
+        KeyCode::Char(c) => app.search_input.push(c),
+        _ => {}
+    }
+}
+
+/// Filters as you type; Enter opens the selected contact, Esc drops the filter
+fn handle_contact_filter(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Esc => {
+            app.contact_filter.clear();
+            app.mode = Mode::Normal;
+            app.contact_filter_changed();
         }
-        KeyCode::Char('c') if app.confirm_action.is_none() => {
-            app.option_selection = 1;
-            app.execute_selected_option();
+        KeyCode::Enter => {
+            app.mode = Mode::Normal;
+            app.run_action(Action::OpenChat);
         }
-        KeyCode::Char('i') => {
-            app.option_selection = 2;
-            app.execute_selected_option();
+        KeyCode::Up | KeyCode::BackTab => app.prev_contact(),
+        KeyCode::Down | KeyCode::Tab => app.next_contact(),
+        KeyCode::Backspace => {
+            app.contact_filter.pop();
+            app.contact_filter_changed();
         }
-        KeyCode::Char('x') => {
-            app.close_contact_options();
+        KeyCode::Char(c) => {
+            app.contact_filter.push(c);
+            app.contact_filter_changed();
         }
         _ => {}
     }
 }
 
-fn handle_normal(app: &mut App, code: KeyCode) {
+/// Digits go into the peer's code; letters are commands since codes have none
+fn handle_verify(app: &mut App, code: KeyCode) {
+    let Some(v) = app.verification.as_mut() else {
+        app.mode = Mode::Normal;
+        return;
+    };
     match code {
-        KeyCode::Char('q') => app.running = false,
-        KeyCode::Char('?') => app.show_help = !app.show_help,
         KeyCode::Esc => {
-            if app.show_help { app.show_help = false; }
+            app.verification = None;
+            app.mode = Mode::Normal;
         }
-        KeyCode::Tab => app.cycle_panel(),
-        KeyCode::Char('i') => {
-            app.mode = Mode::AddContact;
-            app.request_address();
+        KeyCode::Enter => app.submit_verification(true),
+        KeyCode::Backspace => { v.input.pop(); }
+        KeyCode::Char(c) if c.is_ascii_digit() || c == ' ' => {
+            v.input.push(c);
+            v.matched = None;
         }
+        KeyCode::Char('m') => app.submit_verification(false),
+        KeyCode::Char('u') => app.clear_verification(),
+        _ => {}
+    }
+}
+
+fn handle_users(app: &mut App, code: KeyCode) {
+    let hidden = app.users.selected_user().is_some_and(|u| u.hidden);
+    match code {
+        KeyCode::Esc | KeyCode::Char('q') => app.mode = Mode::Normal,
+        KeyCode::Enter => app.switch_user(),
+        KeyCode::Down | KeyCode::Char('j') => app.users.move_selection(true),
+        KeyCode::Up | KeyCode::Char('k') => app.users.move_selection(false),
+        KeyCode::Char('n') => app.start_command("user new "),
         KeyCode::Char('r') => {
-            app.refresh_chat();
-            app.send_cmd("/contacts");
-        }
-        KeyCode::Char('o') => {
-            if let Some(contact) = app.selected_contact() {
-                let name = contact.name.clone();
-                app.open_contact_options(name);
-            }
-        }
-        KeyCode::Char('f') => {
-            if app.current_contact.is_some() {
-                app.file_explorer = Some(ratatui_explorer::FileExplorer::new().unwrap());
-                app.mode = Mode::FileBrowser;
-            }
-        }
-        KeyCode::Char('j') | KeyCode::Down => {
-            match app.panel {
-                Panel::Contacts => app.next_contact(),
-                Panel::Chat => app.scroll_down(),
-                _ => {}
-            }
-        }
-        KeyCode::Char('k') | KeyCode::Up => {
-            match app.panel {
-                Panel::Contacts => app.prev_contact(),
-                Panel::Chat => app.scroll_up(),
-                _ => {}
-            }
+            let name = app.users.selected_user().map(|u| u.name.clone()).unwrap_or_default();
+            app.start_command(&format!("user rename {}", name));
         }
-        KeyCode::Enter => {
-            if app.panel == Panel::Contacts {
-                app.select_contact();
-            } else {
-                app.panel = Panel::Input;
-                app.mode = Mode::Input;
-            }
+        KeyCode::Char('h') => app.start_command(if hidden { "user unhide " } else { "user hide " }),
+        KeyCode::Char('m') => app.toggle_user_muted(),
+        KeyCode::Char('d') => app.delete_user(),
+        _ => {}
+    }
+}
+
+fn handle_contact_prefs(app: &mut App, code: KeyCode) {
+    let Some(editor) = app.prefs_editor.as_mut() else {
+        app.mode = Mode::Normal;
+        return;
+    };
+    match code {
+        KeyCode::Esc | KeyCode::Char('q') => app.close_contact_prefs(),
+        KeyCode::Enter => app.save_contact_prefs(),
+        KeyCode::Down | KeyCode::Char('j') => editor.move_selection(true),
+        KeyCode::Up | KeyCode::Char('k') => editor.move_selection(false),
+        KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => editor.cycle(true),
+        KeyCode::Left | KeyCode::Char('h') => editor.cycle(false),
+        _ => {}
+    }
+}
+
+fn handle_profile(app: &mut App, code: KeyCode) {
+    let Some(editor) = app.profile_editor.as_mut() else {
+        app.mode = Mode::Normal;
+        return;
+    };
+    match (code, editor.field()) {
+        (KeyCode::Esc, _) => app.close_profile_editor(),
+        (KeyCode::Down | KeyCode::Tab, _) => editor.move_selection(true),
+        (KeyCode::Up | KeyCode::BackTab, _) => editor.move_selection(false),
+        (KeyCode::Enter, Field::Picture) => app.open_file_browser(),
+        (KeyCode::Enter, _) => app.save_profile(),
+        (KeyCode::Backspace | KeyCode::Delete, Field::Picture) => editor.set_image(None),
+        (KeyCode::Backspace, Field::Text(i)) => { editor.texts[i].pop(); }
+        (KeyCode::Char(c), Field::Text(i)) => editor.texts[i].push(c),
+        (KeyCode::Left, Field::Preference(_)) => editor.cycle(false),
+        (KeyCode::Right | KeyCode::Char(' '), Field::Preference(_)) => editor.cycle(true),
+        _ => {}
+    }
+}
+
+fn handle_command(app: &mut App, code: KeyCode, mods: KeyModifiers) {
+    let ed = &mut app.command.input;
+    if mods.contains(KeyModifiers::CONTROL) {
+        match code {
+            KeyCode::Char('a') => ed.move_line_start(),
+            KeyCode::Char('e') => ed.move_line_end(),
+            KeyCode::Char('w') | KeyCode::Backspace => ed.delete_word_left(),
+            KeyCode::Char('u') => ed.kill_to_line_start(),
+            _ => return,
         }
+        app.command.edited();
+        return;
+    }
+    
+    match code {
+        KeyCode::Esc => app.mode = Mode::Normal,
+        KeyCode::Backspace if ed.is_empty() => app.mode = Mode::Normal,
+        KeyCode::Enter => app.run_command(),
+        KeyCode::Tab => app.complete_command(),
+        KeyCode::Up => app.command.recall(true),
+        KeyCode::Down => app.command.recall(false),
+        KeyCode::Left => ed.move_left(),
+        KeyCode::Right => ed.move_right(),
+        KeyCode::Home => ed.move_line_start(),
+        KeyCode::End => ed.move_line_end(),
+        KeyCode::Backspace => { ed.backspace(); app.command.edited(); }
+        KeyCode::Delete => { ed.delete(); app.command.edited(); }
+        KeyCode::Char(c) => { ed.insert_char(c); app.command.edited(); }
         _ => {}
     }
 }
 
-fn handle_input(app: &mut App, code: KeyCode) {
+fn handle_console(app: &mut App, code: KeyCode) {
+    let console = &mut app.console;
     match code {
-        KeyCode::Esc => {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            console.visible = false;
             app.mode = Mode::Normal;
-            app.panel = Panel::Chat;
         }
-        KeyCode::Tab => app.cycle_panel(),
-        KeyCode::Enter => app.send_message(),
-        KeyCode::Backspace => app.input_backspace(),
-        KeyCode::Delete => app.input_delete(),
-        KeyCode::Left => app.cursor_left(),
-        KeyCode::Right => app.cursor_right(),
-        KeyCode::Home => app.cursor_home(),
-        KeyCode::End => app.cursor_end(),
-        KeyCode::Char(c) => app.input_char(c),
+        KeyCode::Char(':') => app.start_command(""),
+        KeyCode::Char('/') => app.start_command("/"),
+        KeyCode::Char('t') => console.json = !console.json,
+        KeyCode::Char('c') => console.entries.clear(),
+        KeyCode::Char('j') | KeyCode::Down => console.scroll = console.scroll.saturating_add(1),
+        KeyCode::Char('k') | KeyCode::Up => console.scroll = console.scroll.saturating_sub(1),
+        KeyCode::PageDown => console.scroll = console.scroll.saturating_add(10),
+        KeyCode::PageUp => console.scroll = console.scroll.saturating_sub(10),
+        KeyCode::Char('g') | KeyCode::Home => console.scroll = 0,
+        KeyCode::Char('G') | KeyCode::End => console.scroll = usize::MAX,
+        KeyCode::Tab => {
+            console.visible = false;
+            app.cycle_panel();
+        }
+        _ => {}
+    }
+}
+
+fn handle_paste(app: &mut App, text: &str) {
+    match app.mode {
+        Mode::Input => {
+            if let Some(vi) = app.vi.as_mut() { vi.remember_clipboard(text); }
+            app.composer.insert_str(text);
+        }
+        Mode::ChatSearch => app.search_input.push_str(text.trim()),
+        Mode::Palette => app.palette.query.push_str(text.trim()),
+        Mode::GlobalSearch => app.global_search.input.push_str(text.trim()),
+        Mode::Verify => {
+            if let Some(v) = app.verification.as_mut() {
+                v.input.extend(text.chars().filter(|c| c.is_ascii_digit() || *c == ' '));
+                v.matched = None;
+            }
+        }
+        Mode::ContactFilter => {
+            app.contact_filter.push_str(text.trim());
+            app.contact_filter_changed();
+        }
+        Mode::Command => { app.command.input.insert_str(text.trim()); app.command.edited(); }
+        Mode::AddContact => app.connect_input.push_str(text.trim()),
+        Mode::Profile => {
+            if let Some(editor) = app.profile_editor.as_mut() {
+                if let Field::Text(i) = editor.field() { editor.texts[i].push_str(text.trim()); }
+            }
+        }
         _ => {}
     }
 }
@@ -305,11 +589,14 @@ fn handle_file_browser(app: &mut App, code: KeyCode) {
         match code {
             KeyCode::Esc => {
                 app.file_explorer = None;
-                app.mode = Mode::Normal;
+                app.mode = if app.profile_editor.is_some() { Mode::Profile } else { Mode::Normal };
             }
             KeyCode::Enter => {
                 let path = explorer.current().path().to_path_buf();
-                if path.is_file() {
+                if path.is_file() && app.profile_editor.is_some() {
+                    app.file_explorer = None;
+                    app.set_profile_image(&path);
+                } else if path.is_file() {
                     // Datei ausgewählt - senden
                     if let Some(contact) = &app.current_contact {
                         let cmd = format!("/f @'{}' {}", contact, path.display());
@@ -329,3 +616,22 @@ fn handle_file_browser(app: &mut App, code: KeyCode) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn panic_mode_ignores_global_bindings() {
+        let mut app = App::default();
+        app.run_action(Action::Panic);
+        handle_key(&mut app, KeyCode::Char('p'), KeyModifiers::CONTROL);
+        handle_key(&mut app, KeyCode::Char('l'), KeyModifiers::CONTROL);
+        assert_eq!(app.mode, Mode::Panic);
+        handle_key(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.mode, Mode::Normal);
+        app.run_action(Action::Panic);
+        handle_key(&mut app, KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert!(!app.running);
+    }
+}
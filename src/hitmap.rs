@@ -0,0 +1,81 @@
+//! Clickable regions recorded by the renderer for the mouse handler
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use ratatui::layout::{Position, Rect};
+
+use crate::actions::Action;
+use crate::contact_list::Section;
+
+/// What a click on a region means
+#[derive(Clone, Debug, PartialEq)]
+pub enum Hit {
+    Action(Action),
+    /// Row in the contact list, by index
+    Contact(usize),
+    /// Section header in the contact list; toggles it
+    Section(Section),
+    /// Folder tab above the contact list, by index
+    Folder(usize),
+    /// Message body, by index into the loaded messages
+    Message(usize),
+    /// Play control of a voice message
+    Voice(usize),
+    Link(String),
+    /// Button in the contact options modal
+    ContactOption(usize),
+    /// Row in the command palette
+    PaletteItem(usize),
+    /// Row in the Add Contact link list; 0 is the address, then one-time links
+    LinkRow(usize),
+    /// Feature row of the chat preferences modal
+    PrefRow(usize),
+    /// Field of the profile editor
+    ProfileRow(usize),
+    /// Row in the profile switcher
+    User(usize),
+    /// Row in the all-chats search results
+    SearchResult(usize),
+    /// Message scrollbar; the track maps to the scroll range
+    Scrollbar { total: usize, visible: usize },
+    /// Column between contact list and chat, dragged to resize
+    Divider,
+    /// Area behind a modal; swallows clicks
+    Backdrop,
+}
+
+/// Rebuilt every frame; later regions lie on top of earlier ones
+#[derive(Default)]
+pub struct HitMap {
+    regions: Vec<(Rect, Hit)>,
+}
+
+impl HitMap {
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+    
+    pub fn add(&mut self, rect: Rect, hit: Hit) {
+        if !rect.is_empty() { self.regions.push((rect, hit)); }
+    }
+    
+    /// Covers everything drawn so far, for modals
+    pub fn backdrop(&mut self, rect: Rect) {
+        self.add(rect, Hit::Backdrop);
+    }
+    
+    /// Topmost region under the pointer
+    pub fn at(&self, x: u16, y: u16) -> Option<(Rect, Hit)> {
+        self.regions.iter().rev().find(|(r, _)| r.contains(Position::new(x, y))).cloned()
+    }
+}
+
+/// A mouse drag in progress
+#[derive(Clone, Copy, Debug)]
+pub enum Drag {
+    /// Resizing the contact list, whose left edge is `left`
+    Divider { left: u16 },
+    Scrollbar { track: Rect, total: usize, visible: usize },
+}
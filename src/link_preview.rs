@@ -0,0 +1,137 @@
+//! Link previews for outgoing messages
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::LinkPreviewConfig;
+use crate::thumbnail;
+use crate::types::LinkPreview;
+
+/// First http(s) URL in a message, if any
+pub fn find_url(text: &str) -> Option<&str> {
+    text.split_whitespace().find(|w| w.starts_with("https://") || w.starts_with("http://"))
+}
+
+/// Fetches the page (and its image) in the background through the configured command
+pub fn spawn_fetch(url: String, config: LinkPreviewConfig) -> mpsc::Receiver<Result<LinkPreview>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(fetch_preview(&url, &config));
+    });
+    rx
+}
+
+fn fetch(url: &str, config: &LinkPreviewConfig) -> Result<Vec<u8>> {
+    let mut parts = config.command.split_whitespace()
+        .map(|p| p.replace("{url}", url).replace("{proxy}", &config.proxy));
+    let Some(program) = parts.next() else { bail!("no preview command configured") };
+    let out = Command::new(&program)
+        .args(parts)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .with_context(|| format!("cannot run {}", program))?;
+    if !out.status.success() || out.stdout.is_empty() { bail!("could not fetch {}", url); }
+    Ok(out.stdout)
+}
+
+fn fetch_preview(url: &str, config: &LinkPreviewConfig) -> Result<LinkPreview> {
+    let html = String::from_utf8_lossy(&fetch(url, config)?).into_owned();
+    
+    let title = meta_content(&html, "og:title")
+        .or_else(|| tag_text(&html, "title"))
+        .unwrap_or_else(|| url.to_string());
+    let description = meta_content(&html, "og:description")
+        .or_else(|| meta_content(&html, "description"))
+        .unwrap_or_default();
+    
+    let image = meta_content(&html, "og:image")
+        .filter(|src| src.starts_with("http"))
+        .and_then(|src| fetch(&src, config).ok())
+        .and_then(|bytes| image::load_from_memory(&bytes).ok())
+        .and_then(|img| thumbnail::encode_data_uri(&img, 160));
+    
+    Ok(LinkPreview::new(url.to_string(), title, description, image))
+}
+
+fn meta_content(html: &str, name: &str) -> Option<String> {
+    html.split("<meta").skip(1).find_map(|tag| {
+        let tag = &tag[..tag.find('>')?];
+        let key = attr(tag, "property").or_else(|| attr(tag, "name"))?;
+        if key.eq_ignore_ascii_case(name) { attr(tag, "content").map(unescape) } else { None }
+    }).filter(|s| !s.is_empty())
+}
+
+/// Value of attribute `name` in the inside of a tag. Walks the attributes
+/// one by one, so `name` never matches the tail of `data-name` or a value
+fn attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    loop {
+        rest = rest.trim_start();
+        let end = rest.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(rest.len());
+        if end == 0 && !rest.starts_with('=') { return None; }
+        let key = &rest[..end];
+        rest = rest[end..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+                let (value, next) = match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let close = after[1..].find(quote)? + 1;
+                        (&after[1..close], &after[close + 1..])
+                    }
+                    _ => after.split_at(after.find(char::is_whitespace).unwrap_or(after.len())),
+                };
+                rest = next;
+                Some(value)
+            }
+            None => None,
+        };
+        if key.eq_ignore_ascii_case(name) { return value; }
+    }
+}
+
+fn tag_text(html: &str, tag: &str) -> Option<String> {
+    let start = html.find(&format!("<{}", tag))?;
+    let rest = &html[start..];
+    let rest = &rest[rest.find('>')? + 1..];
+    let text = unescape(rest[..rest.find('<')?].trim());
+    if text.is_empty() { None } else { Some(text) }
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("&quot;", "\"").replace("&#39;", "'").replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn attr_matches_whole_names_only() {
+        let tag = r#" data-name="wrong" name="og:title" xcontent='no' content="Right""#;
+        assert_eq!(attr(tag, "name"), Some("og:title"));
+        assert_eq!(attr(tag, "content"), Some("Right"));
+        assert_eq!(attr(tag, "property"), None);
+    }
+    
+    #[test]
+    fn attr_reads_unquoted_and_spaced_values() {
+        assert_eq!(attr(" property = og:image content=x.png /", "content"), Some("x.png"));
+        assert_eq!(attr(" property = og:image content=x.png /", "property"), Some("og:image"));
+        assert_eq!(attr(" async content=\"a b\"", "content"), Some("a b"));
+    }
+    
+    #[test]
+    fn meta_content_skips_lookalike_attributes() {
+        let html = r#"<meta data-property="og:title" content="Bad"><meta property="og:title" content="Good &amp; right">"#;
+        assert_eq!(meta_content(html, "og:title").as_deref(), Some("Good & right"));
+    }
+}
@@ -4,11 +4,33 @@
 //! GitHub: https://github.com/cannatoshi/simplex-tui
 //! Licensed under AGPL-3.0
 
+mod actions;
 mod app;
+mod audio;
+mod chat_prefs;
+mod clipboard;
 mod colors;
+mod command;
+mod config;
+mod contact_list;
+mod drafts;
+mod editor;
 mod handlers;
+mod hitmap;
+mod link_preview;
+mod notify;
+mod palette;
+mod profile;
+mod qr;
+mod search;
+mod state;
+mod tags;
+mod thumbnail;
+mod timed;
 mod types;
+mod users;
 mod ui;
+mod vi;
 mod websocket;
 
 use std::io;
@@ -17,8 +39,11 @@ use std::sync::mpsc;
 use anyhow::Result;
 use crossterm::{
     execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    event::{DisableMouseCapture, EnableMouseCapture},
+    terminal::{disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen, LeaveAlternateScreen},
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
 
@@ -26,24 +51,37 @@ use app::App;
 use types::SimplexEvent;
 
 fn main() -> Result<()> {
+    let config = config::Config::load()?;
+    let drafts = drafts::Drafts::open(&config.drafts)?;
+    let keymap = actions::Keymap::from_config(&config.keys)?;
+    colors::init(&config.theme)?;
+    
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, EnableBracketedPaste)?;
+    // Lets terminals that support it report Shift+Enter for newlines
+    let enhanced_keys = supports_keyboard_enhancement().unwrap_or(false);
+    if enhanced_keys {
+        execute!(stdout, PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES))?;
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
     
-    let result = run_app(&mut terminal);
+    let result = run_app(&mut terminal, config, drafts, keymap);
     
+    if enhanced_keys {
+        execute!(terminal.backend_mut(), PopKeyboardEnhancementFlags)?;
+    }
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture, DisableBracketedPaste)?;
     terminal.show_cursor()?;
     
     if let Err(e) = result { eprintln!("Error: {}", e); }
     Ok(())
 }
 
-fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> Result<()> {
-    let mut app = App::new();
+fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>, config: config::Config, drafts: drafts::Drafts, keymap: actions::Keymap) -> Result<()> {
+    let mut app = App::new(config, drafts, keymap);
     
     let (event_tx, event_rx) = mpsc::channel();
     let (cmd_tx, cmd_rx) = mpsc::channel();
@@ -57,11 +95,13 @@ fn run_app<B: ratatui::backend::Backend>(terminal: &mut Terminal<B>) -> Result<(
         while let Ok(event) = event_rx.try_recv() {
             handle_simplex_event(&mut app, event);
         }
+        app.update_unread_title();
         
         terminal.draw(|frame| ui::render(frame, &mut app))?;
         handlers::handle_events(&mut app)?;
     }
     
+    app.save_drafts();
     Ok(())
 }
 
@@ -82,16 +122,24 @@ fn handle_simplex_event(app: &mut App, event: SimplexEvent) {
             for new_c in &mut new_contacts {
                 if let Some(old_c) = app.contacts.iter().find(|c| c.name == new_c.name) {
                     new_c.unread = old_c.unread;
+                    new_c.incognito_name = old_c.incognito_name.clone();
                 }
             }
             app.contacts = new_contacts;
+            if let Some(user) = app.user_id {
+                app.drafts.adopt_names(user, app.contacts.iter().map(|c| (c.name.as_str(), c.id)));
+            }
             app.status = "Ready".into();
         }
         
         SimplexEvent::Messages(messages) => {
             let _count = messages.len();
             app.messages = messages;
+            if app.current_contact.as_deref().is_some_and(|c| app.is_blocked(c)) {
+                app.messages.retain(|m| m.mine);
+            }
             app.auto_scroll();
+            app.on_history_loaded();
             
             if let Some(current) = &app.current_contact {
                 if let Some(contact) = app.contacts.iter_mut().find(|c| &c.name == current) {
@@ -101,22 +149,18 @@ fn handle_simplex_event(app: &mut App, event: SimplexEvent) {
             }
         }
         
-        SimplexEvent::NewMessage { sender, text } => {
+        SimplexEvent::NewMessage { sender, .. } if app.is_blocked(&sender) => {}
+        
+        SimplexEvent::NewMessage { sender, message } => {
             let is_mine = sender == "You";
             let is_current_chat = is_mine || app.current_contact.as_ref() == Some(&sender);
             
             if is_current_chat {
-                use chrono::Local;
-                app.messages.push(types::ChatMessage {
-                    sender: sender.clone(),
-                    content: text.clone(),
-                    time: Local::now().format("%H:%M").to_string(),
-                    mine: is_mine,
-                    status: if is_mine { types::MessageStatus::Delivered } else { types::MessageStatus::Delivered },
-                });
+                let is_voice = message.voice.is_some();
+                app.messages.push(message);
                 app.auto_scroll();
                 if is_mine {
-                    app.status = "File sent".into();
+                    app.status = if is_voice { "Voice message sent" } else { "File sent" }.into();
                 } else {
                     app.status = "New message".into();
                 }
@@ -124,7 +168,7 @@ fn handle_simplex_event(app: &mut App, event: SimplexEvent) {
                 if let Some(contact) = app.contacts.iter_mut().find(|c| c.name == sender) {
                     contact.unread += 1;
                 }
-                app.status = format!("📩 {}", sender);
+                app.notify_message(&sender, &message);
             }
         }
         
@@ -148,6 +192,11 @@ fn handle_simplex_event(app: &mut App, event: SimplexEvent) {
             app.status = "Address ready".into();
         }
         
+        SimplexEvent::Invitation(invitation) => app.on_invitation(invitation),
+        SimplexEvent::Invitations(invitations) => app.on_invitations(invitations),
+        SimplexEvent::InvitationUpdated(invitation) => app.on_invitation_updated(invitation),
+        SimplexEvent::InvitationDeleted(id) => app.on_invitation_deleted(id),
+        
         SimplexEvent::AddressDeleted => {
             app.finish_create_address();
         }
@@ -159,8 +208,22 @@ fn handle_simplex_event(app: &mut App, event: SimplexEvent) {
         SimplexEvent::ContactRequest(name) => {
             app.status = format!("✓ Connected: {}", name);
             app.send_cmd("/contacts");
+            // A one-time link may just have been used
+            if !app.invitations.is_empty() { app.request_invitations(); }
+        }
+        
+        SimplexEvent::IncomingRequest(request) => {
+            if app.is_blocked_request(&request) {
+                app.send_cmd(&format!("/_reject {}", request.id));
+                app.status = format!("Rejected request from blocked {}", request.name);
+            } else {
+                app.status = format!("📨 {} wants to connect (:accept {})", request.name, request.name);
+                app.contact_requests.push(request);
+            }
         }
         
+        SimplexEvent::ContactRequests(requests) => app.contact_requests = requests,
+        
         SimplexEvent::ContactDeleted(name) => {
             app.on_contact_deleted(&name);
             app.send_cmd("/contacts");
@@ -170,7 +233,59 @@ fn handle_simplex_event(app: &mut App, event: SimplexEvent) {
             app.on_chat_cleared(&name);
         }
         
+        SimplexEvent::FileReceived { file_id, path } => {
+            app.on_file_received(file_id, path);
+        }
+        
+        SimplexEvent::Groups(groups) => {
+            app.groups = groups;
+        }
+        
+        SimplexEvent::ActiveUser { id, name, profile } => {
+            app.own_profile = Some(profile);
+            app.on_active_user(id, &name);
+        }
+        
+        SimplexEvent::Users(users) => app.users.set_users(users),
+        SimplexEvent::UsersChanged => {
+            app.send_cmd("/users");
+            app.send_cmd("/user");
+        }
+        
+        SimplexEvent::ChatTags(tags) => {
+            app.on_chat_tags(tags);
+        }
+        
+        SimplexEvent::SearchResults { global, hits } => {
+            app.on_search_results(global, hits);
+        }
+        
+        SimplexEvent::CommandResponse { corr_id, response } => {
+            app.console.respond(&corr_id, response);
+        }
+        
+        SimplexEvent::ContactUpdated(contact) => {
+            app.on_contact_updated(contact);
+        }
+        
+        SimplexEvent::ItemsDeleted { chat, ids } => app.on_items_deleted(&chat, &ids),
+        
+        SimplexEvent::IncognitoProfile { contact_id, name } => app.on_incognito_profile(contact_id, name),
+        
+        SimplexEvent::SecurityCode { contact, code } => {
+            app.on_security_code(contact, code);
+        }
+        
+        SimplexEvent::CodeVerified { verified, expected } => {
+            app.on_code_verified(verified, &expected);
+        }
+        
         SimplexEvent::ContactInfo(info_data) => {
+            if let Some(name) = &info_data.incognito_name {
+                if let Some(id) = app.contacts.iter().find(|c| c.name == info_data.name).map(|c| c.id) {
+                    app.on_incognito_profile(id, name.clone());
+                }
+            }
             app.contact_info_data = Some(info_data);
             app.mode = crate::types::Mode::ContactInfo;
         }
@@ -0,0 +1,136 @@
+//! Alerts for messages in chats that are not open
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crossterm::{execute, terminal::SetTitle};
+
+use crate::config::{NotifyConfig, Osc, Redact};
+
+const TITLE: &str = "simplex-tui";
+/// Longer messages are cut in notifications
+const MAX_TEXT: usize = 200;
+
+pub struct Notifier {
+    config: NotifyConfig,
+    /// Count last put in the window title; None while the terminal's own
+    /// title is shown
+    shown_unread: Option<usize>,
+}
+
+impl Notifier {
+    pub fn new(config: NotifyConfig) -> Self {
+        Self { config, shown_unread: None }
+    }
+    
+    /// Rings, raises a desktop notification and runs the hook, as configured
+    pub fn message(&self, sender: &str, text: &str) {
+        let (sender, text) = match self.config.redact {
+            Redact::Off => (clean(sender), clean(text)),
+            Redact::Message => (clean(sender), "New message".to_string()),
+            Redact::All => ("SimpleX".to_string(), "New message".to_string()),
+        };
+        let mut out = std::io::stdout();
+        if self.config.bell { let _ = write!(out, "\x07"); }
+        match self.config.osc {
+            Osc::Off => {}
+            Osc::Osc9 => { let _ = write!(out, "\x1b]9;{}: {}\x07", sender, text); }
+            Osc::Osc777 => { let _ = write!(out, "\x1b]777;notify;{};{}\x07", sender.replace(';', ","), text); }
+        }
+        let _ = out.flush();
+        self.run_command(&sender, &text);
+    }
+    
+    /// Each part of the template is one argument, so the message needs no quoting
+    fn run_command(&self, sender: &str, text: &str) {
+        let mut parts = self.config.command.split_whitespace().map(|p| fill(p, sender, text));
+        let Some(program) = parts.next() else { return };
+        let child = Command::new(program).args(parts)
+            .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+            .spawn();
+        // Reaped in the background so hooks never leave zombies
+        if let Ok(mut child) = child {
+            std::thread::spawn(move || { let _ = child.wait(); });
+        }
+    }
+    
+    /// Puts the unread count in the window title when it changed; the
+    /// terminal's title is saved first (XTWINOPS) so it can be given back
+    pub fn set_unread(&mut self, unread: usize) {
+        if !self.config.title || self.shown_unread == Some(unread) { return; }
+        let mut out = std::io::stdout();
+        if self.shown_unread.is_none() { let _ = write!(out, "\x1b[22;0t"); }
+        self.shown_unread = Some(unread);
+        let title = if unread > 0 { format!("({}) {}", unread, TITLE) } else { TITLE.to_string() };
+        let _ = execute!(out, SetTitle(title));
+    }
+    
+    /// Gives the terminal its saved title back
+    pub fn restore_title(&mut self) {
+        if self.shown_unread.take().is_none() { return; }
+        let mut out = std::io::stdout();
+        let _ = write!(out, "\x1b[23;0t");
+        let _ = out.flush();
+    }
+}
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        self.restore_title();
+    }
+}
+
+/// Replaces `{sender}` and `{message}` in one pass, so placeholders in
+/// the sender's name are not filled in as well
+fn fill(part: &str, sender: &str, text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = part;
+    while let Some(i) = rest.find('{') {
+        out.push_str(&rest[..i]);
+        let tail = &rest[i..];
+        if let Some(r) = tail.strip_prefix("{sender}") {
+            out.push_str(sender);
+            rest = r;
+        } else if let Some(r) = tail.strip_prefix("{message}") {
+            out.push_str(text);
+            rest = r;
+        } else {
+            out.push('{');
+            rest = &tail[1..];
+        }
+    }
+    out + rest
+}
+
+/// One line without control characters, which could end the escape
+/// sequence early, cut to `MAX_TEXT`
+fn clean(text: &str) -> String {
+    let text: String = text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    match text.char_indices().nth(MAX_TEXT) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn fills_placeholders_once() {
+        assert_eq!(fill("{sender}:{message}", "{message}", "hi"), "{message}:hi");
+        assert_eq!(fill("--title={sender}", "alice", "{sender}"), "--title=alice");
+        assert_eq!(fill("{x}{message}{", "a", "b"), "{x}b{");
+    }
+    
+    #[test]
+    fn cleans_and_cuts_text() {
+        assert_eq!(clean("a\x1b]0;x\x07b\nc"), "a ]0;x b c");
+        let long = "x".repeat(MAX_TEXT + 5);
+        assert_eq!(clean(&long).chars().count(), MAX_TEXT + 1);
+    }
+}
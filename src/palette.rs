@@ -0,0 +1,99 @@
+//! Fuzzy command palette over the action registry
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use crate::actions::{Action, ACTIONS};
+
+#[derive(Default)]
+pub struct Palette {
+    pub query: String,
+    pub selected: usize,
+}
+
+/// Subsequence match; consecutive letters and word starts score higher
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last: Option<usize> = None;
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (pos..text.len()).find(|&i| text[i] == q)?;
+        score += 1;
+        if last.is_some_and(|l| l + 1 == found) { score += 5; }
+        if found == 0 || !text[found - 1].is_alphanumeric() { score += 3; }
+        last = Some(found);
+        pos = found + 1;
+    }
+    // Prefer shorter labels among equal matches
+    Some(score * 100 - text.len() as i64)
+}
+
+impl Palette {
+    pub fn reset(&mut self) {
+        self.query.clear();
+        self.selected = 0;
+    }
+    
+    /// Registered actions matching the query, best first
+    pub fn matches(&self) -> Vec<Action> {
+        let mut seen = vec![];
+        let mut scored: Vec<(i64, Action)> = ACTIONS.iter().filter_map(|info| {
+            if seen.contains(&info.action) || !info.action.in_palette() { return None; }
+            seen.push(info.action);
+            fuzzy_score(&self.query, info.label).map(|s| (s, info.action))
+        }).collect();
+        if !self.query.is_empty() {
+            scored.sort_by_key(|(s, _)| -s);
+        }
+        scored.into_iter().map(|(_, a)| a).collect()
+    }
+    
+    pub fn move_selection(&mut self, down: bool) {
+        let len = self.matches().len();
+        if len == 0 { return; }
+        self.selected = if down { (self.selected + 1) % len } else { (self.selected + len - 1) % len };
+    }
+    
+    pub fn selected_action(&self) -> Option<Action> {
+        self.matches().get(self.selected).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn fuzzy_needs_letters_in_order() {
+        assert!(fuzzy_score("adc", "Add contact").is_some());
+        assert!(fuzzy_score("cda", "Add contact").is_none());
+        assert!(fuzzy_score("xyz", "Add contact").is_none());
+    }
+    
+    #[test]
+    fn fuzzy_ignores_case_and_spaces() {
+        assert_eq!(fuzzy_score("ADD C", "Add contact"), fuzzy_score("addc", "Add contact"));
+    }
+    
+    #[test]
+    fn fuzzy_prefers_word_starts_and_runs() {
+        let run = fuzzy_score("con", "Add contact").unwrap();
+        let scattered = fuzzy_score("con", "Clear chat history on").unwrap();
+        assert!(run > scattered);
+        assert!(fuzzy_score("ac", "Add contact") > fuzzy_score("ac", "Back"));
+    }
+    
+    #[test]
+    fn fuzzy_prefers_shorter_labels() {
+        assert!(fuzzy_score("quit", "Quit") > fuzzy_score("quit", "Quit application"));
+    }
+    
+    #[test]
+    fn palette_lists_best_match_first() {
+        let palette = Palette { query: "add contact".into(), selected: 0 };
+        assert_eq!(palette.selected_action(), Some(Action::AddContact));
+        assert!(Palette::default().matches().len() > 1);
+    }
+}
@@ -0,0 +1,111 @@
+//! Own profile editor: names, bio, picture and preferences
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::thumbnail::Thumbnail;
+
+/// Preferences offered in the editor: backend key and label
+pub const PREFERENCES: [(&str, &str); 4] = [
+    ("timedMessages", "Disappearing messages"),
+    ("reactions", "Message reactions"),
+    ("voice", "Voice messages"),
+    ("calls", "Audio/video calls"),
+];
+const ALLOW: [&str; 3] = ["always", "yes", "no"];
+/// Text fields, then the picture, then the preferences
+pub const ROWS: usize = 4 + PREFERENCES.len();
+pub const PICTURE_ROW: usize = 3;
+/// Side of profile pictures, as the SimpleX apps send them
+pub const IMAGE_PX: u32 = 192;
+/// Longest picture data URI the SimpleX apps accept
+pub const MAX_IMAGE_LEN: usize = 12_500;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Field {
+    Text(usize),
+    Picture,
+    Preference(usize),
+}
+
+pub struct ProfileEditor {
+    /// Display name, full name and bio
+    pub texts: [String; 3],
+    /// JPEG data URI
+    pub image: Option<String>,
+    pub thumbnail: Option<Thumbnail>,
+    /// `always`, `yes` or `no` per entry of `PREFERENCES`
+    pub allow: [String; PREFERENCES.len()],
+    pub selected: usize,
+    /// The profile as loaded, so saving keeps fields the editor ignores
+    base: Value,
+}
+
+impl ProfileEditor {
+    /// `profile` is the user's profile with its full preferences
+    pub fn new(profile: &Value) -> Self {
+        let text = |key: &str| profile.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let image = profile.get("image").and_then(|v| v.as_str()).map(str::to_string);
+        let allow = PREFERENCES.map(|(key, _)| {
+            profile.get("preferences").and_then(|p| p.get(key)).and_then(|p| p.get("allow"))
+                .and_then(|a| a.as_str()).unwrap_or("yes").to_string()
+        });
+        Self {
+            texts: [text("displayName"), text("fullName"), text("shortDescr")],
+            thumbnail: image.as_deref().and_then(|i| Thumbnail::from_data_uri(i, 12, 6)),
+            image,
+            allow,
+            selected: 0,
+            base: profile.clone(),
+        }
+    }
+    
+    pub fn field(&self) -> Field {
+        match self.selected {
+            i if i < PICTURE_ROW => Field::Text(i),
+            PICTURE_ROW => Field::Picture,
+            i => Field::Preference(i - PICTURE_ROW - 1),
+        }
+    }
+    
+    pub fn move_selection(&mut self, down: bool) {
+        self.selected = if down { (self.selected + 1) % ROWS } else { (self.selected + ROWS - 1) % ROWS };
+    }
+    
+    /// Steps the selected preference through always, yes and no
+    pub fn cycle(&mut self, forward: bool) {
+        let Field::Preference(i) = self.field() else { return };
+        let pos = ALLOW.iter().position(|a| *a == self.allow[i]).unwrap_or(1);
+        let next = if forward { (pos + 1) % ALLOW.len() } else { (pos + ALLOW.len() - 1) % ALLOW.len() };
+        self.allow[i] = ALLOW[next].to_string();
+    }
+    
+    pub fn set_image(&mut self, uri: Option<String>) {
+        self.thumbnail = uri.as_deref().and_then(|i| Thumbnail::from_data_uri(i, 12, 6));
+        self.image = uri;
+    }
+    
+    /// The edited profile for `/_profile`
+    pub fn to_profile(&self) -> Result<Value> {
+        let [display_name, full_name, bio] = self.texts.each_ref().map(|t| t.trim());
+        if display_name.is_empty() { bail!("Display name cannot be empty"); }
+        if display_name.contains(char::is_whitespace) { bail!("Display name cannot contain spaces"); }
+        
+        let mut profile = self.base.clone();
+        profile["displayName"] = display_name.into();
+        profile["fullName"] = full_name.into();
+        profile["shortDescr"] = if bio.is_empty() { Value::Null } else { bio.into() };
+        profile["image"] = self.image.clone().map_or(Value::Null, Value::from);
+        if !profile["preferences"].is_object() { profile["preferences"] = serde_json::json!({}); }
+        for ((key, _), allow) in PREFERENCES.iter().zip(&self.allow) {
+            let pref = &mut profile["preferences"][*key];
+            if !pref.is_object() { *pref = serde_json::json!({}); }
+            pref["allow"] = allow.as_str().into();
+        }
+        Ok(profile)
+    }
+}
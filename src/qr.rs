@@ -0,0 +1,78 @@
+//! QR codes drawn with half-block characters, and exported as images
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use qrcode::render::svg;
+use qrcode::{Color, EcLevel, QrCode};
+
+/// Light modules around the code, as scanners expect
+const QUIET_ZONE: usize = 2;
+/// Smallest side of exported images, in pixels
+const EXPORT_SIZE: u32 = 512;
+
+/// Low error correction keeps long SimpleX links small enough for a terminal
+fn encode(data: &str) -> Result<QrCode> {
+    QrCode::with_error_correction_level(data.as_bytes(), EcLevel::L).context("text too long for a QR code")
+}
+
+/// Modules of a code including the quiet zone
+pub struct Qr {
+    modules: Vec<Vec<bool>>,
+}
+
+impl Qr {
+    pub fn new(data: &str) -> Option<Self> {
+        let code = encode(data).ok()?;
+        let width = code.width();
+        let colors = code.to_colors();
+        let size = width + 2 * QUIET_ZONE;
+        let dark = |x: usize, y: usize| {
+            if x < QUIET_ZONE || y < QUIET_ZONE || x >= width + QUIET_ZONE || y >= width + QUIET_ZONE { return false; }
+            colors[(y - QUIET_ZONE) * width + x - QUIET_ZONE] == Color::Dark
+        };
+        let modules = (0..size).map(|y| (0..size).map(|x| dark(x, y)).collect()).collect();
+        Some(Self { modules })
+    }
+    
+    /// Modules per side
+    pub fn size(&self) -> usize {
+        self.modules.len()
+    }
+    
+    /// Largest scale whose drawing fits in `cols` x `rows` cells, if any
+    pub fn fit(&self, cols: usize, rows: usize) -> Option<usize> {
+        let size = self.size();
+        (1..=4).rev().find(|s| size * s <= cols && (size * s).div_ceil(2) <= rows)
+    }
+    
+    /// Each module `scale` cells wide and `scale` half-cells high, as
+    /// (top dark, bottom dark) pairs per cell, two module rows per line
+    pub fn half_blocks(&self, scale: usize) -> Vec<Vec<(bool, bool)>> {
+        let side = self.size() * scale;
+        let dark = |x: usize, y: usize| y < side && self.modules[y / scale][x / scale];
+        (0..side).step_by(2)
+            .map(|y| (0..side).map(|x| (dark(x, y), dark(x, y + 1))).collect())
+            .collect()
+    }
+}
+
+/// Writes `data` as a PNG or SVG file, chosen by the extension
+pub fn export(data: &str, path: &Path) -> Result<()> {
+    let code = encode(data)?;
+    match path.extension().and_then(|e| e.to_str()).map(|e| e.to_lowercase()).as_deref() {
+        Some("svg") => {
+            let image = code.render::<svg::Color>().min_dimensions(EXPORT_SIZE, EXPORT_SIZE).build();
+            std::fs::write(path, image).with_context(|| format!("cannot write {}", path.display()))
+        }
+        Some("png") => {
+            let image = code.render::<image::Luma<u8>>().min_dimensions(EXPORT_SIZE, EXPORT_SIZE).build();
+            image.save(path).with_context(|| format!("cannot write {}", path.display()))
+        }
+        _ => bail!("export to a .png or .svg file"),
+    }
+}
@@ -0,0 +1,113 @@
+//! Message search: match helpers and the all-chats search view
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::ops::Range;
+
+use crate::types::SearchHit;
+
+/// Hits requested from the backend per search
+pub const SEARCH_LIMIT: usize = 100;
+/// History loaded to reach hits older than the open chat's messages
+pub const HISTORY_LIMIT: usize = 1000;
+
+/// Byte ranges of case-insensitive occurrences of `query` in `text`
+pub fn find_matches(text: &str, query: &str) -> Vec<Range<usize>> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    if query.is_empty() { return vec![]; }
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let same = |c: char, q: char| c.to_lowercase().eq(std::iter::once(q));
+    
+    let mut out = vec![];
+    let mut i = 0;
+    while i + query.len() <= chars.len() {
+        if query.iter().enumerate().all(|(k, q)| same(chars[i + k].1, *q)) {
+            let (last_pos, last) = chars[i + query.len() - 1];
+            out.push(chars[i].0..last_pos + last.len_utf8());
+            i += query.len();
+        } else {
+            i += 1;
+        }
+    }
+    out
+}
+
+/// Up to `width` characters of `text` around the first match
+pub fn snippet(text: &str, query: &str, width: usize) -> String {
+    let flat = text.replace('\n', " ");
+    let start = find_matches(&flat, query).first().map(|m| flat[..m.start].chars().count()).unwrap_or(0);
+    let skip = start.saturating_sub(width / 3);
+    let mut out: String = flat.chars().skip(skip).take(width).collect();
+    if skip > 0 { out = format!("…{}", out.chars().skip(1).collect::<String>()); }
+    if flat.chars().count() > skip + width { out.pop(); out.push('…'); }
+    out
+}
+
+/// Search across all chats, shown as its own view
+#[derive(Default)]
+pub struct GlobalSearch {
+    pub input: String,
+    /// The query the current hits belong to
+    pub query: String,
+    pub hits: Vec<SearchHit>,
+    pub selected: usize,
+    pub loading: bool,
+}
+
+impl GlobalSearch {
+    pub fn move_selection(&mut self, down: bool) {
+        let len = self.hits.len();
+        if len == 0 { return; }
+        self.selected = if down { (self.selected + 1) % len } else { (self.selected + len - 1) % len };
+    }
+    
+    pub fn selected_hit(&self) -> Option<&SearchHit> {
+        self.hits.get(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn finds_case_insensitive_matches() {
+        assert_eq!(find_matches("Hello hello HELLO", "hello"), vec![0..5, 6..11, 12..17]);
+        assert_eq!(find_matches("abc", ""), vec![]);
+        assert_eq!(find_matches("ab", "abc"), vec![]);
+    }
+    
+    #[test]
+    fn matches_do_not_overlap() {
+        assert_eq!(find_matches("aaaa", "aa"), vec![0..2, 2..4]);
+    }
+    
+    #[test]
+    fn match_ranges_are_byte_offsets() {
+        let text = "Grüße aus Köln";
+        let m = find_matches(text, "KÖLN");
+        assert_eq!(m, vec![12..17]);
+        assert_eq!(&text[m[0].clone()], "Köln");
+    }
+    
+    #[test]
+    fn snippet_keeps_short_text() {
+        assert_eq!(snippet("one\ntwo", "two", 20), "one two");
+    }
+    
+    #[test]
+    fn snippet_centers_on_match() {
+        let text = format!("{}needle{}", "a".repeat(30), "b".repeat(30));
+        let s = snippet(&text, "needle", 12);
+        assert_eq!(s.chars().count(), 12);
+        assert!(s.starts_with('…') && s.ends_with('…'), "{}", s);
+        assert!(s.contains("needle"), "{}", s);
+    }
+    
+    #[test]
+    fn snippet_without_match_starts_at_beginning() {
+        assert_eq!(snippet("abcdefghij", "xyz", 5), "abcd…");
+    }
+}
@@ -0,0 +1,101 @@
+//! UI state remembered between runs
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+
+pub const DEFAULT_CONTACTS_WIDTH: u16 = 32;
+
+/// Stored as `state.json` in the data directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub contacts_width: u16,
+    /// Pinned chats in pinning order; kept locally only
+    #[serde(deserialize_with = "known_entries")]
+    pub pinned: Vec<Pin>,
+    /// Ids of collapsed contact list sections
+    pub collapsed: Vec<String>,
+    /// Tag whose folder is shown; None for all chats
+    pub folder: Option<String>,
+    /// Blocked contacts; kept locally only
+    #[serde(deserialize_with = "known_entries")]
+    pub blocked: Vec<Blocked>,
+    /// Do not disturb: no bell, desktop notification or hook
+    pub dnd: bool,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+/// A chat kept at the top of the contact list
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pin {
+    pub user_id: i64,
+    pub contact_id: i64,
+}
+
+/// A contact whose messages are hidden
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Blocked {
+    pub user_id: i64,
+    pub contact_id: i64,
+    /// From their profile, to turn down new requests under the same name
+    pub display_name: String,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self { contacts_width: DEFAULT_CONTACTS_WIDTH, pinned: vec![], collapsed: vec![], folder: None, blocked: vec![], dnd: false, path: None }
+    }
+}
+
+/// Drops entries in an older format, such as pins and blocks stored by
+/// name, so they do not reset the rest of the state
+fn known_entries<'de, D, T>(de: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let values = Vec::<serde_json::Value>::deserialize(de)?;
+    Ok(values.into_iter().filter_map(|v| serde_json::from_value(v).ok()).collect())
+}
+
+impl UiState {
+    /// A missing or unreadable file just means defaults; losing the pane
+    /// width is not worth refusing to start
+    pub fn load() -> Self {
+        let Some(path) = config::data_dir().map(|d| d.join("state.json")) else { return Self::default() };
+        let mut state: Self = fs::read_to_string(&path).ok()
+            .and_then(|txt| serde_json::from_str(&txt).ok())
+            .unwrap_or_default();
+        state.path = Some(path);
+        state
+    }
+    
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+        fs::write(path, serde_json::to_vec_pretty(self)?).with_context(|| format!("cannot write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn skips_entries_by_name() {
+        let state: UiState = serde_json::from_str(r#"{"pinned":["bob",{"user_id":1,"contact_id":3}],"collapsed":["unread"],"blocked":["eve",{"user_id":1,"contact_id":7,"display_name":"mallory"}]}"#).unwrap();
+        assert_eq!(state.pinned, vec![Pin { user_id: 1, contact_id: 3 }]);
+        assert_eq!(state.collapsed, vec!["unread"]);
+        assert_eq!(state.blocked, vec![Blocked { user_id: 1, contact_id: 7, display_name: "mallory".into() }]);
+    }
+}
@@ -0,0 +1,78 @@
+//! Chat tags: kept by simplex-chat when it supports them, otherwise in a
+//! local file
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::config;
+use crate::types::ChatTag;
+
+/// Local tags are stored as `tags.json`, keyed by `@contact` or `#group`
+#[derive(Default)]
+pub struct Tags {
+    /// The backend's tags; None until it answered or when it has no tags
+    pub backend: Option<Vec<ChatTag>>,
+    local: BTreeMap<String, Vec<String>>,
+    path: Option<PathBuf>,
+}
+
+pub fn chat_key(name: &str, group: bool) -> String {
+    format!("{}{}", if group { '#' } else { '@' }, name)
+}
+
+impl Tags {
+    pub fn load() -> Self {
+        let Some(path) = config::data_dir().map(|d| d.join("tags.json")) else { return Self::default() };
+        let local = fs::read_to_string(&path).ok()
+            .and_then(|txt| serde_json::from_str(&txt).ok())
+            .unwrap_or_default();
+        Self { backend: None, local, path: Some(path) }
+    }
+    
+    fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if let Some(dir) = path.parent() { fs::create_dir_all(dir)?; }
+        fs::write(path, serde_json::to_vec_pretty(&self.local)?).with_context(|| format!("cannot write {}", path.display()))
+    }
+    
+    /// Tag names of a chat; `ids` are the chat's backend tags
+    pub fn of(&self, key: &str, ids: &[i64]) -> Vec<String> {
+        match &self.backend {
+            Some(tags) => tags.iter().filter(|t| ids.contains(&t.chat_tag_id)).map(|t| t.chat_tag_text.clone()).collect(),
+            None => self.local.get(key).cloned().unwrap_or_default(),
+        }
+    }
+    
+    /// Every tag, in the backend's order or alphabetically for local tags
+    pub fn names(&self) -> Vec<String> {
+        match &self.backend {
+            Some(tags) => tags.iter().map(|t| t.chat_tag_text.clone()).collect(),
+            None => {
+                let mut names: Vec<String> = self.local.values().flatten().cloned().collect();
+                names.sort();
+                names.dedup();
+                names
+            }
+        }
+    }
+    
+    pub fn id(&self, name: &str) -> Option<i64> {
+        self.backend.as_ref()?.iter().find(|t| t.chat_tag_text == name).map(|t| t.chat_tag_id)
+    }
+    
+    pub fn set_local(&mut self, key: &str, tags: Vec<String>) -> Result<()> {
+        if tags.is_empty() {
+            self.local.remove(key);
+        } else {
+            self.local.insert(key.to_string(), tags);
+        }
+        self.save()
+    }
+}
@@ -0,0 +1,68 @@
+//! Small images as terminal half-block cells
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::io::Cursor;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage};
+
+/// One cell per column, each holding the top and bottom pixel of a `▀`
+#[derive(Clone, Debug)]
+pub struct Thumbnail {
+    pub rows: Vec<Vec<([u8; 3], [u8; 3])>>,
+}
+
+impl Thumbnail {
+    pub fn from_image(img: &DynamicImage, max_cols: u32, max_rows: u32) -> Self {
+        let img = img.resize(max_cols, max_rows * 2, FilterType::Triangle).to_rgb8();
+        let rows = (0..img.height().div_ceil(2)).map(|row| {
+            (0..img.width()).map(|x| {
+                let top = img.get_pixel(x, row * 2).0;
+                let bottom = if row * 2 + 1 < img.height() { img.get_pixel(x, row * 2 + 1).0 } else { top };
+                (top, bottom)
+            }).collect()
+        }).collect();
+        Self { rows }
+    }
+    
+    pub fn from_data_uri(uri: &str, max_cols: u32, max_rows: u32) -> Option<Self> {
+        let img = decode_data_uri(uri)?;
+        Some(Self::from_image(&img, max_cols, max_rows))
+    }
+}
+
+pub fn decode_data_uri(uri: &str) -> Option<DynamicImage> {
+    let (_, data) = uri.split_once(";base64,")?;
+    let bytes = STANDARD.decode(data.trim()).ok()?;
+    image::load_from_memory(&bytes).ok()
+}
+
+/// Shrinks an image to fit `max_px` and returns it as a JPEG data URI,
+/// the format SimpleX uses for previews and profile pictures
+pub fn encode_data_uri(img: &DynamicImage, max_px: u32) -> Option<String> {
+    jpeg_data_uri(&img.resize(max_px, max_px, FilterType::Triangle), 75)
+}
+
+/// Crops an image square and lowers quality, then size, until its data URI
+/// is at most `max_len` bytes, as profile pictures must be
+pub fn encode_square_data_uri(img: &DynamicImage, max_px: u32, max_len: usize) -> Option<String> {
+    let mut px = max_px;
+    while px >= 32 {
+        let square = img.resize_to_fill(px, px, FilterType::Triangle);
+        for quality in [85, 70, 55, 40] {
+            let uri = jpeg_data_uri(&square, quality)?;
+            if uri.len() <= max_len { return Some(uri); }
+        }
+        px = px * 3 / 4;
+    }
+    None
+}
+
+fn jpeg_data_uri(img: &DynamicImage, quality: u8) -> Option<String> {
+    let mut buf = Cursor::new(Vec::new());
+    JpegEncoder::new_with_quality(&mut buf, quality).encode_image(&img.to_rgb8()).ok()?;
+    Some(format!("data:image/jpg;base64,{}", STANDARD.encode(buf.into_inner())))
+}
@@ -0,0 +1,122 @@
+//! Disappearing messages: TTL parsing and countdown formatting
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local};
+
+use crate::types::FeatureState;
+
+const UNITS: [(&str, i64); 5] = [("w", 604_800), ("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)];
+
+/// `off`/`no` gives None; otherwise a number with an optional unit
+/// (`s`, `m`, `h`, `d`, `w`; seconds without one), e.g. `30s` or `1d`
+pub fn parse_ttl(text: &str) -> Result<Option<i64>> {
+    let text = text.trim().to_lowercase();
+    if matches!(text.as_str(), "off" | "no") { return Ok(None); }
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let Ok(number) = number.parse::<i64>() else { bail!("Expected off or a time such as 30s, 5m, 1h, 1d") };
+    let unit = match unit.trim() {
+        "" | "s" | "sec" => 1,
+        "m" | "min" => 60,
+        "h" | "hour" | "hours" => 3_600,
+        "d" | "day" | "days" => 86_400,
+        "w" | "week" | "weeks" => 604_800,
+        other => bail!("Unknown time unit: {}", other),
+    };
+    if number <= 0 { bail!("Time must be positive"); }
+    let Some(secs) = number.checked_mul(unit) else { bail!("Time is too long") };
+    Ok(Some(secs))
+}
+
+/// The largest unit that divides `secs` evenly, e.g. `90m` or `1d`
+pub fn format_ttl(secs: i64) -> String {
+    let (unit, size) = UNITS.iter().find(|(_, size)| secs % size == 0).copied().unwrap_or(("s", 1));
+    format!("{}{}", secs / size, unit)
+}
+
+/// Negotiated state for contact info and status messages
+pub fn describe(state: FeatureState) -> String {
+    let ttl = state.ttl.map(|t| format!(" ({})", format_ttl(t))).unwrap_or_default();
+    match (state.for_user, state.for_contact) {
+        (true, true) => format!("on{}", ttl),
+        (true, false) => format!("on for your messages{}", ttl),
+        (false, true) => format!("on for their messages{}", ttl),
+        (false, false) => "off".into(),
+    }
+}
+
+/// Time left until `delete_at` in its two largest units, e.g. `4m 12s`
+pub fn countdown(delete_at: DateTime<Local>) -> String {
+    let left = (delete_at - Local::now()).num_seconds().max(0);
+    let i = UNITS.iter().position(|(_, size)| left >= *size).unwrap_or(UNITS.len() - 1);
+    let (unit, size) = UNITS[i];
+    let mut text = format!("{}{}", left / size, unit);
+    if let Some((next, next_size)) = UNITS.get(i + 1) {
+        let n = left % size / next_size;
+        if n > 0 { text.push_str(&format!(" {}{}", n, next)); }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn parses_times_with_units() {
+        assert_eq!(parse_ttl("30").unwrap(), Some(30));
+        assert_eq!(parse_ttl("30s").unwrap(), Some(30));
+        assert_eq!(parse_ttl("5m").unwrap(), Some(300));
+        assert_eq!(parse_ttl(" 2 hours ").unwrap(), Some(7_200));
+        assert_eq!(parse_ttl("1D").unwrap(), Some(86_400));
+        assert_eq!(parse_ttl("2w").unwrap(), Some(1_209_600));
+    }
+    
+    #[test]
+    fn off_disables() {
+        assert_eq!(parse_ttl("off").unwrap(), None);
+        assert_eq!(parse_ttl("No").unwrap(), None);
+    }
+    
+    #[test]
+    fn rejects_bad_times() {
+        assert!(parse_ttl("").is_err());
+        assert!(parse_ttl("0").is_err());
+        assert!(parse_ttl("-5m").is_err());
+        assert!(parse_ttl("5y").is_err());
+        assert!(parse_ttl("soon").is_err());
+    }
+    
+    #[test]
+    fn rejects_overflowing_times() {
+        assert!(parse_ttl("99999999999999w").is_err());
+        assert!(parse_ttl("99999999999999999999").is_err());
+    }
+    
+    #[test]
+    fn formats_with_largest_even_unit() {
+        assert_eq!(format_ttl(30), "30s");
+        assert_eq!(format_ttl(90), "90s");
+        assert_eq!(format_ttl(5_400), "90m");
+        assert_eq!(format_ttl(86_400), "1d");
+        assert_eq!(format_ttl(1_209_600), "2w");
+        assert_eq!(format_ttl(parse_ttl("36h").unwrap().unwrap()), "36h");
+    }
+    
+    #[test]
+    fn countdown_shows_two_units() {
+        let at = |ms| Local::now() + chrono::Duration::milliseconds(ms);
+        assert_eq!(countdown(at(252_500)), "4m 12s");
+        assert_eq!(countdown(at(7_200_500)), "2h");
+        assert_eq!(countdown(at(9_500)), "9s");
+    }
+    
+    #[test]
+    fn countdown_stops_at_zero() {
+        assert_eq!(countdown(Local::now() - chrono::Duration::seconds(5)), "0s");
+    }
+}
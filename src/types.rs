@@ -4,17 +4,160 @@
 //! GitHub: https://github.com/cannatoshi/simplex-tui
 //! Licensed under AGPL-3.0
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::Action;
+use crate::thumbnail::Thumbnail;
 
 #[derive(Clone, Debug)]
 pub struct Contact {
+    pub id: i64,
     pub name: String,
     pub unread: usize,
+    pub settings: ChatSettings,
+    /// Backend chat tags, by id
+    pub tag_ids: Vec<i64>,
+    pub profile: Profile,
+    /// Name set locally with `/_set alias`; empty when unset
+    pub alias: String,
+    /// Security code was verified
+    pub verified: bool,
+    /// Connected with a random profile instead of ours
+    pub incognito: bool,
+    /// Name of that random profile; fetched with `/_info` when needed
+    pub incognito_name: Option<String>,
+    pub prefs: ContactPrefs,
 }
 
 impl Contact {
-    pub fn new(name: String) -> Self {
-        Self { name, unread: 0 }
+    pub fn new(id: i64, name: String) -> Self {
+        Self { id, name, unread: 0, settings: ChatSettings::default(), tag_ids: vec![], profile: Profile::default(), alias: String::new(), verified: false, incognito: false, incognito_name: None, prefs: ContactPrefs::default() }
+    }
+    
+    /// The alias when set, otherwise the name
+    pub fn label(&self) -> &str {
+        if self.alias.is_empty() { &self.name } else { &self.alias }
+    }
+}
+
+/// Per-contact preferences: ours for this contact, and what both sides
+/// ended up with
+#[derive(Clone, Debug, Default)]
+pub struct ContactPrefs {
+    /// `userPreferences`, sent back whole with `/_set prefs`
+    pub user: serde_json::Value,
+    /// `mergedPreferences`
+    pub merged: serde_json::Value,
+}
+
+/// One feature as negotiated with a contact
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FeatureState {
+    /// We may use it
+    pub for_user: bool,
+    /// The contact may use it
+    pub for_contact: bool,
+    /// Disappearing messages only: seconds until deletion
+    pub ttl: Option<i64>,
+}
+
+impl ContactPrefs {
+    pub fn state(&self, feature: &str) -> FeatureState {
+        let merged = &self.merged[feature];
+        FeatureState {
+            for_user: merged["enabled"]["forUser"].as_bool().unwrap_or(false),
+            for_contact: merged["enabled"]["forContact"].as_bool().unwrap_or(false),
+            ttl: merged["userPreference"]["preference"]["ttl"].as_i64().or_else(|| merged["contactPreference"]["ttl"].as_i64()),
+        }
+    }
+    
+    /// Seconds our new messages live, when disappearing messages are on
+    pub fn timed_ttl(&self) -> Option<i64> {
+        let state = self.state("timedMessages");
+        state.ttl.filter(|_| state.for_user)
+    }
+}
+
+/// A contact's profile as they published it
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Profile {
+    pub display_name: String,
+    pub full_name: String,
+    #[serde(rename = "shortDescr")]
+    pub bio: Option<String>,
+    /// Data URI
+    pub image: Option<String>,
+    pub contact_link: Option<String>,
+}
+
+/// A chat profile in simplex-chat; each has its own contacts and chats
+#[derive(Clone, Debug)]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    pub active: bool,
+    /// Hidden behind a password; switched to with `/user name password`
+    pub hidden: bool,
+    pub muted: bool,
+    pub unread: usize,
+    /// As the backend sent it, so renaming keeps fields the client ignores
+    pub profile: serde_json::Value,
+}
+
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub id: i64,
+    pub name: String,
+    pub tag_ids: Vec<i64>,
+    pub settings: ChatSettings,
+}
+
+/// Someone who asked to connect through our address
+#[derive(Clone, Debug)]
+pub struct ContactRequest {
+    /// For `/_accept` and `/_reject`
+    pub id: i64,
+    pub name: String,
+    /// Name in their profile; `name` gets a suffix when it is taken
+    pub display_name: String,
+}
+
+/// A one-time invitation link we created that nobody has used yet
+#[derive(Clone, Debug)]
+pub struct Invitation {
+    /// Pending connection id, for `/_delete :id`
+    pub conn_id: i64,
+    pub link: String,
+    pub incognito: bool,
+    pub created_at: String,
+}
+
+/// A tag defined in simplex-chat
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatTag {
+    pub chat_tag_id: i64,
+    pub chat_tag_text: String,
+}
+
+/// Per-chat settings as the backend stores them; sent back whole by `/_settings`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatSettings {
+    pub enable_ntfs: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_rcpts: Option<bool>,
+    #[serde(default)]
+    pub favorite: bool,
+}
+
+/// Notification levels for `enableNtfs`, in the order they cycle
+pub const NTFS_LEVELS: [&str; 3] = ["all", "mentions", "none"];
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        Self { enable_ntfs: "all".into(), send_rcpts: None, favorite: false }
     }
 }
 
@@ -29,11 +172,69 @@ pub enum MessageStatus {
 
 #[derive(Clone, Debug)]
 pub struct ChatMessage {
+    /// Backend item id; None until a locally shown message is echoed back
+    pub id: Option<i64>,
     pub sender: String,
     pub content: String,
     pub time: String,
     pub mine: bool,
     pub status: MessageStatus,
+    pub voice: Option<VoiceClip>,
+    pub link: Option<LinkPreview>,
+    pub timer: Option<Timer>,
+}
+
+/// Disappearing message timer
+#[derive(Clone, Copy, Debug)]
+pub struct Timer {
+    pub ttl: i64,
+    /// Set once the countdown started: on sending, or when read
+    pub delete_at: Option<chrono::DateTime<chrono::Local>>,
+}
+
+/// A message found by the backend search
+#[derive(Clone, Debug)]
+pub struct SearchHit {
+    /// Contact or group name
+    pub chat: String,
+    pub group: bool,
+    pub item_id: i64,
+    pub time: String,
+    pub sender: String,
+    pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct VoiceClip {
+    pub duration: u64,
+    pub file_id: Option<u64>,
+    pub path: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LinkPreview {
+    pub uri: String,
+    pub title: String,
+    pub description: String,
+    /// JPEG data URI as sent over the wire
+    pub image: Option<String>,
+    pub thumbnail: Option<Thumbnail>,
+}
+
+impl LinkPreview {
+    pub fn new(uri: String, title: String, description: String, image: Option<String>) -> Self {
+        let thumbnail = image.as_deref().and_then(|i| Thumbnail::from_data_uri(i, 12, 4));
+        Self { uri, title, description, image, thumbnail }
+    }
+    
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "uri": self.uri,
+            "title": self.title,
+            "description": self.description,
+            "image": self.image.clone().unwrap_or_default(),
+        })
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -48,6 +249,16 @@ pub enum Mode {
     ContactOptions,
     ContactInfo,
     FileBrowser,
+    ChatSearch,
+    Command,
+    Console,
+    Palette,
+    GlobalSearch,
+    ContactFilter,
+    Verify,
+    Users,
+    Profile,
+    ContactPrefs,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -55,6 +266,13 @@ pub enum ContactOption {
     DeleteContact,
     ClearChat,
     ContactInfo,
+    SetAlias,
+    Verify,
+    Disappearing,
+    Preferences,
+    Notifications,
+    Block,
+    ShareProfile,
     Cancel,
 }
 
@@ -64,6 +282,13 @@ impl ContactOption {
             Self::DeleteContact,
             Self::ClearChat,
             Self::ContactInfo,
+            Self::SetAlias,
+            Self::Verify,
+            Self::Disappearing,
+            Self::Preferences,
+            Self::Notifications,
+            Self::Block,
+            Self::ShareProfile,
             Self::Cancel,
         ]
     }
@@ -73,21 +298,37 @@ impl ContactOption {
             Self::DeleteContact => "Delete Contact",
             Self::ClearChat => "Clear Chat History",
             Self::ContactInfo => "Contact Info",
+            Self::SetAlias => "Set Alias",
+            Self::Verify => "Verify Security Code",
+            Self::Disappearing => "Disappearing Messages",
+            Self::Preferences => "Chat Preferences",
+            Self::Notifications => "Notifications",
+            Self::Block => "Block / Unblock",
+            Self::ShareProfile => "Share Main Profile",
             Self::Cancel => "Cancel",
         }
     }
     
-    pub fn key(&self) -> char {
+    /// Keymap action selecting this option in the modal
+    pub fn action(&self) -> Action {
         match self {
-            Self::DeleteContact => 'd',
-            Self::ClearChat => 'c',
-            Self::ContactInfo => 'i',
-            Self::Cancel => 'x',
+            Self::DeleteContact => Action::DeleteContact,
+            Self::ClearChat => Action::ClearChat,
+            Self::ContactInfo => Action::ContactInfo,
+            Self::SetAlias => Action::SetAlias,
+            Self::Verify => Action::VerifyContact,
+            Self::Disappearing => Action::SetDisappearing,
+            Self::Preferences => Action::ContactPrefs,
+            Self::Notifications => Action::SetNotifications,
+            Self::Block => Action::ToggleBlock,
+            Self::ShareProfile => Action::ShareProfile,
+            Self::Cancel => Action::Back,
         }
     }
     
+    /// Needs confirming; sharing the main profile cannot be taken back either
     pub fn is_destructive(&self) -> bool {
-        matches!(self, Self::DeleteContact | Self::ClearChat)
+        matches!(self, Self::DeleteContact | Self::ClearChat | Self::ShareProfile)
     }
 }
 
@@ -96,16 +337,47 @@ pub enum SimplexEvent {
     Connected,
     Disconnected,
     Contacts(Vec<Contact>),
+    Groups(Vec<Group>),
+    /// `profile` carries the user's full preferences
+    ActiveUser { id: i64, name: String, profile: serde_json::Value },
+    Users(Vec<User>),
+    /// A profile was hidden, renamed or deleted; the list needs reloading
+    UsersChanged,
+    /// The user's chat tags; None when the backend has no tag support
+    ChatTags(Option<Vec<ChatTag>>),
     Messages(Vec<ChatMessage>),
-    NewMessage { sender: String, text: String },
+    NewMessage { sender: String, message: ChatMessage },
     MessageUpdate { status: MessageStatus },
     InviteLink(String),
+    /// A one-time link was created
+    Invitation(Invitation),
+    /// Pending one-time links, oldest first
+    Invitations(Vec<Invitation>),
+    InvitationUpdated(Invitation),
+    InvitationDeleted(i64),
     AddressDeleted,
     AddressCreated,
     ContactRequest(String),
+    /// Someone asked to connect through our address
+    IncomingRequest(ContactRequest),
+    /// Requests still waiting for an answer
+    ContactRequests(Vec<ContactRequest>),
     ContactDeleted(String),
     ChatCleared(String),
+    FileReceived { file_id: u64, path: String },
     ContactInfo(ContactInfoData),
+    /// A contact changed, e.g. its alias
+    ContactUpdated(Contact),
+    SecurityCode { contact: Contact, code: String },
+    /// The random profile an incognito contact knows us by
+    IncognitoProfile { contact_id: i64, name: String },
+    /// Result of comparing a typed code; `expected` is our side's code
+    CodeVerified { verified: bool, expected: String },
+    CommandResponse { corr_id: String, response: serde_json::Value },
+    /// Backend search results, for the open chat or across all chats
+    SearchResults { global: bool, hits: Vec<SearchHit> },
+    /// Items deleted in a chat, e.g. disappearing messages that expired
+    ItemsDeleted { chat: String, ids: Vec<i64> },
     Error(String),
     Status(String),
 }
@@ -133,13 +405,33 @@ impl ApiCommand {
 #[derive(Debug, Clone, Default)]
 pub struct ContactInfoData {
     pub name: String,
-    pub bio: String,
-    pub address: String,
+    pub alias: String,
+    pub profile: Profile,
+    pub avatar: Option<Thumbnail>,
     pub receiving_server: String,
     pub sending_server: String,
     pub created_at: String,
     pub updated_at: String,
     pub pq_encryption: bool,
+    pub verified: bool,
+    /// Random profile name this contact sees; None when not incognito
+    pub incognito_name: Option<String>,
+    pub prefs: ContactPrefs,
     pub connection_status: String,
     pub chat_version: String,
 }
+
+/// Comparing security codes with a contact
+#[derive(Debug, Clone)]
+pub struct Verification {
+    pub contact: String,
+    pub contact_id: i64,
+    /// Our side's code
+    pub code: String,
+    /// The peer's code as typed or pasted
+    pub input: String,
+    /// Outcome of the last comparison
+    pub matched: Option<bool>,
+    /// Waiting for the backend to drop the verification
+    pub clearing: bool,
+}
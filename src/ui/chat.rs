@@ -4,39 +4,65 @@
 //! GitHub: https://github.com/cannatoshi/simplex-tui
 //! Licensed under AGPL-3.0
 
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
 use ratatui::{
     Frame, layout::Rect,
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
 };
 
+use crate::actions::Action;
 use crate::app::App;
+use crate::audio;
 use crate::colors;
-use crate::types::MessageStatus;
+use crate::hitmap::Hit;
+use crate::link_preview;
+use crate::search;
+use crate::thumbnail::Thumbnail;
+use crate::timed;
+use crate::types::{ChatMessage, LinkPreview, MessageStatus, Timer};
 
 pub fn render_header(frame: &mut Frame, area: Rect, app: &App) {
     let w = area.width as usize;
     let title = "SimpleX TUI (cyberdeck)";
     
     let header = if let Some(name) = &app.current_contact {
-        let initials = colors::get_initials(name);
+        let contact = app.contacts.iter().find(|c| &c.name == name);
+        let label = contact.map(|c| c.label()).unwrap_or(name);
+        let initials = colors::get_initials(label);
         let color = colors::avatar_color(name);
         
-        let left_len = 1 + 1 + initials.len() + 2 + name.len();
-        let right_len = title.len() + 1;
-        let space = w.saturating_sub(left_len + right_len);
-        
-        Line::from(vec![
+        let mut spans = vec![
             Span::styled(" ", Style::default()),
             Span::styled("[", Style::default().fg(color)),
             Span::styled(initials, Style::default().fg(color).add_modifier(Modifier::BOLD)),
             Span::styled("] ", Style::default().fg(color)),
-            Span::styled(name, Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD)),
-            Span::styled(" ".repeat(space), Style::default()),
-            Span::styled(title, Style::default().fg(colors::BLUE)),
-            Span::styled(" ", Style::default()),
-        ])
+            Span::styled(label, Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)),
+        ];
+        if label != name {
+            spans.push(Span::styled(format!("  {}", name), Style::default().fg(colors::text_dim())));
+        }
+        if contact.is_some_and(|c| c.verified) {
+            spans.push(Span::styled("  ✓ verified", Style::default().fg(colors::success())));
+        }
+        if let Some(c) = contact.filter(|c| c.incognito) {
+            let text = match &c.incognito_name {
+                Some(random) => format!("  incognito as {}", random),
+                None => "  incognito".to_string(),
+            };
+            spans.push(Span::styled(text, Style::default().fg(colors::warning())));
+        }
+        
+        let left_len: usize = spans.iter().map(|s| s.content.chars().count()).sum();
+        let right_len = title.len() + 1;
+        let space = w.saturating_sub(left_len + right_len);
+        spans.push(Span::styled(" ".repeat(space), Style::default()));
+        spans.push(Span::styled(title, Style::default().fg(colors::accent())));
+        spans.push(Span::styled(" ", Style::default()));
+        Line::from(spans)
     } else {
         let left_text = "No chat selected";
         let left_len = left_text.len() + 2;
@@ -45,17 +71,17 @@ pub fn render_header(frame: &mut Frame, area: Rect, app: &App) {
         
         Line::from(vec![
             Span::styled(" ", Style::default()),
-            Span::styled(left_text, Style::default().fg(colors::TEXT_DIM)),
+            Span::styled(left_text, Style::default().fg(colors::text_dim())),
             Span::styled(" ".repeat(space), Style::default()),
-            Span::styled(title, Style::default().fg(colors::BLUE)),
+            Span::styled(title, Style::default().fg(colors::accent())),
             Span::styled(" ", Style::default()),
         ])
     };
     
     let block = Block::default()
         .borders(Borders::BOTTOM)
-        .border_style(Style::default().fg(colors::BORDER))
-        .style(Style::default().bg(colors::BG_SECONDARY));
+        .border_style(Style::default().fg(colors::border()))
+        .style(Style::default().bg(colors::bg_secondary()));
     
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -65,7 +91,7 @@ pub fn render_header(frame: &mut Frame, area: Rect, app: &App) {
 pub fn render_messages(frame: &mut Frame, area: Rect, app: &mut App) {
     let block = Block::default()
         .borders(Borders::NONE)
-        .style(Style::default().bg(colors::BG));
+        .style(Style::default().bg(colors::bg()));
     
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -77,51 +103,229 @@ pub fn render_messages(frame: &mut Frame, area: Rect, app: &mut App) {
             "← Select a contact to start chatting" 
         };
         frame.render_widget(
-            Paragraph::new(Line::from(Span::styled(format!("  {}", msg), Style::default().fg(colors::TEXT_DIM)))),
+            Paragraph::new(Line::from(Span::styled(format!("  {}", msg), Style::default().fg(colors::text_dim())))),
             inner
         );
         return;
     }
     
+    // Rightmost column is kept for the scrollbar
+    let text_area = Rect { width: inner.width.saturating_sub(1), ..inner };
+    let width = text_area.width;
+    let wrapped = |ls: &[Line]| Paragraph::new(ls.to_vec()).wrap(Wrap { trim: false }).line_count(width);
+    let play_hint = app.keymap.hint(Action::PlayVoice);
+    let current_hit = app.search_hits.get(app.search_pos).copied();
+    let view = |index: usize| MessageView {
+        index,
+        width,
+        playing: app.playing_voice,
+        play_hint: &play_hint,
+        query: app.search_query.as_deref(),
+        current: current_hit == Some(index),
+    };
+    
+    // Wrapped heights are cached, so only changed messages get rewrapped
+    app.chat_heights.resize(app.messages.len(), (0, 0));
+    let mut offsets = Vec::with_capacity(app.messages.len());
+    let mut total: usize = 0;
+    for (index, msg) in app.messages.iter().enumerate() {
+        let key = view(index).key(msg);
+        let cached = &mut app.chat_heights[index];
+        if cached.0 != key || cached.1 == 0 {
+            *cached = (key, wrapped(&view(index).lines(msg).0));
+        }
+        offsets.push(total);
+        total += cached.1;
+    }
+    
+    let vis = inner.height as usize;
+    let wanted = app.pending_jump.take().and_then(|i| offsets.get(i).copied()).unwrap_or(app.scroll);
+    let scroll = wanted.min(total.saturating_sub(vis));
+    app.scroll = scroll;
+    app.chat_view_height = vis;
+    
+    // Only the messages in view are built and drawn
+    let first = offsets.partition_point(|&o| o <= scroll).saturating_sub(1);
     let mut lines: Vec<Line> = Vec::new();
+    // Clickable parts as (first row, rows, target), in wrapped rows
+    let mut targets: Vec<(usize, usize, Hit)> = vec![];
+    for (index, msg) in app.messages.iter().enumerate().skip(first) {
+        let top = offsets[index];
+        if top >= scroll + vis { break; }
+        let (msg_lines, parts) = view(index).lines(msg);
+        let height = app.chat_heights[index].1;
+        targets.push((top, height.saturating_sub(1), Hit::Message(index)));
+        for (start, end, hit) in parts {
+            targets.push((top + wrapped(&msg_lines[..start]), wrapped(&msg_lines[start..end]), hit));
+        }
+        lines.extend(msg_lines);
+    }
+    
+    for (row, height, hit) in targets {
+        let top = row.max(scroll);
+        let bottom = (row + height).min(scroll + vis);
+        if top >= bottom { continue; }
+        let rect = Rect { y: text_area.y + (top - scroll) as u16, height: (bottom - top) as u16, ..text_area };
+        app.hits.add(rect, hit);
+    }
+    
+    let skip = scroll - offsets.get(first).copied().unwrap_or(0);
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).scroll((skip as u16, 0)), text_area);
     
-    for msg in &app.messages {
-        let sender_color = if msg.mine { colors::BLUE } else { colors::TEXT };
+    if total > vis {
+        let mut state = ScrollbarState::new(total.saturating_sub(vis)).position(scroll);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_style(Style::default().fg(colors::border()))
+            .thumb_style(Style::default().fg(colors::accent()));
+        frame.render_stateful_widget(scrollbar, inner, &mut state);
+        let track = Rect { x: inner.x + inner.width.saturating_sub(1), width: 1, ..inner };
+        app.hits.add(track, Hit::Scrollbar { total, visible: vis });
+    }
+}
+
+/// What a message is drawn with besides the message itself
+struct MessageView<'a> {
+    index: usize,
+    width: u16,
+    playing: Option<u64>,
+    play_hint: &'a str,
+    query: Option<&'a str>,
+    current: bool,
+}
+
+impl<'a> MessageView<'a> {
+    /// Changes whenever the message would wrap differently; search marks
+    /// only restyle text, so they are left out
+    fn key(&self, msg: &ChatMessage) -> u64 {
+        let mut h = DefaultHasher::new();
+        (self.width, &msg.sender, &msg.time, msg.mine, msg.status as u8, &msg.content).hash(&mut h);
+        msg.timer.map(timer_text).hash(&mut h);
+        if let Some(voice) = &msg.voice {
+            (voice.duration, voice.file_id.is_some() && self.playing == voice.file_id, self.play_hint).hash(&mut h);
+        }
+        if let Some(link) = &msg.link {
+            (&link.title, &link.description, &link.uri, link.thumbnail.as_ref().map(|t| t.rows.len())).hash(&mut h);
+        }
+        h.finish()
+    }
+    
+    /// The message's lines, ending with a blank one, and its clickable
+    /// parts as line ranges
+    fn lines(&self, msg: &'a ChatMessage) -> (Vec<Line<'a>>, Vec<(usize, usize, Hit)>) {
+        let index = self.index;
+        let mut lines = vec![];
+        let sender_color = if msg.mine { colors::accent() } else { colors::text() };
         
         let mut header_spans = vec![
             Span::styled(" ", Style::default()),
             Span::styled(&msg.sender, Style::default().fg(sender_color).add_modifier(Modifier::BOLD)),
-            Span::styled(format!("  {}", msg.time), Style::default().fg(colors::TEXT_DIM)),
+            Span::styled(format!("  {}", msg.time), Style::default().fg(colors::text_dim())),
         ];
         
         if msg.mine {
             let (icon, color) = match msg.status {
-                MessageStatus::Sending => ("  ○", colors::TEXT_DIM),
-                MessageStatus::Sent => ("  ✓", colors::BLUE),
-                MessageStatus::Delivered => ("  ✓✓", colors::BLUE),
-                MessageStatus::Read => ("  ✓✓", colors::BLUE_LIGHT),
-                MessageStatus::Failed => ("  ✗", colors::DANGER),
+                MessageStatus::Sending => ("  ○", colors::text_dim()),
+                MessageStatus::Sent => ("  ✓", colors::accent()),
+                MessageStatus::Delivered => ("  ✓✓", colors::accent()),
+                MessageStatus::Read => ("  ✓✓", colors::accent_light()),
+                MessageStatus::Failed => ("  ✗", colors::danger()),
             };
             header_spans.push(Span::styled(icon, Style::default().fg(color)));
         }
+        if let Some(timer) = msg.timer {
+            header_spans.push(Span::styled(format!("  ⏱ {}", timer_text(timer)), Style::default().fg(colors::warning())));
+        }
         
         lines.push(Line::from(header_spans));
-        lines.push(Line::from(vec![
-            Span::styled(" ", Style::default()),
-            Span::styled(&msg.content, Style::default().fg(colors::TEXT)),
-        ]));
+        let mut parts: Vec<(usize, usize, Hit)> = vec![];
+        if let Some(voice) = &msg.voice {
+            let playing = voice.file_id.is_some() && self.playing == voice.file_id;
+            let (control, color) = if playing { ("■", colors::success()) } else { ("▶", colors::accent()) };
+            parts.push((lines.len(), lines.len() + 1, Hit::Voice(index)));
+            lines.push(Line::from(vec![
+                Span::styled(" 🎤 ", Style::default()),
+                Span::styled(control, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                Span::styled(format!(" {}", audio::format_duration(voice.duration)), Style::default().fg(colors::text())),
+                Span::styled(
+                    if playing { "  playing".to_string() } else { format!("  {} play", self.play_hint) },
+                    Style::default().fg(colors::text_dim()),
+                ),
+            ]));
+        }
+        for text_line in msg.content.split('\n').filter(|_| !msg.content.is_empty()) {
+            if let Some(url) = link_preview::find_url(text_line) {
+                parts.push((lines.len(), lines.len() + 1, Hit::Link(url.to_string())));
+            }
+            let mut spans = vec![Span::styled(" ", Style::default())];
+            spans.extend(highlight(text_line, self.query, Style::default().fg(colors::text()), self.current));
+            lines.push(Line::from(spans));
+        }
+        if let Some(link) = &msg.link {
+            let card = link_card(link, self.width as usize);
+            parts.push((lines.len(), lines.len() + card.len(), Hit::Link(link.uri.clone())));
+            lines.extend(card);
+        }
         lines.push(Line::from(""));
+        (lines, parts)
     }
-    
-    let total = lines.len() as u16;
-    let vis = inner.height;
-    let scroll = if app.scroll == usize::MAX {
-        if total > vis { total - vis } else { 0 }
-    } else {
-        (app.scroll as u16).min(total.saturating_sub(vis))
+}
+
+/// Received ones only start counting down once read
+fn timer_text(timer: Timer) -> String {
+    timer.delete_at.map_or_else(|| timed::format_ttl(timer.ttl), timed::countdown)
+}
+
+/// Splits `text` into spans with search matches marked; the hit being
+/// shown gets the accent color
+pub fn highlight<'a>(text: &'a str, query: Option<&str>, style: Style, current: bool) -> Vec<Span<'a>> {
+    let matches = query.map(|q| search::find_matches(text, q)).unwrap_or_default();
+    let mark = Style::default().fg(colors::bg()).bg(if current { colors::accent() } else { colors::warning() });
+    let mut spans = vec![];
+    let mut pos = 0;
+    for m in matches {
+        if m.start > pos { spans.push(Span::styled(&text[pos..m.start], style)); }
+        spans.push(Span::styled(&text[m.start..m.end], mark));
+        pos = m.end;
+    }
+    if pos < text.len() || spans.is_empty() { spans.push(Span::styled(&text[pos..], style)); }
+    spans
+}
+
+fn link_card(link: &LinkPreview, width: usize) -> Vec<Line<'static>> {
+    let thumb_w = link.thumbnail.as_ref().and_then(|t| t.rows.first()).map(|r| r.len() + 1).unwrap_or(0);
+    let text_w = width.saturating_sub(4 + thumb_w).max(8);
+    let clip = |s: &str| -> String {
+        if s.chars().count() > text_w { format!("{}…", s.chars().take(text_w - 1).collect::<String>()) } else { s.to_string() }
     };
     
-    if app.scroll == usize::MAX { app.scroll = scroll as usize; }
+    let text = [
+        Span::styled(clip(&link.title), Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)),
+        Span::styled(clip(&link.description), Style::default().fg(colors::text_muted())),
+        Span::styled(clip(&link.uri), Style::default().fg(colors::accent_light())),
+    ];
+    let thumb_rows = link.thumbnail.as_ref().map(|t| t.rows.len()).unwrap_or(0);
     
-    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).scroll((scroll, 0)), inner);
+    (0..text.len().max(thumb_rows)).map(|i| {
+        let mut spans = vec![Span::styled("  ┃ ", Style::default().fg(colors::accent()))];
+        if let Some(thumb) = &link.thumbnail {
+            spans.extend(thumbnail_row(thumb, i));
+            spans.push(Span::raw(" "));
+        }
+        if let Some(t) = text.get(i) { spans.push(t.clone()); }
+        Line::from(spans)
+    }).collect()
+}
+
+pub fn thumbnail_row(thumb: &Thumbnail, row: usize) -> Vec<Span<'static>> {
+    let width = thumb.rows.first().map(|r| r.len()).unwrap_or(0);
+    match thumb.rows.get(row) {
+        Some(cells) => cells.iter().map(|(top, bottom)| {
+            Span::styled("▀", Style::default()
+                .fg(colors::adapt(Color::Rgb(top[0], top[1], top[2])))
+                .bg(colors::adapt(Color::Rgb(bottom[0], bottom[1], bottom[2]))))
+        }).collect(),
+        None => vec![Span::raw(" ".repeat(width))],
+    }
 }
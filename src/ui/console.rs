@@ -0,0 +1,60 @@
+//! Command response pane
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+
+use crate::app::App;
+use crate::colors;
+use crate::command;
+use crate::types::Mode;
+
+pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
+    let focused = app.mode == Mode::Console;
+    let format = if app.console.json { "json" } else { "pretty" };
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .border_style(Style::default().fg(if focused { colors::accent() } else { colors::border() }))
+        .title(Span::styled(format!(" Console · {} ", format), Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
+        .title_bottom(Span::styled(" [t] json/pretty  [c] clear  [:] command  [Esc] close ", Style::default().fg(colors::text_dim())))
+        .style(Style::default().bg(colors::bg()));
+    
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    
+    let mut lines: Vec<Line> = Vec::new();
+    for entry in &app.console.entries {
+        lines.push(Line::from(vec![
+            Span::styled(" › ", Style::default().fg(colors::accent())),
+            Span::styled(entry.command.as_str(), Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)),
+        ]));
+        match &entry.response {
+            Some(resp) => {
+                let body = if app.console.json {
+                    serde_json::to_string_pretty(resp).unwrap_or_default().lines().map(String::from).collect()
+                } else {
+                    command::pretty(resp)
+                };
+                lines.extend(body.into_iter().map(|l| Line::from(Span::styled(format!("   {}", l), Style::default().fg(colors::text_muted())))));
+            }
+            None => lines.push(Line::from(Span::styled("   waiting for response...", Style::default().fg(colors::text_dim())))),
+        }
+        lines.push(Line::from(""));
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled("  Type : then a /command to see its response here", Style::default().fg(colors::text_dim()))));
+    }
+    
+    let para = Paragraph::new(lines).wrap(Wrap { trim: false });
+    let max_scroll = para.line_count(inner.width).saturating_sub(inner.height as usize);
+    app.console.scroll = app.console.scroll.min(max_scroll);
+    frame.render_widget(para.scroll((app.console.scroll.min(u16::MAX as usize) as u16, 0)), inner);
+}
@@ -11,13 +11,17 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
+use crate::actions::{Action, ACTION_BAR};
 use crate::app::App;
 use crate::colors;
+use crate::contact_list::Row;
+use crate::hitmap::Hit;
+use crate::types::Mode;
 
 pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
     let block = Block::default()
         .borders(Borders::NONE)
-        .style(Style::default().bg(colors::BG));
+        .style(Style::default().bg(colors::bg()));
     
     frame.render_widget(block, area);
     
@@ -25,98 +29,208 @@ pub fn render(frame: &mut Frame, area: Rect, app: &mut App) {
     let w = area.width as usize;
     let sep: String = "─".repeat(w);
     
-    lines.push(Line::from(Span::styled(
-        " Contacts",
-        Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD)
-    )));
-    lines.push(Line::from(Span::styled(sep.clone(), Style::default().fg(colors::BORDER))));
+    let folders = app.folders();
+    if folders.len() > 1 {
+        lines.push(folder_tabs(app, &folders, area));
+    } else {
+        lines.push(Line::from(Span::styled(
+            " Contacts",
+            Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)
+        )));
+    }
+    let filtering = app.mode == Mode::ContactFilter;
+    if filtering || !app.contact_filter.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled(" / ", Style::default().fg(colors::accent())),
+            Span::styled(app.contact_filter.clone(), Style::default().fg(colors::text())),
+            Span::styled(if filtering { "▌" } else { "" }, Style::default().fg(colors::accent())),
+        ]));
+    }
+    lines.push(Line::from(Span::styled(sep.clone(), Style::default().fg(colors::border()))));
+    let header = lines.len();
     
+    let rows = app.contact_rows();
     if app.contacts.is_empty() {
         lines.push(Line::from(""));
-        lines.push(Line::from(Span::styled("  No contacts", Style::default().fg(colors::TEXT_DIM))));
-        lines.push(Line::from(Span::styled("  Press [i] to add", Style::default().fg(colors::TEXT_DIM))));
-        lines.push(Line::from(Span::styled("  Double-click for options", Style::default().fg(colors::TEXT_DIM))));
-    } else {
-        for (i, contact) in app.contacts.iter().enumerate() {
-            let selected = app.contact_state.selected() == Some(i);
-            let bg = if selected { colors::BG_HIGHLIGHT } else { colors::BG };
-            
-            let marker = if selected { "▸ " } else { "  " };
-            let marker_color = if selected { colors::BLUE } else { colors::TEXT_DIM };
-            
-            let initials = colors::get_initials(&contact.name);
-            let avatar_color = colors::avatar_color(&contact.name);
-            
-            let name_style = if selected {
-                Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD)
-            } else {
-                Style::default().fg(colors::TEXT)
-            };
-            
-            let mut spans = vec![
-                Span::styled(marker.to_string(), Style::default().fg(marker_color).bg(bg)),
-                Span::styled("[".to_string(), Style::default().fg(avatar_color).bg(bg)),
-                Span::styled(initials.clone(), Style::default().fg(avatar_color).add_modifier(Modifier::BOLD).bg(bg)),
-                Span::styled("] ".to_string(), Style::default().fg(avatar_color).bg(bg)),
-                Span::styled(contact.name.clone(), name_style.bg(bg)),
-            ];
-            
-            let unread_text = if contact.unread > 0 {
-                format!(" ({})", contact.unread)
-            } else {
-                String::new()
-            };
-            
-            if contact.unread > 0 {
-                spans.push(Span::styled(
-                    unread_text.clone(),
-                    Style::default().fg(colors::UNREAD).add_modifier(Modifier::BOLD).bg(bg)
-                ));
+        lines.push(Line::from(Span::styled("  No contacts", Style::default().fg(colors::text_dim()))));
+        lines.push(Line::from(Span::styled(format!("  Press {} to add", app.keymap.hint(Action::AddContact)), Style::default().fg(colors::text_dim()))));
+        lines.push(Line::from(Span::styled("  Double-click for options", Style::default().fg(colors::text_dim()))));
+        frame.render_widget(Paragraph::new(lines), area);
+        return;
+    }
+    if rows.is_empty() {
+        lines.push(Line::from(Span::styled("  No match", Style::default().fg(colors::text_dim()))));
+    }
+    
+    // Lines of the list below the header, as (first line, lines, hit)
+    let mut list: Vec<Line> = vec![];
+    let mut spans_of: Vec<(usize, usize, Hit)> = vec![];
+    let mut selected_span = None;
+    for row in &rows {
+        let first = list.len();
+        match *row {
+            Row::Section(section, count) => {
+                let collapsed = app.ui_state.collapsed.iter().any(|c| c == section.id());
+                let selected = app.selected_section == Some(section);
+                let bg = if selected { colors::bg_highlight() } else { colors::bg() };
+                let arrow = if collapsed { "▸" } else { "▾" };
+                let label = format!(" {} {} ({})", arrow, section.label(), count);
+                let pad = w.saturating_sub(label.chars().count());
+                list.push(Line::from(vec![
+                    Span::styled(label, Style::default().fg(colors::text_muted()).add_modifier(Modifier::BOLD).bg(bg)),
+                    Span::styled(" ".repeat(pad), Style::default().bg(bg)),
+                ]));
+                if selected { selected_span = Some((first, 1)); }
+                spans_of.push((first, 1, Hit::Section(section)));
+            }
+            Row::Contact(i) => {
+                let contact = &app.contacts[i];
+                let selected = app.selected_section.is_none() && app.contact_state.selected() == Some(i);
+                let bg = if selected { colors::bg_highlight() } else { colors::bg() };
+                
+                let marker = if selected { "▸ " } else { "  " };
+                let marker_color = if selected { colors::accent() } else { colors::text_dim() };
+                
+                let initials = colors::get_initials(contact.label());
+                let avatar_color = colors::avatar_color(&contact.name);
+                
+                let name_style = if selected {
+                    Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(colors::text())
+                };
+                
+                let mut spans = vec![
+                    Span::styled(marker.to_string(), Style::default().fg(marker_color).bg(bg)),
+                    Span::styled("[".to_string(), Style::default().fg(avatar_color).bg(bg)),
+                    Span::styled(initials.clone(), Style::default().fg(avatar_color).add_modifier(Modifier::BOLD).bg(bg)),
+                    Span::styled("] ".to_string(), Style::default().fg(avatar_color).bg(bg)),
+                    Span::styled(contact.label().to_string(), name_style.bg(bg)),
+                ];
+                let mut used_len = marker.chars().count() + 1 + initials.chars().count() + 2 + contact.label().chars().count();
+                
+                if contact.verified {
+                    spans.push(Span::styled(" ✓", Style::default().fg(colors::success()).bg(bg)));
+                    used_len += 2;
+                }
+                
+                if contact.settings.favorite {
+                    spans.push(Span::styled(" ★", Style::default().fg(colors::warning()).bg(bg)));
+                    used_len += 2;
+                }
+                
+                let muted = contact.settings.enable_ntfs == "none";
+                if app.is_blocked(&contact.name) {
+                    spans.push(Span::styled(" ⊘", Style::default().fg(colors::danger()).bg(bg)));
+                    used_len += 2;
+                } else if muted {
+                    spans.push(Span::styled(" ◌", Style::default().fg(colors::text_muted()).bg(bg)));
+                    used_len += 2;
+                } else if contact.settings.enable_ntfs == "mentions" {
+                    spans.push(Span::styled(" @", Style::default().fg(colors::text_muted()).bg(bg)));
+                    used_len += 2;
+                }
+                
+                if contact.unread > 0 {
+                    let unread_text = format!(" ({})", contact.unread);
+                    used_len += unread_text.chars().count();
+                    let color = if muted { colors::text_muted() } else { colors::unread() };
+                    spans.push(Span::styled(
+                        unread_text,
+                        Style::default().fg(color).add_modifier(Modifier::BOLD).bg(bg)
+                    ));
+                }
+                
+                let remaining = w.saturating_sub(used_len);
+                spans.push(Span::styled(" ".repeat(remaining), Style::default().bg(bg)));
+                
+                list.push(Line::from(spans));
+                list.push(Line::from(Span::styled(sep.clone(), Style::default().fg(colors::border()))));
+                if selected { selected_span = Some((first, 2)); }
+                spans_of.push((first, 2, Hit::Contact(i)));
             }
-            
-            let used_len = marker.len() + 1 + initials.len() + 2 + contact.name.len() + unread_text.len();
-            let remaining = w.saturating_sub(used_len);
-            spans.push(Span::styled(" ".repeat(remaining), Style::default().bg(bg)));
-            
-            lines.push(Line::from(spans));
-            lines.push(Line::from(Span::styled(sep.clone(), Style::default().fg(colors::BORDER))));
         }
     }
     
+    // Scroll just far enough to keep the selection in view
+    let avail = (area.height as usize).saturating_sub(header);
+    let offset = selected_span.map(|(first, n)| (first + n).saturating_sub(avail)).unwrap_or(0);
+    for (first, n, hit) in spans_of {
+        if first < offset || first + n > offset + avail { continue; }
+        let y = area.y + (header + first - offset) as u16;
+        app.hits.add(Rect { y, height: n as u16, ..area }, hit);
+    }
+    lines.extend(list.into_iter().skip(offset));
+    
     frame.render_widget(Paragraph::new(lines), area);
 }
 
-pub fn render_actions(frame: &mut Frame, area: Rect) {
+/// One tab per folder with its unread count; tabs before the active one
+/// are dropped when they don't all fit
+fn folder_tabs(app: &mut App, folders: &[Option<String>], area: Rect) -> Line<'static> {
+    let active = app.active_folder();
+    let tabs: Vec<(usize, String, bool)> = folders.iter().enumerate().map(|(i, f)| {
+        let unread = app.folder_unread(f);
+        let label = f.as_deref().unwrap_or("All");
+        let text = if unread > 0 { format!(" {} {} ", label, unread) } else { format!(" {} ", label) };
+        (i, text, *f == active)
+    }).collect();
+    
+    let width = |tabs: &[(usize, String, bool)]| tabs.iter().map(|(_, t, _)| t.chars().count()).sum::<usize>();
+    let active_pos = tabs.iter().position(|(_, _, a)| *a).unwrap_or(0);
+    let mut first = 0;
+    while first < active_pos && width(&tabs[first..=active_pos]) > area.width as usize { first += 1; }
+    
+    let mut spans = vec![];
+    let mut x = area.x;
+    for (i, text, is_active) in &tabs[first..] {
+        let w = text.chars().count() as u16;
+        if x + w > area.x + area.width { break; }
+        app.hits.add(Rect { x, y: area.y, width: w, height: 1 }, Hit::Folder(*i));
+        x += w;
+        let style = if *is_active {
+            Style::default().fg(colors::bg()).bg(colors::accent()).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(colors::text_muted())
+        };
+        spans.push(Span::styled(text.clone(), style));
+    }
+    Line::from(spans)
+}
+
+pub fn render_actions(frame: &mut Frame, area: Rect, app: &mut App) {
     if area.height < 2 { return; }
     
     let w = area.width as usize;
     let sep: String = "─".repeat(w);
     
-    let content = "[i]Add  [o]Opts  [r]Rfsh  [?]Help";
-    let pad = w.saturating_sub(content.len()) / 2;
-    let padding: String = " ".repeat(pad);
+    // Buttons come from the registry, labelled with their current key
+    let buttons: Vec<(Action, String, &str)> = ACTION_BAR.iter()
+        .map(|(action, label)| (*action, app.keymap.first_key(*action).unwrap_or_default(), *label))
+        .collect();
+    let content_w: usize = buttons.iter().map(|(_, k, l)| k.chars().count() + l.len() + 2).sum::<usize>()
+        + 2 * buttons.len().saturating_sub(1);
+    let pad = w.saturating_sub(content_w) / 2;
+    
+    let mut spans = vec![Span::styled(" ".repeat(pad), Style::default())];
+    let mut x = area.x + pad as u16;
+    for (i, (action, key, label)) in buttons.iter().enumerate() {
+        let bw = (key.chars().count() + label.len() + 2) as u16;
+        app.hits.add(Rect { x, y: area.y + 1, width: bw, height: 1 }, Hit::Action(*action));
+        x += bw + 2;
+        spans.push(Span::styled("[", Style::default().fg(colors::text_dim())));
+        spans.push(Span::styled(key.clone(), Style::default().fg(colors::accent())));
+        spans.push(Span::styled(format!("]{}", label), Style::default().fg(colors::text_dim())));
+        if i + 1 < buttons.len() { spans.push(Span::styled("  ", Style::default())); }
+    }
     
     let lines = vec![
-        Line::from(Span::styled(sep, Style::default().fg(colors::BORDER))),
-        Line::from(vec![
-            Span::styled(padding, Style::default()),
-            Span::styled("[", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled("i", Style::default().fg(colors::BLUE)),
-            Span::styled("]Add  ", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled("[", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled("o", Style::default().fg(colors::BLUE)),
-            Span::styled("]Opts  ", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled("[", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled("r", Style::default().fg(colors::BLUE)),
-            Span::styled("]Rfsh  ", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled("[", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled("?", Style::default().fg(colors::BLUE)),
-            Span::styled("]Help", Style::default().fg(colors::TEXT_DIM)),
-        ]),
+        Line::from(Span::styled(sep, Style::default().fg(colors::border()))),
+        Line::from(spans),
     ];
     
     frame.render_widget(
-        Paragraph::new(lines).style(Style::default().bg(colors::BG_SECONDARY)),
+        Paragraph::new(lines).style(Style::default().bg(colors::bg_secondary())),
         area
     );
 }
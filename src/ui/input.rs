@@ -11,35 +11,113 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
+use crate::actions::Action;
 use crate::app::App;
+use crate::audio;
 use crate::colors;
 use crate::types::Mode;
+use crate::vi::ViMode;
+
+const MAX_LINES: usize = 8;
+const BTN_W: u16 = 4;
+
+/// Box height for the composer at the given outer width: grows with the text
+pub fn height(app: &App, width: u16) -> u16 {
+    let text_w = width.saturating_sub(2 + BTN_W + 1) as usize;
+    let (lines, _) = app.composer.layout(text_w);
+    lines.len().clamp(1, MAX_LINES) as u16 + 2
+}
 
 pub fn render(frame: &mut Frame, area: Rect, app: &App) {
     let active = app.mode == Mode::Input;
-    let bc = if active { colors::BLUE } else { colors::BORDER };
+    let bc = if active { colors::accent() } else { colors::border() };
     
-    let block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(bc)).style(Style::default().bg(colors::BG_SECONDARY));
+    let mut block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(bc)).style(Style::default().bg(colors::bg_secondary()));
+    if let Some(vi) = app.vi.as_ref().filter(|_| active) {
+        let color = if vi.mode == ViMode::Insert { colors::success() } else { colors::warning() };
+        block = block.title(Span::styled(format!(" {} {}", vi.mode.label(), vi.pending()), Style::default().fg(color).add_modifier(Modifier::BOLD)));
+    }
     let inner = block.inner(area);
     frame.render_widget(block, area);
     
-    let btn_w = 4;
-    let inp_w = inner.width.saturating_sub(btn_w);
+    let inp_w = inner.width.saturating_sub(BTN_W);
+    let text_area = Rect { x: inner.x + 1, y: inner.y, width: inp_w.saturating_sub(1), height: inner.height };
     
-    let content = if app.input.is_empty() && !active {
-        Line::from(Span::styled(" Type a message...", Style::default().fg(colors::TEXT_DIM)))
+    if let Some((_, started)) = &app.recording {
+        let dot = if (app.tick / 5).is_multiple_of(2) { "●" } else { " " };
+        let line = Line::from(vec![
+            Span::styled(format!("{} ", dot), Style::default().fg(colors::danger()).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("Recording {}", audio::format_duration(started.elapsed().as_secs())), Style::default().fg(colors::text())),
+            Span::styled(format!("  {} stop & send", app.keymap.hint(Action::RecordVoice)), Style::default().fg(colors::text_dim())),
+        ]);
+        frame.render_widget(Paragraph::new(line), text_area);
+    } else if app.composer.is_empty() && !active {
+        frame.render_widget(Paragraph::new(Span::styled("Type a message...", Style::default().fg(colors::text_dim()))), text_area);
     } else {
-        let cursor_on = active && (app.tick / 5) % 2 == 0;
-        let before: String = app.input.chars().take(app.cursor).collect();
-        let after: String = app.input.chars().skip(app.cursor).collect();
-        let mut spans = vec![Span::styled(" ", Style::default()), Span::styled(before, Style::default().fg(colors::TEXT))];
-        if cursor_on { spans.push(Span::styled("▌", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD))); }
-        spans.push(Span::styled(after, Style::default().fg(colors::TEXT)));
-        Line::from(spans)
-    };
+        let selection = app.vi.as_ref().and_then(|vi| vi.selection(&app.composer));
+        let mut offsets = vec![app.composer.cursor()];
+        if let Some((from, to)) = selection { offsets.extend([from, to]); }
+        let (lines, pos) = app.composer.layout_positions(text_area.width as usize, &offsets);
+        let (cur_row, cur_col) = pos[0];
+        let visible = text_area.height as usize;
+        let first = (cur_row + 1).saturating_sub(visible);
+        let shown: Vec<Line> = lines.into_iter().skip(first).take(visible)
+            .map(|l| Line::from(Span::styled(l, Style::default().fg(colors::text()))))
+            .collect();
+        frame.render_widget(Paragraph::new(shown), text_area);
+        
+        if let [_, start, end] = pos[..] {
+            highlight(frame, text_area, first, start, end);
+        }
+        
+        // Vi normal mode shows a steady block cursor
+        let steady = app.vi.as_ref().is_some_and(|vi| vi.mode != ViMode::Insert);
+        let cursor_on = active && (steady || (app.tick / 5).is_multiple_of(2));
+        let (cx, cy) = (text_area.x + cur_col as u16, text_area.y + (cur_row - first) as u16);
+        if cursor_on && cx < text_area.x + text_area.width {
+            if let Some(cell) = frame.buffer_mut().cell_mut((cx, cy)) {
+                cell.set_bg(colors::accent()).set_fg(colors::bg_secondary());
+            }
+        }
+    }
+    
+    let sc = if !app.composer.is_empty() { colors::accent() } else { colors::text_dim() };
+    frame.render_widget(Paragraph::new(Line::from(Span::styled(" ➤ ", Style::default().fg(sc).add_modifier(Modifier::BOLD)))), Rect { x: inner.x + inp_w, y: inner.y, width: BTN_W, height: 1 });
+}
+
+/// Reverses the cells between two (row, col) layout positions
+fn highlight(frame: &mut Frame, area: Rect, first: usize, start: (usize, usize), end: (usize, usize)) {
+    for row in start.0.max(first)..=end.0 {
+        let Some(y) = (row - first).try_into().ok().filter(|y: &u16| *y < area.height) else { continue };
+        let from = if row == start.0 { start.1 } else { 0 };
+        let to = if row == end.0 { end.1 } else { area.width as usize };
+        for col in from..to.min(area.width as usize) {
+            if let Some(cell) = frame.buffer_mut().cell_mut((area.x + col as u16, area.y + y)) {
+                cell.set_style(Style::default().add_modifier(Modifier::REVERSED));
+            }
+        }
+    }
+}
+
+pub fn render_preview(frame: &mut Frame, area: Rect, app: &App) {
+    if area.height == 0 { return; }
     
-    frame.render_widget(Paragraph::new(content), Rect { x: inner.x, y: inner.y, width: inp_w, height: inner.height });
+    let line = match (&app.compose_preview, &app.preview_fetch) {
+        (Some(preview), _) => {
+            let desc = if preview.description.is_empty() { String::new() } else { format!(" — {}", preview.description) };
+            Line::from(vec![
+                Span::styled(" 🔗 ", Style::default()),
+                Span::styled(preview.title.clone(), Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)),
+                Span::styled(desc, Style::default().fg(colors::text_muted())),
+                Span::styled("  [Ctrl-X] remove", Style::default().fg(colors::text_dim())),
+            ])
+        }
+        (None, Some((url, _))) => Line::from(vec![
+            Span::styled(" 🔗 ", Style::default()),
+            Span::styled(format!("Loading preview for {}...", url), Style::default().fg(colors::text_dim())),
+        ]),
+        (None, None) => return,
+    };
     
-    let sc = if !app.input.is_empty() { colors::BLUE } else { colors::TEXT_DIM };
-    frame.render_widget(Paragraph::new(Line::from(Span::styled(" ➤ ", Style::default().fg(sc).add_modifier(Modifier::BOLD)))), Rect { x: inner.x + inp_w, y: inner.y, width: btn_w, height: inner.height });
+    frame.render_widget(Paragraph::new(line).style(Style::default().bg(colors::bg_secondary())), area);
 }
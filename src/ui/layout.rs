@@ -12,38 +12,67 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
+use crate::actions::Action;
 use crate::app::App;
 use crate::colors;
-use crate::types::Mode;
+use crate::hitmap::{Drag, Hit};
+use crate::types::{Mode, Panel};
 
-use super::{contacts, chat, input, status, modals};
+use super::{contacts, chat, console, input, status, modals};
+
+/// Narrowest chat pane shown next to the contact list; below that only one
+/// pane is drawn
+const MIN_CHAT_WIDTH: u16 = 48;
 
 pub fn render(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
+    app.hits.clear();
     
     let outer = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(colors::BORDER_ACTIVE))
-        .style(Style::default().bg(colors::BG));
+        .border_style(Style::default().fg(colors::border_active()))
+        .style(Style::default().bg(colors::bg()));
     
     let inner = outer.inner(area);
     frame.render_widget(outer, area);
     
-    let cols = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Length(32), Constraint::Min(1)])
-        .split(inner);
+    let width = app.ui_state.contacts_width;
+    app.compact = inner.width < width + 1 + MIN_CHAT_WIDTH;
+    if app.compact {
+        if app.panel == Panel::Contacts {
+            render_left(frame, inner, app);
+        } else {
+            render_right(frame, Rect { x: inner.x.saturating_sub(1), width: inner.width + 1, ..inner }, app);
+        }
+    } else {
+        let cols = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(width), Constraint::Min(1)])
+            .split(inner);
+        
+        render_left(frame, cols[0], app);
+        render_right(frame, cols[1], app);
+        let dragging = matches!(app.dragging, Some(Drag::Divider { .. }));
+        draw_divider(frame, area, cols[1].x - area.x, dragging);
+        app.hits.add(Rect { x: cols[1].x, width: 1, ..inner }, Hit::Divider);
+    }
     
-    render_left(frame, cols[0], app);
-    render_right(frame, cols[1], app);
-    draw_divider(frame, area, 33);
+    // Modals swallow clicks outside their own regions
+    let modal = !matches!(app.mode, Mode::Normal | Mode::Input | Mode::Command | Mode::Console | Mode::ChatSearch | Mode::ContactFilter);
+    if app.show_help || modal { app.hits.backdrop(area); }
     
-    if app.show_help { modals::render_help(frame); }
+    if app.show_help { modals::render_help(frame, app); }
     if app.mode == Mode::Panic { modals::render_panic(frame, app); }
     if app.mode == Mode::AddContact { modals::render_add_contact(frame, app); }
     if app.mode == Mode::ContactOptions { modals::render_contact_options(frame, app); }
     if app.mode == Mode::ContactInfo { modals::render_contact_info(frame, app); }
+    if app.mode == Mode::Verify { modals::render_verify(frame, app); }
     if app.mode == Mode::FileBrowser { modals::render_file_browser(frame, app); }
+    if app.mode == Mode::Palette { modals::render_palette(frame, app); }
+    if app.mode == Mode::GlobalSearch { modals::render_global_search(frame, app); }
+    if app.mode == Mode::Users { modals::render_users(frame, app); }
+    if app.mode == Mode::Profile { modals::render_profile(frame, app); }
+    if app.mode == Mode::ContactPrefs { modals::render_contact_prefs(frame, app); }
 }
 
 fn render_left(frame: &mut Frame, area: Rect, app: &mut App) {
@@ -53,42 +82,51 @@ fn render_left(frame: &mut Frame, area: Rect, app: &mut App) {
         .split(area);
     
     contacts::render(frame, rows[0], app);
-    contacts::render_actions(frame, rows[1]);
+    contacts::render_actions(frame, rows[1], app);
 }
 
 fn render_right(frame: &mut Frame, area: Rect, app: &mut App) {
     let chat_area = Rect { x: area.x + 1, y: area.y, width: area.width.saturating_sub(1), height: area.height };
     
+    let preview_h = if app.compose_preview.is_some() || app.preview_fetch.is_some() { 1 } else { 0 };
+    let input_h = input::height(app, chat_area.width);
+    
     let rows = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(2),
             Constraint::Min(1),
-            Constraint::Length(3),
+            Constraint::Length(preview_h),
+            Constraint::Length(input_h),
             Constraint::Length(1),
             Constraint::Length(1),
         ])
         .split(chat_area);
     
-    app.last_input_y = Some(rows[2].y);
+    app.hits.add(rows[3], Hit::Action(Action::Compose));
     
     chat::render_header(frame, rows[0], app);
-    chat::render_messages(frame, rows[1], app);
-    input::render(frame, rows[2], app);
+    if app.console.visible {
+        console::render(frame, rows[1], app);
+    } else {
+        chat::render_messages(frame, rows[1], app);
+    }
+    input::render_preview(frame, rows[2], app);
+    input::render(frame, rows[3], app);
     
-    let sep = "─".repeat(rows[3].width as usize);
+    let sep = "─".repeat(rows[4].width as usize);
     frame.render_widget(
-        Paragraph::new(Line::from(Span::styled(sep, Style::default().fg(colors::BORDER))))
-            .style(Style::default().bg(colors::BG_SECONDARY)),
-        rows[3]
+        Paragraph::new(Line::from(Span::styled(sep, Style::default().fg(colors::border()))))
+            .style(Style::default().bg(colors::bg_secondary())),
+        rows[4]
     );
     
-    status::render(frame, rows[4], app);
+    status::render(frame, rows[5], app);
 }
 
-fn draw_divider(frame: &mut Frame, area: Rect, x_off: u16) {
+fn draw_divider(frame: &mut Frame, area: Rect, x_off: u16, dragging: bool) {
     let x = area.x + x_off;
-    let style = Style::default().fg(colors::BORDER_ACTIVE);
+    let style = Style::default().fg(if dragging { colors::accent_light() } else { colors::border_active() });
     
     frame.render_widget(Paragraph::new(Line::from(Span::styled("┬", style))), Rect { x, y: area.y, width: 1, height: 1 });
     for row in 1..area.height.saturating_sub(1) {
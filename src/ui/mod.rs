@@ -7,6 +7,7 @@
 mod layout;
 mod contacts;
 mod chat;
+mod console;
 mod input;
 mod status;
 pub mod modals;
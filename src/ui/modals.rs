@@ -7,66 +7,405 @@
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
 };
 
+use crate::actions::{Action, Context, ACTIONS};
 use crate::app::App;
 use crate::colors;
+use crate::hitmap::Hit;
+use crate::chat_prefs::FEATURES;
+use crate::qr::Qr;
 use crate::types::ContactOption;
 
 
-pub fn render_help(frame: &mut Frame) {
+pub fn render_help(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
     frame.render_widget(Clear, area);
+    app.hits.add(area, Hit::Action(Action::Help));
     
     let block = Block::default()
-        .title(Span::styled(" Help ", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD)))
-        .borders(Borders::ALL).border_style(Style::default().fg(colors::BLUE))
-        .style(Style::default().bg(colors::BG));
+        .title(Span::styled(" Help ", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL).border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
     
     let inner = block.inner(area);
     frame.render_widget(block, area);
     
-    let lines = vec![
+    // Key sections are generated from the active keymap, so they never drift
+    let section = |context: Context| {
+        let mut lines = vec![
+            Line::from(""),
+            Line::from(Span::styled(format!(" {}", context.label()), Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))),
+            Line::from(""),
+        ];
+        for action in app.keymap.bound_in(context) {
+            let bound = app.keymap.keys_in(action, context);
+            let label = ACTIONS.iter()
+                .find(|a| a.action == action && a.context == context)
+                .unwrap_or(action.info())
+                .label;
+            lines.push(if action == Action::Panic {
+                klc(&bound, "PANIC MODE - Emergency actions", colors::danger())
+            } else {
+                kl(&bound, label)
+            });
+        }
+        lines
+    };
+    let mut keys: Vec<Line> = [Context::Anywhere, Context::Normal, Context::Contacts, Context::Chat]
+        .into_iter().flat_map(section).collect();
+    let modals: Vec<Line> = [Context::AddContact, Context::ContactOptions]
+        .into_iter().flat_map(section).collect();
+    
+    let mut composer = modals;
+    composer.extend([
         Line::from(""),
-        Line::from(Span::styled(" Navigation", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(" Composer (fixed keys)", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))),
         Line::from(""),
-        kl("Tab", "Switch panel"),
-        kl("j / ↓", "Move down / Scroll"),
-        kl("k / ↑", "Move up / Scroll"),
-        kl("Enter", "Select contact / Send message"),
+        kl("Alt/Shift+⏎", "New line"),
+        kl("Ctrl+←/→", "Move by word (also Alt-b / Alt-f)"),
+        kl("Ctrl-W", "Delete word back (Alt-d: forward)"),
+        kl("Ctrl-K / U", "Kill to line end / start"),
+        kl("Ctrl-Y", "Yank killed text"),
+        kl("Ctrl-Z / R", "Undo / Redo"),
+        kl("↑ / ↓", "Recall sent messages (empty composer)"),
+        kl("Ctrl-X", "Remove link preview before sending"),
+        kl("Esc (vi)", "Normal mode when vi_mode is on"),
         kl("Esc", "Back / Close modal"),
         Line::from(""),
-        Line::from(Span::styled(" Actions", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD))),
-        Line::from(""),
-        kl("i", "Add contact"),
-        kl("o", "Contact options (or double-click)"),
-        kl("r", "Refresh contacts & chat"),
-        kl("?", "Toggle this help"),
-        kl("q", "Quit application"),
-        Line::from(""),
-        Line::from(Span::styled(" Emergency", Style::default().fg(colors::DANGER).add_modifier(Modifier::BOLD))),
-        Line::from(""),
-        klc("F12", "PANIC MODE - Emergency actions", colors::DANGER),
+        Line::from(Span::styled(" Command Line (fixed keys)", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))),
         Line::from(""),
+        kl(":open NAME", "Open a chat"),
+        kl(":info", "Contact info"),
+        kl("/...", "Raw simplex-chat command"),
+        kl("Tab", "Complete names and paths"),
+    ]);
+    
+    // The last row keeps the hint in view while the rest scrolls
+    let body = Rect { height: inner.height.saturating_sub(1), ..inner };
+    let close = match app.keymap.keys_for(Action::Help) {
+        keys if keys.is_empty() => "[Esc]".to_string(),
+        keys => format!("[Esc] or [{}]", keys),
+    };
+    let footer = format!(" Press {} to close · j/k, PgUp/PgDn to scroll", close);
+    frame.render_widget(
+        Paragraph::new(Span::styled(footer, Style::default().fg(colors::text_dim()))),
+        Rect { y: inner.y + body.height, height: inner.height - body.height, ..inner },
+    );
+    
+    // Side by side when there is room, otherwise one long column
+    let columns = if inner.width >= 100 {
+        let half = inner.width / 2;
+        vec![(keys, Rect { width: half, ..body }), (composer, Rect { x: body.x + half, width: body.width - half, ..body })]
+    } else {
+        keys.append(&mut composer);
+        vec![(keys, body)]
+    };
+    let longest = columns.iter().map(|(lines, _)| lines.len()).max().unwrap_or(0);
+    app.help_height = body.height as usize;
+    app.help_scroll = app.help_scroll.min(longest.saturating_sub(app.help_height));
+    for (lines, rect) in columns {
+        frame.render_widget(Paragraph::new(lines).scroll((app.help_scroll as u16, 0)), rect);
+    }
+}
+
+pub fn render_palette(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(70);
+    let height = area.height.saturating_sub(4).min(20);
+    let rect = Rect { x: area.x + (area.width - width) / 2, y: area.y + 2, width, height };
+    frame.render_widget(Clear, rect);
+    
+    let block = Block::default()
+        .title(Span::styled(" Command Palette ", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL).border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
+    let inner = block.inner(rect);
+    frame.render_widget(block, rect);
+    
+    let mut lines = vec![Line::from(vec![
+        Span::styled(" › ", Style::default().fg(colors::accent())),
+        Span::styled(app.palette.query.clone(), Style::default().fg(colors::text())),
+        Span::styled("▌", Style::default().fg(colors::accent())),
+    ])];
+    lines.push(Line::from(Span::styled("─".repeat(inner.width as usize), Style::default().fg(colors::border()))));
+    
+    let matches = app.palette.matches();
+    let visible = (inner.height as usize).saturating_sub(2);
+    let first = (app.palette.selected + 1).saturating_sub(visible);
+    let label_w = (inner.width as usize).saturating_sub(20);
+    for (i, action) in matches.iter().enumerate().skip(first).take(visible) {
+        let row = inner.y + 2 + (i - first) as u16;
+        app.hits.add(Rect { y: row, height: 1, ..inner }, Hit::PaletteItem(i));
+        let selected = i == app.palette.selected;
+        let style = if selected { Style::default().fg(colors::bg()).bg(colors::accent()) } else { Style::default().fg(colors::text()) };
+        let key_style = if selected { style } else { Style::default().fg(colors::text_dim()) };
+        let label: String = action.info().label.chars().take(label_w).collect();
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {:w$}", label, w = label_w), style),
+            Span::styled(format!("{:>18} ", app.keymap.keys_for(*action)), key_style),
+        ]));
+    }
+    if matches.is_empty() {
+        lines.push(Line::from(Span::styled(" No matching action", Style::default().fg(colors::text_dim()))));
+    }
+    
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+pub fn render_global_search(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(90);
+    let height = area.height.saturating_sub(4).min(30);
+    let rect = Rect { x: area.x + (area.width - width) / 2, y: area.y + 2, width, height };
+    frame.render_widget(Clear, rect);
+    
+    let block = Block::default()
+        .title(Span::styled(" Search All Chats ", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL).border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
+    let inner = block.inner(rect);
+    frame.render_widget(block, rect);
+    
+    let gs = &app.global_search;
+    let mut lines = vec![Line::from(vec![
+        Span::styled(" / ", Style::default().fg(colors::accent())),
+        Span::styled(gs.input.clone(), Style::default().fg(colors::text())),
+        Span::styled("▌", Style::default().fg(colors::accent())),
+    ])];
+    lines.push(Line::from(Span::styled("─".repeat(inner.width as usize), Style::default().fg(colors::border()))));
+    
+    // Two rows per hit: chat and time, then sender and the matching text
+    let visible = (inner.height as usize).saturating_sub(2) / 2;
+    let first = (gs.selected + 1).saturating_sub(visible);
+    let text_w = (inner.width as usize).saturating_sub(4);
+    for (i, hit) in gs.hits.iter().enumerate().skip(first).take(visible) {
+        let row = inner.y + 2 + 2 * (i - first) as u16;
+        app.hits.add(Rect { y: row, height: 2, ..inner }, Hit::SearchResult(i));
+        let selected = i == gs.selected;
+        let bg = if selected { colors::bg_highlight() } else { colors::bg() };
+        let marker = if selected { " ▸ " } else { "   " };
+        let kind = if hit.group { "  group" } else { "" };
+        lines.push(Line::from(vec![
+            Span::styled(marker, Style::default().fg(colors::accent()).bg(bg)),
+            Span::styled(hit.chat.clone(), Style::default().fg(colors::avatar_color(&hit.chat)).add_modifier(Modifier::BOLD).bg(bg)),
+            Span::styled(format!("  {}{}", hit.time, kind), Style::default().fg(colors::text_dim()).bg(bg)),
+        ]));
+        let prefix = format!("{}: ", hit.sender);
+        let snippet = crate::search::snippet(&hit.text, &gs.query, text_w.saturating_sub(prefix.chars().count()));
+        let mut spans = vec![
+            Span::styled("   ", Style::default().bg(bg)),
+            Span::styled(prefix, Style::default().fg(colors::text_muted()).bg(bg)),
+        ];
+        spans.extend(super::chat::highlight(&snippet, Some(&gs.query), Style::default().fg(colors::text()).bg(bg), false)
+            .into_iter().map(|s| Span::styled(s.content.into_owned(), s.style)));
+        lines.push(Line::from(spans));
+    }
+    let note = if gs.loading {
+        " Searching..."
+    } else if gs.query.is_empty() {
+        " Type a query and press Enter"
+    } else if gs.hits.is_empty() {
+        " No messages found"
+    } else {
+        ""
+    };
+    if !note.is_empty() {
+        lines.push(Line::from(Span::styled(note, Style::default().fg(colors::text_dim()))));
+    }
+    
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+pub fn render_users(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(60);
+    let height = area.height.saturating_sub(4).min(app.users.users.len() as u16 + 8);
+    let rect = Rect { x: area.x + (area.width - width) / 2, y: area.y + 2, width, height };
+    frame.render_widget(Clear, rect);
+    
+    let block = Block::default()
+        .title(Span::styled(" User Profiles ", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL).border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
+    let inner = block.inner(rect);
+    frame.render_widget(block, rect);
+    
+    let mut lines = vec![Line::from("")];
+    let users = &app.users;
+    let visible = (inner.height as usize).saturating_sub(5);
+    let first = (users.selected + 1).saturating_sub(visible);
+    for (i, user) in users.users.iter().enumerate().skip(first).take(visible) {
+        app.hits.add(Rect { y: inner.y + lines.len() as u16, height: 1, ..inner }, Hit::User(i));
+        let selected = i == users.selected;
+        let bg = if selected { colors::bg_highlight() } else { colors::bg() };
+        let marker = if selected { " ▸ " } else { "   " };
+        let mut spans = vec![
+            Span::styled(marker, Style::default().fg(colors::accent()).bg(bg)),
+            Span::styled(user.name.clone(), Style::default().fg(colors::avatar_color(&user.name)).add_modifier(Modifier::BOLD).bg(bg)),
+        ];
+        if user.active { spans.push(Span::styled("  ● active", Style::default().fg(colors::success()).bg(bg))); }
+        if user.hidden { spans.push(Span::styled("  hidden", Style::default().fg(colors::warning()).bg(bg))); }
+        if user.muted { spans.push(Span::styled("  muted", Style::default().fg(colors::text_dim()).bg(bg))); }
+        if user.unread > 0 { spans.push(Span::styled(format!("  ({})", user.unread), Style::default().fg(colors::unread()).bg(bg))); }
+        lines.push(Line::from(spans));
+    }
+    if users.users.is_empty() {
+        lines.push(Line::from(Span::styled("   Loading profiles...", Style::default().fg(colors::text_dim()))));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" Enter switch · n new · r rename · h hide · m mute", Style::default().fg(colors::text_dim()))));
+    lines.push(Line::from(Span::styled(" d delete · :user <name> <password> opens hidden ones", Style::default().fg(colors::text_dim()))));
+    
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+pub fn render_contact_prefs(frame: &mut Frame, app: &mut App) {
+    let Some(editor) = &app.prefs_editor else { return };
+    let Some(contact) = app.contacts.iter().find(|c| c.id == editor.contact_id) else { return };
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(80);
+    let height = area.height.saturating_sub(4).min(FEATURES.len() as u16 + 9);
+    let rect = Rect { x: area.x + (area.width - width) / 2, y: area.y + 2, width, height };
+    frame.render_widget(Clear, rect);
+    
+    let block = Block::default()
+        .title(Span::styled(format!(" Chat Preferences: {} ", contact.label()), Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL).border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
+    let inner = block.inner(rect);
+    frame.render_widget(block, rect);
+    
+    let dim = Style::default().fg(colors::text_dim());
+    let mut lines = vec![
         Line::from(""),
-        Line::from(Span::styled(" Press [Esc] or [?] to close", Style::default().fg(colors::TEXT_DIM))),
+        Line::from(Span::styled(format!("   {:<24}{:<16}{:<12}{}", "Feature", "You", "Contact", "Result"), dim.add_modifier(Modifier::BOLD))),
     ];
+    for (i, (key, label)) in FEATURES.iter().enumerate() {
+        app.hits.add(Rect { y: inner.y + lines.len() as u16, height: 1, ..inner }, Hit::PrefRow(i));
+        let selected = i == editor.selected;
+        let bg = if selected { colors::bg_highlight() } else { colors::bg() };
+        let state = contact.prefs.state(key);
+        let theirs = contact.prefs.merged[*key]["contactPreference"]["allow"].as_str().unwrap_or("-");
+        let ours = match &editor.values[i] {
+            Some(allow) => allow.clone(),
+            None => format!("default ({})", contact.prefs.merged[*key]["userPreference"]["preference"]["allow"].as_str().unwrap_or("-")),
+        };
+        let (result, color) = match (state.for_user, state.for_contact) {
+            (true, true) => ("both", colors::success()),
+            (true, false) => ("you only", colors::warning()),
+            (false, true) => ("contact only", colors::warning()),
+            (false, false) => ("off", colors::text_muted()),
+        };
+        let ttl = state.ttl.filter(|_| *key == "timedMessages" && state.for_user).map(|t| format!(" {}", crate::timed::format_ttl(t))).unwrap_or_default();
+        lines.push(Line::from(vec![
+            Span::styled(if selected { " ▸ " } else { "   " }, Style::default().fg(colors::accent()).bg(bg)),
+            Span::styled(format!("{:<24}", label), Style::default().fg(colors::text()).bg(bg)),
+            Span::styled(format!("{:<16}", format!("◂ {} ▸", ours)), Style::default().fg(colors::accent()).bg(bg)),
+            Span::styled(format!("{:<12}", theirs), Style::default().fg(colors::text_muted()).bg(bg)),
+            Span::styled(format!("{}{}", result, ttl), Style::default().fg(color).bg(bg)),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" always: even if the contact says no · yes: if they allow it too", dim)));
+    lines.push(Line::from(Span::styled(" ↑↓ select · ←→ change · Enter save · Esc close", dim)));
     
     frame.render_widget(Paragraph::new(lines), inner);
 }
 
+pub fn render_profile(frame: &mut Frame, app: &mut App) {
+    let Some(editor) = &app.profile_editor else { return };
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(70);
+    let height = area.height.saturating_sub(4).min(24);
+    let rect = Rect { x: area.x + (area.width - width) / 2, y: area.y + 2, width, height };
+    frame.render_widget(Clear, rect);
+    
+    let block = Block::default()
+        .title(Span::styled(" Your Profile ", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL).border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
+    let inner = block.inner(rect);
+    frame.render_widget(block, rect);
+    
+    let label_style = |row: usize| if row == editor.selected {
+        Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(colors::text_dim())
+    };
+    let marker = |row: usize| if row == editor.selected { " ▸ " } else { "   " };
+    let mut lines = vec![Line::from("")];
+    let mut rows = vec![];
+    
+    let value_w = (inner.width as usize).saturating_sub(20);
+    for (row, label) in ["Display name", "Full name", "Bio"].into_iter().enumerate() {
+        rows.push((lines.len(), 1, row));
+        let text = &editor.texts[row];
+        let shown: String = text.chars().skip(text.chars().count().saturating_sub(value_w)).collect();
+        let cursor = if row == editor.selected && (app.tick / 5).is_multiple_of(2) { "▌" } else { "" };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}{:<15}", marker(row), label), label_style(row)),
+            Span::styled(shown, Style::default().fg(colors::text())),
+            Span::styled(cursor, Style::default().fg(colors::accent())),
+        ]));
+    }
+    
+    // Picture: the thumbnail, or a note, with its key hints
+    let row = crate::profile::PICTURE_ROW;
+    let hint = Span::styled("Enter choose · Backspace remove", Style::default().fg(colors::text_dim()));
+    let start = lines.len();
+    match &editor.thumbnail {
+        Some(thumb) => {
+            for (i, _) in thumb.rows.iter().enumerate() {
+                let label = if i == 0 { format!("{}{:<15}", marker(row), "Picture") } else { " ".repeat(18) };
+                let mut spans = vec![Span::styled(label, label_style(row))];
+                spans.extend(super::chat::thumbnail_row(thumb, i));
+                if i == 0 { spans.push(Span::raw("  ")); spans.push(hint.clone()); }
+                lines.push(Line::from(spans));
+            }
+        }
+        None => lines.push(Line::from(vec![
+            Span::styled(format!("{}{:<15}", marker(row), "Picture"), label_style(row)),
+            Span::styled("none  ", Style::default().fg(colors::text_muted())),
+            hint,
+        ])),
+    }
+    rows.push((start, lines.len() - start, row));
+    
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" Preferences", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))));
+    for (i, (_, label)) in crate::profile::PREFERENCES.iter().enumerate() {
+        let row = crate::profile::PICTURE_ROW + 1 + i;
+        rows.push((lines.len(), 1, row));
+        let allow = &editor.allow[i];
+        let color = if allow == "no" { colors::text_muted() } else { colors::success() };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}{:<24}", marker(row), label), label_style(row)),
+            Span::styled(format!("◂ {} ▸", allow), Style::default().fg(color)),
+        ]));
+    }
+    
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(" ↑↓ select · ←→ change · Enter save · Esc cancel", Style::default().fg(colors::text_dim()))));
+    
+    for (line, height, row) in rows {
+        app.hits.add(Rect { y: inner.y + line as u16, height: height as u16, ..inner }, Hit::ProfileRow(row));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
 pub fn render_panic(frame: &mut Frame, app: &App) {
     let area = frame.area();
     frame.render_widget(Clear, area);
     
-    let flash = if (app.tick / 4) % 2 == 0 { colors::DANGER } else { colors::TEXT_DIM };
+    let flash = if (app.tick / 4).is_multiple_of(2) { colors::danger() } else { colors::text_dim() };
     let block = Block::default()
         .title(Span::styled(" ⚠ PANIC MODE ⚠ ", Style::default().fg(flash).add_modifier(Modifier::BOLD)))
         .borders(Borders::ALL).border_style(Style::default().fg(flash))
-        .style(Style::default().bg(colors::BG));
+        .style(Style::default().bg(colors::bg()));
     
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -74,20 +413,20 @@ pub fn render_panic(frame: &mut Frame, app: &App) {
     let lines = vec![
         Line::from(""),
         Line::from(""),
-        Line::from(Span::styled(" Emergency Actions:", Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(" Emergency Actions:", Style::default().fg(colors::text()).add_modifier(Modifier::BOLD))),
         Line::from(""),
         Line::from(""),
-        po("1", "LOCK", "Lock screen immediately", colors::BLUE),
+        po("1", "LOCK", "Lock screen immediately", colors::accent()),
         Line::from(""),
-        po("2", "CLOSE", "Exit application", colors::BLUE),
+        po("2", "CLOSE", "Exit application", colors::accent()),
         Line::from(""),
-        po("3", "NUKE", "Destroy all local data", colors::DANGER),
+        po("3", "NUKE", "Destroy all local data", colors::danger()),
         Line::from(""),
-        po("4", "SSH", "Enter stealth SSH mode", colors::BLUE),
+        po("4", "SSH", "Enter stealth SSH mode", colors::accent()),
         Line::from(""),
         Line::from(""),
         Line::from(""),
-        Line::from(Span::styled(" Press [Esc] to cancel", Style::default().fg(colors::TEXT_DIM))),
+        Line::from(Span::styled(" Press [Esc] to cancel", Style::default().fg(colors::text_dim()))),
     ];
     
     frame.render_widget(Paragraph::new(lines), inner);
@@ -98,95 +437,161 @@ pub fn render_add_contact(frame: &mut Frame, app: &mut App) {
     frame.render_widget(Clear, area);
     
     let block = Block::default()
-        .title(Span::styled(" Add Contact ", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD)))
-        .borders(Borders::ALL).border_style(Style::default().fg(colors::BLUE))
-        .style(Style::default().bg(colors::BG));
+        .title(Span::styled(" Add Contact ", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL).border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
     
     let inner = block.inner(area);
     frame.render_widget(block, area);
     
+    let shown = app.invitation_selected.and_then(|i| app.invitations.get(i));
+    let heading = match shown {
+        Some(inv) if inv.incognito => " One-time link (incognito):".to_string(),
+        Some(_) => " One-time link:".to_string(),
+        None => " Your SimpleX Address:".to_string(),
+    };
     let mut lines = vec![
         Line::from(""),
-        Line::from(Span::styled(" Your SimpleX Address:", Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(heading, Style::default().fg(colors::text()).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+    ];
+    
+    let sep: String = "─".repeat((inner.width as usize).saturating_sub(2));
+    
+    // The address, then pending one-time links; selecting one shows it above
+    let selected_style = Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD);
+    let row_style = |selected: bool| if selected { selected_style } else { Style::default().fg(colors::text()) };
+    let marker = |selected: bool| if selected { " ▸ " } else { "   " };
+    let mut list = vec![
+        Line::from(""),
+        Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::border()))),
         Line::from(""),
+        Line::from(vec![
+            Span::styled(" Links:", Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)),
+            Span::styled(format!(
+                "  {} new one-time link · {} incognito: {} · {} cancel",
+                app.keymap.hint(Action::CreateInvitation),
+                app.keymap.hint(Action::ToggleIncognito),
+                if app.invite_incognito { "on" } else { "off" },
+                app.keymap.hint(Action::CancelInvitation),
+            ), Style::default().fg(colors::text_dim())),
+        ]),
+        Line::from(Span::styled(format!("{}Long-term address", marker(shown.is_none())), row_style(shown.is_none()))),
     ];
+    for (i, inv) in app.invitations.iter().enumerate() {
+        let selected = app.invitation_selected == Some(i);
+        list.push(Line::from(vec![
+            Span::styled(format!("{}One-time link, created {}", marker(selected), inv.created_at), row_style(selected)),
+            Span::styled(if inv.incognito { "  incognito" } else { "" }, Style::default().fg(colors::warning())),
+        ]));
+    }
     
-    if let Some(link) = &app.invite_link {
+    // Connect section, buttons, the hint line under the link and the list
+    let below = 16 + list.len();
+    
+    if let Some(link) = app.shown_link() {
         let cw = (inner.width as usize).saturating_sub(4);
-        for chunk in link.chars().collect::<Vec<_>>().chunks(cw) {
-            lines.push(Line::from(Span::styled(
-                format!(" {}", chunk.iter().collect::<String>()),
-                Style::default().fg(colors::BLUE)
-            )));
+        let text: Vec<Line> = link.chars().collect::<Vec<_>>().chunks(cw).map(|chunk| Line::from(Span::styled(
+            format!(" {}", chunk.iter().collect::<String>()),
+            Style::default().fg(colors::accent())
+        ))).collect();
+        let room = (inner.height as usize).saturating_sub(lines.len() + below);
+        let qr = Qr::new(link);
+        match qr.as_ref().and_then(|qr| Some((qr, qr.fit((inner.width as usize).saturating_sub(2), room)?))) {
+            Some((qr, scale)) => {
+                let drawing = qr_lines(qr, scale, 1);
+                let with_text = drawing.len() + text.len() < room;
+                lines.extend(drawing);
+                if with_text {
+                    lines.push(Line::from(""));
+                    lines.extend(text);
+                }
+            }
+            // Too small for a scannable code: the link as text
+            None => lines.extend(text),
         }
         lines.push(Line::from(""));
-        lines.push(Line::from(Span::styled(" ✓ Share this link with your contact", Style::default().fg(colors::SUCCESS))));
+        lines.push(Line::from(vec![
+            Span::styled(" ✓ Share this link with your contact", Style::default().fg(colors::success())),
+            Span::styled(
+                format!("  {} copy · {} export QR", app.keymap.hint(Action::CopyLink), app.keymap.hint(Action::ExportQr)),
+                Style::default().fg(colors::text_dim()),
+            ),
+        ]));
     } else {
-        lines.push(Line::from(Span::styled(" Loading address...", Style::default().fg(colors::TEXT_DIM))));
-        lines.push(Line::from(Span::styled(" Press [g] to refresh or [c] to create new", Style::default().fg(colors::TEXT_DIM))));
+        lines.push(Line::from(Span::styled(" Loading address...", Style::default().fg(colors::text_dim()))));
+        lines.push(Line::from(Span::styled(
+            format!(" Press {} to refresh or {} to create new", app.keymap.hint(Action::RefreshAddress), app.keymap.hint(Action::CreateAddress)),
+            Style::default().fg(colors::text_dim()),
+        )));
     }
     
-    let sep: String = "─".repeat((inner.width as usize).saturating_sub(2));
+    
+    let first_row = inner.y + (lines.len() + list.len() - app.invitations.len() - 1) as u16;
+    for row in 0..=app.invitations.len() {
+        app.hits.add(Rect { x: inner.x, y: first_row + row as u16, width: inner.width, height: 1 }, Hit::LinkRow(row));
+    }
+    lines.extend(list);
+    
     lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::BORDER))));
+    lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::border()))));
     lines.push(Line::from(""));
     
-    lines.push(Line::from(Span::styled(" Connect to someone:", Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD))));
+    lines.push(Line::from(Span::styled(" Connect to someone:", Style::default().fg(colors::text()).add_modifier(Modifier::BOLD))));
     lines.push(Line::from(""));
     
     let input_w = (inner.width as usize).saturating_sub(4);
     let top = format!(" ┌{}┐", "─".repeat(input_w));
     let bot = format!(" └{}┘", "─".repeat(input_w));
     
-    lines.push(Line::from(Span::styled(top, Style::default().fg(colors::BLUE))));
+    lines.push(Line::from(Span::styled(top, Style::default().fg(colors::accent()))));
     
-    let cursor = if (app.tick / 5) % 2 == 0 { "▌" } else { " " };
+    let cursor = if (app.tick / 5).is_multiple_of(2) { "▌" } else { " " };
     
     if app.connect_input.is_empty() {
         let placeholder = "Paste invite link here...";
         let pad = input_w.saturating_sub(placeholder.len() + 2);
         lines.push(Line::from(vec![
-            Span::styled(" │ ".to_string(), Style::default().fg(colors::BLUE)),
-            Span::styled(placeholder.to_string(), Style::default().fg(colors::TEXT_DIM)),
-            Span::styled(cursor.to_string(), Style::default().fg(colors::BLUE)),
+            Span::styled(" │ ".to_string(), Style::default().fg(colors::accent())),
+            Span::styled(placeholder.to_string(), Style::default().fg(colors::text_dim())),
+            Span::styled(cursor.to_string(), Style::default().fg(colors::accent())),
             Span::styled(" ".repeat(pad), Style::default()),
-            Span::styled("│".to_string(), Style::default().fg(colors::BLUE)),
+            Span::styled("│".to_string(), Style::default().fg(colors::accent())),
         ]));
     } else {
         let display: String = app.connect_input.chars().take(input_w - 4).collect();
         let pad = input_w.saturating_sub(display.len() + 2);
         lines.push(Line::from(vec![
-            Span::styled(" │ ".to_string(), Style::default().fg(colors::BLUE)),
-            Span::styled(display, Style::default().fg(colors::TEXT)),
-            Span::styled(cursor.to_string(), Style::default().fg(colors::BLUE)),
+            Span::styled(" │ ".to_string(), Style::default().fg(colors::accent())),
+            Span::styled(display, Style::default().fg(colors::text())),
+            Span::styled(cursor.to_string(), Style::default().fg(colors::accent())),
             Span::styled(" ".repeat(pad), Style::default()),
-            Span::styled("│".to_string(), Style::default().fg(colors::BLUE)),
+            Span::styled("│".to_string(), Style::default().fg(colors::accent())),
         ]));
     }
     
-    lines.push(Line::from(Span::styled(bot, Style::default().fg(colors::BLUE))));
+    lines.push(Line::from(Span::styled(bot, Style::default().fg(colors::accent()))));
     lines.push(Line::from(""));
-    lines.push(Line::from(Span::styled(" Press [Enter] to connect", Style::default().fg(colors::TEXT_DIM))));
+    let how = if app.invite_incognito { " incognito" } else { "" };
+    lines.push(Line::from(Span::styled(format!(" Press {} to connect{}", app.keymap.hint(Action::Connect), how), Style::default().fg(colors::text_dim()))));
     lines.push(Line::from(""));
     
     let button_y = inner.y + lines.len() as u16;
-    
-    
-        app.btn_refresh = Some(Rect { x: inner.x + 2, y: button_y, width: 14, height: 3 });
-        app.btn_create = Some(Rect { x: inner.x + 18, y: button_y, width: 16, height: 3 });
-        app.btn_close = Some(Rect { x: inner.x + 36, y: button_y, width: 14, height: 3 });
+    app.hits.add(Rect { x: inner.x + 1, y: button_y, width: 14, height: 3 }, Hit::Action(Action::RefreshAddress));
+    app.hits.add(Rect { x: inner.x + 17, y: button_y, width: 16, height: 3 }, Hit::Action(Action::CreateAddress));
+    app.hits.add(Rect { x: inner.x + 35, y: button_y, width: 14, height: 3 }, Hit::Action(Action::Back));
     
     lines.push(Line::from(Span::styled(
         " ┌────────────┐  ┌──────────────┐  ┌────────────┐".to_string(),
-        Style::default().fg(colors::BLUE)
+        Style::default().fg(colors::accent())
     )));
     lines.push(Line::from(Span::styled(
         " │[g] Refresh │  │[c] Create New│  │  [Esc] X   │".to_string(),
-        Style::default().fg(colors::BLUE)
+        Style::default().fg(colors::accent())
     )));
     lines.push(Line::from(Span::styled(
         " └────────────┘  └──────────────┘  └────────────┘".to_string(),
-        Style::default().fg(colors::BLUE)
+        Style::default().fg(colors::accent())
     )));
     
     frame.render_widget(Paragraph::new(lines), inner);
@@ -204,15 +609,15 @@ pub fn render_contact_options(frame: &mut Frame, app: &mut App) {
     let block = Block::default()
         .title(Span::styled(title, Style::default().fg(avatar_color).add_modifier(Modifier::BOLD)))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(colors::BLUE))
-        .style(Style::default().bg(colors::BG));
+        .border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
     
     let inner = block.inner(area);
     frame.render_widget(block, area);
     
     let mut lines = vec![
         Line::from(""),
-        Line::from(Span::styled(" Contact Options", Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(" Contact Options", Style::default().fg(colors::text()).add_modifier(Modifier::BOLD))),
         Line::from(""),
     ];
     
@@ -221,62 +626,68 @@ pub fn render_contact_options(frame: &mut Frame, app: &mut App) {
         Span::styled("    [", Style::default().fg(avatar_color)),
         Span::styled(&initials, Style::default().fg(avatar_color).add_modifier(Modifier::BOLD)),
         Span::styled("] ", Style::default().fg(avatar_color)),
-        Span::styled(contact_name, Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD)),
+        Span::styled(contact_name, Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)),
     ]));
     lines.push(Line::from(""));
     
     let sep: String = "─".repeat((inner.width as usize).saturating_sub(2));
-    lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::BORDER))));
+    lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::border()))));
     lines.push(Line::from(""));
     
     if let Some(action) = &app.confirm_action {
-        let warning_color = if (app.tick / 4) % 2 == 0 { colors::DANGER } else { colors::WARNING };
+        let warning_color = if (app.tick / 4).is_multiple_of(2) { colors::danger() } else { colors::warning() };
         lines.push(Line::from(Span::styled(
             format!(" ⚠ Confirm: {} ?", action.label()),
             Style::default().fg(warning_color).add_modifier(Modifier::BOLD)
         )));
         lines.push(Line::from(Span::styled(
-            " Press [Enter] again to confirm, [Esc] to cancel",
-            Style::default().fg(colors::TEXT_DIM)
+            format!(" Press {} again to confirm, {} to cancel", app.keymap.hint(Action::RunOption), app.keymap.hint(Action::Back)),
+            Style::default().fg(colors::text_dim())
         )));
         lines.push(Line::from(""));
-        lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::BORDER))));
+        lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::border()))));
         lines.push(Line::from(""));
     }
     
     let button_start_y = inner.y + lines.len() as u16;
     
     let options = ContactOption::all();
+    // Boxed buttons when they fit with the hints below, one line each otherwise
+    let room = (inner.y + inner.height).saturating_sub(button_start_y) as usize;
+    let btn_h = if room >= options.len() * 3 + 4 { 3 } else { 1 };
     for (i, option) in options.iter().enumerate() {
         let is_selected = i == app.option_selection;
         let is_destructive = option.is_destructive();
         
-        let btn_y = button_start_y + (i as u16 * 3);
-        
-            app.btn_contact_options[i] = Some(Rect { 
-                x: inner.x + 2, 
-                y: btn_y, 
-                width: 40, 
-                height: 3 
-            });
+        let btn_y = button_start_y + (i as u16 * btn_h);
+        app.hits.add(Rect { x: inner.x + 2, y: btn_y, width: 40, height: btn_h }, Hit::ContactOption(i));
         
         let (border_color, text_color, marker) = if is_selected {
             if is_destructive {
-                (colors::DANGER, colors::DANGER, "▸")
+                (colors::danger(), colors::danger(), "▸")
             } else {
-                (colors::BLUE, colors::BLUE, "▸")
+                (colors::accent(), colors::accent(), "▸")
             }
         } else {
             if is_destructive {
-                (colors::TEXT_DIM, colors::DANGER, " ")
+                (colors::text_dim(), colors::danger(), " ")
             } else {
-                (colors::TEXT_DIM, colors::TEXT, " ")
+                (colors::text_dim(), colors::text(), " ")
             }
         };
         
-        let key_char = option.key();
+        let key_char = app.keymap.keys_in(option.action(), Context::ContactOptions);
         let label = option.label();
         
+        if btn_h == 1 {
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {} ", marker), Style::default().fg(border_color)),
+                Span::styled(format!("[{}]", key_char), Style::default().fg(border_color)),
+                Span::styled(format!(" {}", label), Style::default().fg(text_color)),
+            ]));
+            continue;
+        }
+        
         lines.push(Line::from(vec![
             Span::styled(format!("  {}┌", marker), Style::default().fg(border_color)),
             Span::styled("─".repeat(36), Style::default().fg(border_color)),
@@ -284,7 +695,7 @@ pub fn render_contact_options(frame: &mut Frame, app: &mut App) {
         ]));
         
         let content = format!("[{}] {}", key_char, label);
-        let padding = 36 - content.len();
+        let padding = 36usize.saturating_sub(content.chars().count());
         lines.push(Line::from(vec![
             Span::styled("   │ ", Style::default().fg(border_color)),
             Span::styled(format!("[{}]", key_char), Style::default().fg(border_color)),
@@ -303,15 +714,15 @@ pub fn render_contact_options(frame: &mut Frame, app: &mut App) {
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         " Use ↑↓ or j/k to navigate, Enter to select",
-        Style::default().fg(colors::TEXT_DIM)
+        Style::default().fg(colors::text_dim())
     )));
     lines.push(Line::from(Span::styled(
         " Or press the key in brackets for quick access",
-        Style::default().fg(colors::TEXT_DIM)
+        Style::default().fg(colors::text_dim())
     )));
     lines.push(Line::from(Span::styled(
         " Double-click opens this menu",
-        Style::default().fg(colors::TEXT_DIM)
+        Style::default().fg(colors::text_dim())
     )));
     
     frame.render_widget(Paragraph::new(lines), inner);
@@ -319,14 +730,14 @@ pub fn render_contact_options(frame: &mut Frame, app: &mut App) {
 
 fn kl(k: &str, d: &str) -> Line<'static> {
     Line::from(vec![
-        Span::styled(format!(" {:12}", k), Style::default().fg(colors::BLUE)),
-        Span::styled(d.to_string(), Style::default().fg(colors::TEXT))
+        Span::styled(format!(" {:16}", k), Style::default().fg(colors::accent())),
+        Span::styled(d.to_string(), Style::default().fg(colors::text()))
     ])
 }
 
 fn klc(k: &str, d: &str, c: ratatui::style::Color) -> Line<'static> {
     Line::from(vec![
-        Span::styled(format!(" {:12}", k), Style::default().fg(c)),
+        Span::styled(format!(" {:16}", k), Style::default().fg(c)),
         Span::styled(d.to_string(), Style::default().fg(c))
     ])
 }
@@ -335,7 +746,7 @@ fn po(k: &str, n: &str, d: &str, c: ratatui::style::Color) -> Line<'static> {
     Line::from(vec![
         Span::styled(format!("  [{}] ", k), Style::default().fg(c)),
         Span::styled(format!("{:8}", n), Style::default().fg(c).add_modifier(Modifier::BOLD)),
-        Span::styled(format!(" - {}", d), Style::default().fg(colors::TEXT_DIM)),
+        Span::styled(format!(" - {}", d), Style::default().fg(colors::text_dim())),
     ])
 }
 
@@ -348,14 +759,15 @@ pub fn render_contact_info(frame: &mut Frame, app: &mut App) {
         None => return,
     };
     
+    app.hits.add(area, Hit::Action(Action::Back));
     let avatar_color = colors::avatar_color(&info.name);
     let title = format!(" Contact Info: {} ", info.name);
     
     let block = Block::default()
         .title(Span::styled(title, Style::default().fg(avatar_color).add_modifier(Modifier::BOLD)))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(colors::BLUE))
-        .style(Style::default().bg(colors::BG));
+        .border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
     
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -364,120 +776,253 @@ pub fn render_contact_info(frame: &mut Frame, app: &mut App) {
         Line::from(""),
     ];
     
-    // Avatar and name
-    let initials = colors::get_initials(&info.name);
-    lines.push(Line::from(vec![
-        Span::styled("    [", Style::default().fg(avatar_color)),
-        Span::styled(&initials, Style::default().fg(avatar_color).add_modifier(Modifier::BOLD)),
-        Span::styled("] ", Style::default().fg(avatar_color)),
-        Span::styled(&info.name, Style::default().fg(colors::TEXT).add_modifier(Modifier::BOLD)),
-    ]));
+    // Avatar and names; the real name stays visible next to an alias
+    let profile = &info.profile;
+    let shown = if info.alias.is_empty() { &info.name } else { &info.alias };
+    let mut names = vec![Line::from(Span::styled(shown.clone(), Style::default().fg(colors::text()).add_modifier(Modifier::BOLD)))];
+    let mut detail = |label: &str, value: &str| {
+        if value.is_empty() || value == shown { return; }
+        names.push(Line::from(vec![
+            Span::styled(format!("{}: ", label), Style::default().fg(colors::text_dim())),
+            Span::styled(value.to_string(), Style::default().fg(colors::text_muted())),
+        ]));
+    };
+    detail("Name", &info.name);
+    if profile.display_name != info.name { detail("Display name", &profile.display_name); }
+    detail("Full name", &profile.full_name);
+    match &info.avatar {
+        Some(thumb) => {
+            let rows = thumb.rows.len().max(names.len());
+            for row in 0..rows {
+                let mut spans = vec![Span::raw("    ")];
+                spans.extend(super::chat::thumbnail_row(thumb, row));
+                spans.push(Span::raw("  "));
+                if let Some(line) = names.get(row) { spans.extend(line.spans.clone()); }
+                lines.push(Line::from(spans));
+            }
+        }
+        None => {
+            let initials = colors::get_initials(&info.name);
+            for (row, line) in names.into_iter().enumerate() {
+                let mut spans = if row == 0 {
+                    vec![
+                        Span::styled("    [", Style::default().fg(avatar_color)),
+                        Span::styled(initials.clone(), Style::default().fg(avatar_color).add_modifier(Modifier::BOLD)),
+                        Span::styled("] ", Style::default().fg(avatar_color)),
+                    ]
+                } else {
+                    vec![Span::raw(" ".repeat(7 + initials.chars().count()))]
+                };
+                spans.extend(line.spans);
+                lines.push(Line::from(spans));
+            }
+        }
+    }
     lines.push(Line::from(""));
     
     let sep: String = "─".repeat((inner.width as usize).saturating_sub(2));
-    lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::BORDER))));
+    lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::border()))));
     lines.push(Line::from(""));
     
     // Bio
-    lines.push(Line::from(Span::styled(" Bio", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD))));
-    if info.bio.is_empty() {
-        lines.push(Line::from(Span::styled("   No bio set", Style::default().fg(colors::TEXT_DIM))));
-    } else {
-        lines.push(Line::from(Span::styled(format!("   {}", info.bio), Style::default().fg(colors::TEXT))));
+    lines.push(Line::from(Span::styled(" Bio", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))));
+    match profile.bio.as_deref().filter(|b| !b.is_empty()) {
+        Some(bio) => lines.push(Line::from(Span::styled(format!("   {}", bio), Style::default().fg(colors::text())))),
+        None => lines.push(Line::from(Span::styled("   No bio set", Style::default().fg(colors::text_dim())))),
     }
     lines.push(Line::from(""));
     
     // Connection Status
-    lines.push(Line::from(Span::styled(" Connection", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD))));
-    let status_color = if info.connection_status == "ready" { colors::SUCCESS } else { colors::WARNING };
+    lines.push(Line::from(Span::styled(" Connection", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))));
+    let status_color = if info.connection_status == "ready" { colors::success() } else { colors::warning() };
     lines.push(Line::from(vec![
-        Span::styled("   Status: ", Style::default().fg(colors::TEXT_DIM)),
+        Span::styled("   Status: ", Style::default().fg(colors::text_dim())),
         Span::styled(&info.connection_status, Style::default().fg(status_color)),
     ]));
     
     // PQ Encryption
     let pq_text = if info.pq_encryption { "✓ Quantum Resistant" } else { "Standard" };
-    let pq_color = if info.pq_encryption { colors::SUCCESS } else { colors::TEXT };
+    let pq_color = if info.pq_encryption { colors::success() } else { colors::text() };
     lines.push(Line::from(vec![
-        Span::styled("   Encryption: ", Style::default().fg(colors::TEXT_DIM)),
+        Span::styled("   Encryption: ", Style::default().fg(colors::text_dim())),
         Span::styled(pq_text, Style::default().fg(pq_color)),
     ]));
+    let (verified_text, verified_color) = if info.verified { ("✓ Verified", colors::success()) } else { ("Not verified", colors::text()) };
+    lines.push(Line::from(vec![
+        Span::styled("   Security code: ", Style::default().fg(colors::text_dim())),
+        Span::styled(verified_text, Style::default().fg(verified_color)),
+    ]));
+    let timed = info.prefs.state("timedMessages");
+    lines.push(Line::from(vec![
+        Span::styled("   Disappearing messages: ", Style::default().fg(colors::text_dim())),
+        Span::styled(
+            crate::timed::describe(timed),
+            Style::default().fg(if timed.for_user || timed.for_contact { colors::warning() } else { colors::text() }),
+        ),
+    ]));
+    if let Some(random) = &info.incognito_name {
+        lines.push(Line::from(vec![
+            Span::styled("   Incognito: ", Style::default().fg(colors::text_dim())),
+            Span::styled(format!("they see you as {}", random), Style::default().fg(colors::warning())),
+        ]));
+    }
     
     // Chat Version
     if !info.chat_version.is_empty() {
         lines.push(Line::from(vec![
-            Span::styled("   Protocol: ", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled(&info.chat_version, Style::default().fg(colors::TEXT)),
+            Span::styled("   Protocol: ", Style::default().fg(colors::text_dim())),
+            Span::styled(&info.chat_version, Style::default().fg(colors::text())),
         ]));
     }
     lines.push(Line::from(""));
     
     // Servers
-    lines.push(Line::from(Span::styled(" Servers", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD))));
+    lines.push(Line::from(Span::styled(" Servers", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))));
     if !info.receiving_server.is_empty() {
         let server_short: String = info.receiving_server.chars().take(50).collect();
         lines.push(Line::from(vec![
-            Span::styled("   Recv: ", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled(server_short, Style::default().fg(colors::TEXT)),
+            Span::styled("   Recv: ", Style::default().fg(colors::text_dim())),
+            Span::styled(server_short, Style::default().fg(colors::text())),
         ]));
     }
     if !info.sending_server.is_empty() {
         let server_short: String = info.sending_server.chars().take(50).collect();
         lines.push(Line::from(vec![
-            Span::styled("   Send: ", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled(server_short, Style::default().fg(colors::TEXT)),
+            Span::styled("   Send: ", Style::default().fg(colors::text_dim())),
+            Span::styled(server_short, Style::default().fg(colors::text())),
         ]));
     }
     lines.push(Line::from(""));
     
     // Address
-    if !info.address.is_empty() {
-        lines.push(Line::from(Span::styled(" Contact Address", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD))));
+    if let Some(address) = profile.contact_link.as_deref().filter(|a| !a.is_empty()) {
+        lines.push(Line::from(Span::styled(" Contact Address", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))));
         let addr_width = (inner.width as usize).saturating_sub(6);
-        for chunk in info.address.chars().collect::<Vec<_>>().chunks(addr_width) {
+        for chunk in address.chars().collect::<Vec<_>>().chunks(addr_width) {
             lines.push(Line::from(Span::styled(
                 format!("   {}", chunk.iter().collect::<String>()),
-                Style::default().fg(colors::BLUE_LIGHT)
+                Style::default().fg(colors::accent_light())
             )));
         }
         lines.push(Line::from(""));
     }
     
     // Timestamps
-    lines.push(Line::from(Span::styled(" History", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD))));
+    lines.push(Line::from(Span::styled(" History", Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD))));
     if !info.created_at.is_empty() {
         lines.push(Line::from(vec![
-            Span::styled("   Added: ", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled(&info.created_at, Style::default().fg(colors::TEXT)),
+            Span::styled("   Added: ", Style::default().fg(colors::text_dim())),
+            Span::styled(&info.created_at, Style::default().fg(colors::text())),
         ]));
     }
     if !info.updated_at.is_empty() {
         lines.push(Line::from(vec![
-            Span::styled("   Updated: ", Style::default().fg(colors::TEXT_DIM)),
-            Span::styled(&info.updated_at, Style::default().fg(colors::TEXT)),
+            Span::styled("   Updated: ", Style::default().fg(colors::text_dim())),
+            Span::styled(&info.updated_at, Style::default().fg(colors::text())),
         ]));
     }
     lines.push(Line::from(""));
     
-    lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::BORDER))));
+    lines.push(Line::from(Span::styled(format!(" {}", sep), Style::default().fg(colors::border()))));
     lines.push(Line::from(""));
     lines.push(Line::from(Span::styled(
         " Press [Esc] or [Enter] to close",
-        Style::default().fg(colors::TEXT_DIM)
+        Style::default().fg(colors::text_dim())
+    )));
+    
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Security code as digits in groups of five, then as a QR code for the
+/// contact's phone to scan when there is room
+pub fn render_verify(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+    frame.render_widget(Clear, area);
+    let Some(v) = &app.verification else { return };
+    let verified = app.contacts.iter().find(|c| c.id == v.contact_id).is_some_and(|c| c.verified);
+    
+    let block = Block::default()
+        .title(Span::styled(format!(" Verify: {} ", v.contact), Style::default().fg(colors::avatar_color(&v.contact)).add_modifier(Modifier::BOLD)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    
+    let heading = |t: &str| Line::from(Span::styled(format!(" {}", t), Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)));
+    let mut lines = vec![Line::from("")];
+    let (badge, badge_color) = if verified { ("✓ Verified", colors::success()) } else { ("Not verified", colors::warning()) };
+    lines.push(Line::from(vec![
+        Span::styled(" Status: ", Style::default().fg(colors::text_dim())),
+        Span::styled(badge, Style::default().fg(badge_color).add_modifier(Modifier::BOLD)),
+    ]));
+    lines.push(Line::from(""));
+    
+    lines.push(heading("Security code"));
+    for row in code_groups(&v.code).chunks(6) {
+        lines.push(Line::from(Span::styled(format!("   {}", row.join(" ")), Style::default().fg(colors::text()).add_modifier(Modifier::BOLD))));
+    }
+    lines.push(Line::from(""));
+    
+    let mut footer = vec![heading("Contact's code")];
+    footer.push(Line::from(vec![
+        Span::styled("   › ", Style::default().fg(colors::accent())),
+        Span::styled(code_groups(&v.input).join(" "), Style::default().fg(colors::text())),
+        Span::styled("▌", Style::default().fg(colors::accent())),
+    ]));
+    match v.matched {
+        Some(true) => footer.push(Line::from(Span::styled("   ✓ Codes match, contact verified", Style::default().fg(colors::success())))),
+        Some(false) => footer.push(Line::from(Span::styled("   ✗ Codes don't match", Style::default().fg(colors::danger()).add_modifier(Modifier::BOLD)))),
+        None => footer.push(Line::from("")),
+    }
+    footer.push(Line::from(""));
+    footer.push(Line::from(Span::styled(
+        " [Enter] Compare  [m] Codes match (compared by eye)  [u] Unverify  [Esc] Close",
+        Style::default().fg(colors::text_dim()),
     )));
     
+    let room = (inner.height as usize).saturating_sub(lines.len() + footer.len() + 1);
+    let qr = Qr::new(&v.code);
+    match qr.as_ref().and_then(|qr| Some((qr, qr.fit((inner.width as usize).saturating_sub(6), room)?))) {
+        Some((qr, scale)) => {
+            lines.extend(qr_lines(qr, scale, 3));
+            lines.push(Line::from(""));
+        }
+        _ => {
+            lines.push(Line::from(Span::styled("   Enlarge the terminal to show the QR code", Style::default().fg(colors::text_dim()))));
+            lines.push(Line::from(""));
+        }
+    }
+    lines.extend(footer);
+    
     frame.render_widget(Paragraph::new(lines), inner);
 }
 
+fn code_groups(code: &str) -> Vec<String> {
+    let digits: Vec<char> = code.chars().filter(|c| c.is_ascii_digit()).collect();
+    digits.chunks(5).map(|c| c.iter().collect()).collect()
+}
+
+/// Dark on light regardless of theme, so phones can scan it
+fn qr_lines(qr: &Qr, scale: usize, indent: usize) -> Vec<Line<'static>> {
+    let color = |dark: bool| if dark { Color::Black } else { Color::White };
+    qr.half_blocks(scale).iter().map(|row| {
+        let mut spans = vec![Span::raw(" ".repeat(indent))];
+        spans.extend(row.iter().map(|(top, bottom)| Span::styled("▀", Style::default().fg(color(*top)).bg(color(*bottom)))));
+        Line::from(spans)
+    }).collect()
+}
+
 pub fn render_file_browser(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
     frame.render_widget(Clear, area);
+    let title = if app.profile_editor.is_some() { " Select Profile Picture " } else { " Select File to Send " };
     
     let block = Block::default()
-        .title(Span::styled(" Select File to Send ", Style::default().fg(colors::BLUE).add_modifier(Modifier::BOLD)))
+        .title(Span::styled(title, Style::default().fg(colors::accent()).add_modifier(Modifier::BOLD)))
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(colors::BLUE))
-        .style(Style::default().bg(colors::BG));
+        .border_style(Style::default().fg(colors::accent()))
+        .style(Style::default().bg(colors::bg()));
     
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -486,3 +1031,44 @@ pub fn render_file_browser(frame: &mut Frame, app: &mut App) {
         frame.render_widget_ref(explorer.widget(), inner);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{backend::TestBackend, Terminal};
+    
+    use super::*;
+    use crate::actions::Keymap;
+    
+    fn screen(app: &mut App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| render_help(frame, app)).unwrap();
+        terminal.backend().buffer().content().iter().map(|cell| cell.symbol()).collect()
+    }
+    
+    #[test]
+    fn help_scrolls_to_the_last_section() {
+        let mut app = App::default();
+        app.toggle_help();
+        assert!(!screen(&mut app).contains("Command Line (fixed keys)"));
+        app.help_scroll = usize::MAX;
+        let text = screen(&mut app);
+        assert!(text.contains("Command Line (fixed keys)"));
+        assert!(text.contains("Press [Esc] or [?] to close"));
+        // Borders and the hint row leave 21 lines; the last page stays full
+        assert_eq!(app.help_height, 21);
+        assert!(app.help_scroll > 0 && app.help_scroll < usize::MAX);
+        let last = app.help_scroll;
+        app.scroll_help(5);
+        screen(&mut app);
+        assert_eq!(app.help_scroll, last);
+    }
+    
+    #[test]
+    fn close_hint_follows_the_keymap() {
+        let keys = toml::from_str("[normal]\nhelp = \"f1\"").unwrap();
+        let mut app = App::default();
+        app.keymap = Keymap::from_config(&keys).unwrap();
+        app.toggle_help();
+        assert!(screen(&mut app).contains("Press [Esc] or [F1] to close"));
+    }
+}
@@ -7,6 +7,7 @@
 use ratatui::{Frame, layout::Rect, style::Style, text::{Line, Span}, widgets::Paragraph};
 use crate::app::App;
 use crate::colors;
+use crate::types::Mode;
 
 const FOOTER_TEXTS: [&str; 3] = [
     "v0.1.2-alpha",
@@ -15,15 +16,30 @@ const FOOTER_TEXTS: [&str; 3] = [
 ];
 
 pub fn render(frame: &mut Frame, area: Rect, app: &App) {
+    if app.mode == Mode::ChatSearch {
+        let line = Line::from(vec![
+            Span::styled(" /", Style::default().fg(colors::accent())),
+            Span::styled(&app.search_input, Style::default().fg(colors::text())),
+            Span::styled("▌", Style::default().fg(colors::accent())),
+        ]);
+        frame.render_widget(Paragraph::new(line).style(Style::default().bg(colors::bg_secondary())), area);
+        return;
+    }
+    if app.mode == Mode::Command {
+        render_command(frame, area, app);
+        return;
+    }
+    
     let w = area.width as usize;
     
-    let (dot, dc) = if app.connected { ("●", colors::BLUE) } else { ("○", colors::WARNING) };
-    let (conn_txt, tc) = if app.connected { ("Connected", colors::BLUE) } else { ("Offline", colors::WARNING) };
+    let (dot, dc) = if app.connected { ("●", colors::accent()) } else { ("○", colors::warning()) };
+    let (conn_txt, tc) = if app.connected { ("Connected", colors::accent()) } else { ("Offline", colors::warning()) };
     
     let footer_text = get_footer_text(app.tick);
     
     let _left = format!(" {} {} │ {}", dot, conn_txt, app.status);
-    let left_len = 3 + conn_txt.len() + 3 + app.status.len();
+    let dnd = if app.ui_state.dnd { " │ DND" } else { "" };
+    let left_len = 3 + conn_txt.len() + dnd.chars().count() + 3 + app.status.len();
     
     let right_len = footer_text.len() + 2;
     
@@ -34,14 +50,33 @@ pub fn render(frame: &mut Frame, area: Rect, app: &App) {
         Span::styled(dot, Style::default().fg(dc)),
         Span::styled(" ", Style::default()),
         Span::styled(conn_txt, Style::default().fg(tc)),
-        Span::styled(" │ ", Style::default().fg(colors::BORDER)),
-        Span::styled(&app.status, Style::default().fg(colors::TEXT_MUTED)),
+        Span::styled(dnd, Style::default().fg(colors::warning())),
+        Span::styled(" │ ", Style::default().fg(colors::border())),
+        Span::styled(&app.status, Style::default().fg(colors::text_muted())),
         Span::styled(" ".repeat(space), Style::default()),
-        Span::styled(footer_text, Style::default().fg(colors::TEXT_DIM)),
+        Span::styled(footer_text, Style::default().fg(colors::text_dim())),
         Span::styled(" ", Style::default()),
     ]);
     
-    frame.render_widget(Paragraph::new(line).style(Style::default().bg(colors::BG_SECONDARY)), area);
+    frame.render_widget(Paragraph::new(line).style(Style::default().bg(colors::bg_secondary())), area);
+}
+
+fn render_command(frame: &mut Frame, area: Rect, app: &App) {
+    let ed = &app.command.input;
+    let (before, after) = ed.text().split_at(ed.cursor());
+    let mut rest = after.chars();
+    let under = rest.next().map(|c| c.to_string()).unwrap_or_else(|| " ".into());
+    let sigil = if ed.text().starts_with('/') { " " } else { " :" };
+    let hint = app.command.completion_hint().map(|h| format!("  ({})", h)).unwrap_or_default();
+    
+    let line = Line::from(vec![
+        Span::styled(sigil, Style::default().fg(colors::accent())),
+        Span::styled(before, Style::default().fg(colors::text())),
+        Span::styled(under, Style::default().fg(colors::bg_secondary()).bg(colors::accent())),
+        Span::styled(rest.as_str(), Style::default().fg(colors::text())),
+        Span::styled(hint, Style::default().fg(colors::text_dim())),
+    ]);
+    frame.render_widget(Paragraph::new(line).style(Style::default().bg(colors::bg_secondary())), area);
 }
 
 fn get_footer_text(tick: u64) -> String {
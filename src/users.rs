@@ -0,0 +1,39 @@
+//! User profiles: the profile switcher's state
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use crate::types::User;
+
+#[derive(Default)]
+pub struct UserSwitcher {
+    /// Profiles as listed by `/users`; hidden ones only while active
+    pub users: Vec<User>,
+    pub selected: usize,
+    /// First press of the delete key; the second one deletes
+    pub confirm_delete: bool,
+}
+
+impl UserSwitcher {
+    /// Keeps the selection on the same profile, or moves it to the active one
+    pub fn set_users(&mut self, users: Vec<User>) {
+        let selected = self.selected_user().map(|u| u.id);
+        self.users = users.into_iter().filter(|u| !u.hidden || u.active).collect();
+        self.selected = selected.and_then(|id| self.users.iter().position(|u| u.id == id))
+            .or_else(|| self.users.iter().position(|u| u.active))
+            .unwrap_or(0);
+        self.confirm_delete = false;
+    }
+    
+    pub fn move_selection(&mut self, down: bool) {
+        let len = self.users.len();
+        if len == 0 { return; }
+        self.selected = if down { (self.selected + 1) % len } else { (self.selected + len - 1) % len };
+        self.confirm_delete = false;
+    }
+    
+    pub fn selected_user(&self) -> Option<&User> {
+        self.users.get(self.selected)
+    }
+}
@@ -0,0 +1,496 @@
+//! Optional vi-style modal editing for the composer
+//! 
+//! Copyright (c) 2026 cannatoshi
+//! GitHub: https://github.com/cannatoshi/simplex-tui
+//! Licensed under AGPL-3.0
+
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::clipboard;
+use crate::config::EditorConfig;
+use crate::editor::Editor;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViMode { Normal, Insert, Visual, VisualLine }
+
+impl ViMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Insert => "INSERT",
+            Self::Visual => "VISUAL",
+            Self::VisualLine => "V-LINE",
+        }
+    }
+}
+
+/// What the caller should do after a key went through the vi layer
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ViOutcome {
+    Handled,
+    /// Insert mode: let the regular composer bindings handle the key
+    PassThrough,
+    Send,
+    Leave,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Span { Exclusive, Inclusive, Linewise }
+
+#[derive(Clone, Debug, Default)]
+struct Register { text: String, linewise: bool }
+
+pub struct Vi {
+    pub mode: ViMode,
+    /// Keys of an unfinished command, e.g. `"a2d`
+    pending: String,
+    anchor: usize,
+    registers: HashMap<char, Register>,
+}
+
+enum Parse { Incomplete, Invalid, Done(Command) }
+
+struct Command {
+    register: Option<char>,
+    count: usize,
+    op: Option<char>,
+    key: String,
+}
+
+/// Splits `["x][count][op][count]key` into its parts; in visual mode
+/// operators act on the selection right away instead of waiting for a motion
+fn parse(keys: &str, visual: bool) -> Parse {
+    let chars: Vec<char> = keys.chars().collect();
+    let mut i = 0;
+    let mut register = None;
+    if chars.first() == Some(&'"') {
+        match chars.get(1) {
+            Some(r) => { register = Some(*r); i = 2; }
+            None => return Parse::Incomplete,
+        }
+    }
+    
+    let take_count = |i: &mut usize| {
+        let mut n = 0usize;
+        while let Some(d) = chars.get(*i).and_then(|c| c.to_digit(10)) {
+            if n == 0 && d == 0 { break; }
+            n = n.saturating_mul(10).saturating_add(d as usize);
+            *i += 1;
+        }
+        n
+    };
+    let c1 = take_count(&mut i);
+    
+    let Some(&c) = chars.get(i) else { return Parse::Incomplete };
+    let op = if !visual && matches!(c, 'd' | 'c' | 'y') { i += 1; Some(c) } else { None };
+    let c2 = if op.is_some() { take_count(&mut i) } else { 0 };
+    
+    let key: String = chars[i..].iter().collect();
+    match key.as_str() {
+        "" | "g" => Parse::Incomplete,
+        k if k == "gg" || k.chars().count() == 1 => Parse::Done(Command { register, count: c1.max(1) * c2.max(1), op, key }),
+        _ => Parse::Invalid,
+    }
+}
+
+/// Normal mode keeps the cursor on a character, not past the line end
+fn clamp_to_line(ed: &mut Editor) {
+    let pos = ed.cursor();
+    if pos == ed.line_end_at(pos) && pos > ed.line_start_at(pos) {
+        ed.set_cursor(ed.prev_boundary(pos));
+    }
+}
+
+impl Vi {
+    pub fn new() -> Self {
+        Self { mode: ViMode::Insert, pending: String::new(), anchor: 0, registers: HashMap::new() }
+    }
+    
+    pub fn pending(&self) -> &str { &self.pending }
+    
+    /// Selection as a byte range, for highlighting in visual mode
+    pub fn selection(&self, ed: &Editor) -> Option<(usize, usize)> {
+        let (a, b) = (self.anchor.min(ed.cursor()), self.anchor.max(ed.cursor()));
+        match self.mode {
+            ViMode::Visual => Some((a, ed.next_boundary(b))),
+            ViMode::VisualLine => Some((ed.line_start_at(a), ed.line_end_at(b))),
+            _ => None,
+        }
+    }
+    
+    /// Remembers pasted text so `"+p` works without a paste command
+    pub fn remember_clipboard(&mut self, text: &str) {
+        self.registers.insert('+', Register { text: text.to_string(), linewise: false });
+    }
+    
+    pub fn handle_key(&mut self, ed: &mut Editor, code: KeyCode, mods: KeyModifiers, config: &EditorConfig) -> ViOutcome {
+        if self.mode == ViMode::Insert {
+            if code == KeyCode::Esc {
+                self.mode = ViMode::Normal;
+                if ed.cursor() > ed.line_start_at(ed.cursor()) { ed.move_left(); }
+                return ViOutcome::Handled;
+            }
+            return ViOutcome::PassThrough;
+        }
+        
+        if mods.contains(KeyModifiers::CONTROL) {
+            if code == KeyCode::Char('r') { ed.redo(); }
+            return ViOutcome::Handled;
+        }
+        
+        let c = match code {
+            KeyCode::Char(c) => c,
+            KeyCode::Esc if !self.pending.is_empty() => { self.pending.clear(); return ViOutcome::Handled; }
+            KeyCode::Esc if self.mode == ViMode::Normal => return ViOutcome::Leave,
+            KeyCode::Esc => { self.mode = ViMode::Normal; return ViOutcome::Handled; }
+            KeyCode::Enter if self.mode == ViMode::Normal => return ViOutcome::Send,
+            KeyCode::Left => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Up => 'k',
+            KeyCode::Down => 'j',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            KeyCode::Backspace => 'h',
+            _ => return ViOutcome::Handled,
+        };
+        
+        self.pending.push(c);
+        let visual = matches!(self.mode, ViMode::Visual | ViMode::VisualLine);
+        let cmd = match parse(&self.pending, visual) {
+            Parse::Incomplete => return ViOutcome::Handled,
+            Parse::Invalid => { self.pending.clear(); return ViOutcome::Handled; }
+            Parse::Done(cmd) => cmd,
+        };
+        self.pending.clear();
+        
+        if visual {
+            self.visual_command(ed, cmd, config);
+        } else {
+            self.normal_command(ed, cmd, config);
+        }
+        ViOutcome::Handled
+    }
+    
+    fn motion(ed: &Editor, from: usize, key: &str, count: usize) -> Option<(usize, Span)> {
+        let mut pos = from;
+        let span = match key {
+            "h" => { for _ in 0..count { if pos > ed.line_start_at(pos) { pos = ed.prev_boundary(pos); } } Span::Exclusive }
+            "l" | " " => { for _ in 0..count { if pos < ed.line_end_at(pos) { pos = ed.next_boundary(pos); } } Span::Exclusive }
+            "w" => { for _ in 0..count { pos = ed.word_start_pos(pos); } Span::Exclusive }
+            "b" => { for _ in 0..count { pos = ed.word_left_pos(pos); } Span::Exclusive }
+            "e" => {
+                for _ in 0..count {
+                    let end = ed.word_right_pos(ed.next_boundary(pos));
+                    pos = ed.prev_boundary(end).max(pos);
+                }
+                Span::Inclusive
+            }
+            "0" => { pos = ed.line_start_at(pos); Span::Exclusive }
+            "^" => {
+                let start = ed.line_start_at(pos);
+                let indent = ed.text()[start..ed.line_end_at(pos)].len() - ed.text()[start..ed.line_end_at(pos)].trim_start().len();
+                pos = start + indent;
+                Span::Exclusive
+            }
+            "$" => { pos = ed.line_end_at(pos); Span::Exclusive }
+            "j" => { for _ in 0..count { pos = ed.vertical_pos(pos, true)?; } Span::Linewise }
+            "k" => { for _ in 0..count { pos = ed.vertical_pos(pos, false)?; } Span::Linewise }
+            "gg" => { pos = 0; Span::Linewise }
+            "G" => { pos = ed.line_start_at(ed.text().len()); Span::Linewise }
+            _ => return None,
+        };
+        Some((pos, span))
+    }
+    
+    fn store(&mut self, register: Option<char>, text: String, linewise: bool, config: &EditorConfig) {
+        let reg = Register { text, linewise };
+        match register {
+            Some('_') => return,
+            Some(r @ ('+' | '*')) => {
+                clipboard::copy(&reg.text, config);
+                self.registers.insert(r, reg.clone());
+            }
+            Some(r) if r.is_ascii_uppercase() => {
+                let entry = self.registers.entry(r.to_ascii_lowercase()).or_default();
+                entry.text.push_str(&reg.text);
+            }
+            Some(r) => { self.registers.insert(r, reg.clone()); }
+            None => {}
+        }
+        self.registers.insert('"', reg);
+    }
+    
+    fn load(&self, register: Option<char>, config: &EditorConfig) -> Option<Register> {
+        match register {
+            Some(r @ ('+' | '*')) => clipboard::paste(config)
+                .map(|text| Register { text, linewise: false })
+                .or_else(|| self.registers.get(&r).cloned()),
+            Some(r) => self.registers.get(&r.to_ascii_lowercase()).cloned(),
+            None => self.registers.get(&'"').cloned(),
+        }
+    }
+    
+    /// Applies d/c/y to a byte range; linewise ranges cover whole lines
+    fn operate(&mut self, ed: &mut Editor, op: char, (from, to): (usize, usize), linewise: bool, register: Option<char>, config: &EditorConfig) {
+        let (mut from, mut to) = (from.min(to), from.max(to));
+        if linewise {
+            from = ed.line_start_at(from);
+            to = ed.line_end_at(to);
+        }
+        let text = ed.text()[from..to].to_string();
+        self.store(register, text, linewise, config);
+        
+        match op {
+            'y' => ed.set_cursor(from),
+            'd' => {
+                // Take a neighbouring newline along so no empty line is left behind
+                if linewise {
+                    if to < ed.text().len() { to += 1; } else { from = from.saturating_sub(1); }
+                }
+                ed.remove(from, to);
+                if linewise { ed.set_cursor(ed.line_start_at(ed.cursor().min(ed.text().len()))); }
+                self.mode = ViMode::Normal;
+                clamp_to_line(ed);
+            }
+            'c' => {
+                ed.remove(from, to);
+                self.mode = ViMode::Insert;
+            }
+            _ => {}
+        }
+    }
+    
+    fn normal_command(&mut self, ed: &mut Editor, cmd: Command, config: &EditorConfig) {
+        let Command { register, count, op, key } = cmd;
+        let pos = ed.cursor();
+        
+        if let Some(op) = op {
+            // dd, cc, yy
+            if key.len() == 1 && key.starts_with(op) {
+                let end = (1..count).try_fold(pos, |p, _| ed.vertical_pos(p, true)).unwrap_or(ed.text().len());
+                self.operate(ed, op, (pos, end), true, register, config);
+                return;
+            }
+            // cw behaves like ce, as in vim
+            let key = if op == 'c' && key == "w" { "e".to_string() } else { key };
+            if let Some((target, span)) = Self::motion(ed, pos, &key, count) {
+                let (a, b) = (pos.min(target), pos.max(target));
+                let b = if span == Span::Inclusive { ed.next_boundary(b) } else { b };
+                self.operate(ed, op, (a, b), span == Span::Linewise, register, config);
+            }
+            return;
+        }
+        
+        if let Some((target, _)) = Self::motion(ed, pos, &key, count) {
+            ed.set_cursor(target);
+            clamp_to_line(ed);
+            return;
+        }
+        
+        match key.as_str() {
+            "i" => self.mode = ViMode::Insert,
+            "a" => { if pos < ed.line_end_at(pos) { ed.move_right(); } self.mode = ViMode::Insert; }
+            "I" => { if let Some((p, _)) = Self::motion(ed, pos, "^", 1) { ed.set_cursor(p); } self.mode = ViMode::Insert; }
+            "A" => { ed.move_line_end(); self.mode = ViMode::Insert; }
+            "o" => { ed.move_line_end(); ed.newline(); self.mode = ViMode::Insert; }
+            "O" => { ed.move_line_start(); ed.newline(); ed.move_left(); self.mode = ViMode::Insert; }
+            "x" => {
+                let end = (0..count).fold(pos, |p, _| if p < ed.line_end_at(pos) { ed.next_boundary(p) } else { p });
+                if end > pos { self.operate(ed, 'd', (pos, end), false, register, config); }
+            }
+            "X" => {
+                let start = (0..count).fold(pos, |p, _| if p > ed.line_start_at(pos) { ed.prev_boundary(p) } else { p });
+                if start < pos { self.operate(ed, 'd', (start, pos), false, register, config); }
+            }
+            "D" => { let end = ed.line_end_at(pos); self.operate(ed, 'd', (pos, end), false, register, config); }
+            "C" => { let end = ed.line_end_at(pos); self.operate(ed, 'c', (pos, end), false, register, config); }
+            "Y" => self.operate(ed, 'y', (pos, pos), true, register, config),
+            "p" | "P" => {
+                let Some(reg) = self.load(register, config) else { return };
+                let text = reg.text.repeat(count);
+                if reg.linewise {
+                    if key == "p" {
+                        ed.set_cursor(ed.line_end_at(pos));
+                        ed.insert_str(&format!("\n{}", text));
+                    } else {
+                        let start = ed.line_start_at(pos);
+                        ed.set_cursor(start);
+                        ed.insert_str(&format!("{}\n", text));
+                        ed.set_cursor(start);
+                    }
+                } else {
+                    if key == "p" && pos < ed.line_end_at(pos) { ed.move_right(); }
+                    ed.insert_str(&text);
+                    ed.move_left();
+                }
+            }
+            "u" => { for _ in 0..count { ed.undo(); } clamp_to_line(ed); }
+            "v" => { self.anchor = pos; self.mode = ViMode::Visual; }
+            "V" => { self.anchor = pos; self.mode = ViMode::VisualLine; }
+            _ => {}
+        }
+    }
+    
+    fn visual_command(&mut self, ed: &mut Editor, cmd: Command, config: &EditorConfig) {
+        let pos = ed.cursor();
+        if let Some((target, _)) = Self::motion(ed, pos, &cmd.key, cmd.count) {
+            ed.set_cursor(target);
+            return;
+        }
+        let linewise = self.mode == ViMode::VisualLine;
+        let Some((from, to)) = self.selection(ed) else { return };
+        match cmd.key.as_str() {
+            "d" | "x" => self.operate(ed, 'd', (from, to), linewise, cmd.register, config),
+            "c" | "s" => self.operate(ed, 'c', (from, to), linewise, cmd.register, config),
+            "y" => { self.operate(ed, 'y', (from, to), linewise, cmd.register, config); self.mode = ViMode::Normal; }
+            "v" if self.mode == ViMode::Visual => self.mode = ViMode::Normal,
+            "V" if self.mode == ViMode::VisualLine => self.mode = ViMode::Normal,
+            "v" => self.mode = ViMode::Visual,
+            "V" => self.mode = ViMode::VisualLine,
+            "o" => { let a = self.anchor; self.anchor = pos; ed.set_cursor(a); }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn command(keys: &str, visual: bool) -> Command {
+        match parse(keys, visual) {
+            Parse::Done(cmd) => cmd,
+            _ => panic!("{} did not parse", keys),
+        }
+    }
+    
+    /// Types `keys` in normal mode, starting at byte offset `cursor`
+    fn run(text: &str, cursor: usize, keys: &str) -> (Vi, Editor) {
+        let mut ed = Editor::default();
+        ed.set_text(text);
+        ed.set_cursor(cursor);
+        let mut vi = Vi::new();
+        vi.mode = ViMode::Normal;
+        type_keys(&mut vi, &mut ed, keys);
+        (vi, ed)
+    }
+    
+    fn type_keys(vi: &mut Vi, ed: &mut Editor, keys: &str) {
+        for c in keys.chars() {
+            vi.handle_key(ed, KeyCode::Char(c), KeyModifiers::NONE, &EditorConfig::default());
+        }
+    }
+    
+    fn register(vi: &Vi, r: char) -> &str {
+        vi.registers.get(&r).map_or("", |reg| reg.text.as_str())
+    }
+    
+    #[test]
+    fn parses_register_counts_and_operator() {
+        let cmd = command("\"a2d3w", false);
+        assert_eq!((cmd.register, cmd.count, cmd.op, cmd.key.as_str()), (Some('a'), 6, Some('d'), "w"));
+        let cmd = command("10x", false);
+        assert_eq!((cmd.count, cmd.op, cmd.key.as_str()), (10, None, "x"));
+        // A leading zero is the line start motion, not a count
+        assert_eq!(command("0", false).key, "0");
+        assert_eq!(command("dgg", false).key, "gg");
+        // Visual mode takes d as a command of its own
+        assert_eq!((command("d", true).op, command("d", true).key.as_str()), (None, "d"));
+        for keys in ["\"", "2", "d", "\"ad", "g", "dg"] {
+            assert!(matches!(parse(keys, false), Parse::Incomplete), "{}", keys);
+        }
+        assert!(matches!(parse("dzz", false), Parse::Invalid));
+    }
+    
+    #[test]
+    fn delete_and_put_lines() {
+        let (mut vi, mut ed) = run("one\ntwo\nthree", 0, "dd");
+        assert_eq!(ed.text(), "two\nthree");
+        assert_eq!(register(&vi, '"'), "one");
+        type_keys(&mut vi, &mut ed, "p");
+        assert_eq!(ed.text(), "two\none\nthree");
+        type_keys(&mut vi, &mut ed, "G");
+        type_keys(&mut vi, &mut ed, "P");
+        assert_eq!(ed.text(), "two\none\none\nthree");
+        let (_, ed) = run("one\ntwo\nthree", 0, "2dd");
+        assert_eq!(ed.text(), "three");
+    }
+    
+    #[test]
+    fn change_word_stops_at_word_end() {
+        let (vi, ed) = run("hello world", 0, "cw");
+        assert_eq!(ed.text(), " world");
+        assert_eq!(vi.mode, ViMode::Insert);
+        assert_eq!(register(&vi, '"'), "hello");
+    }
+    
+    #[test]
+    fn counts_multiply_around_operator() {
+        assert_eq!(run("a b c d", 0, "d2w").1.text(), "c d");
+        assert_eq!(run("a b c d", 0, "2dw").1.text(), "c d");
+        assert_eq!(run("a b c d e", 0, "2d2w").1.text(), "e");
+        assert_eq!(run("1\n2\n3\n4", 0, "d2j").1.text(), "4");
+    }
+    
+    #[test]
+    fn x_and_put_characters() {
+        let (mut vi, mut ed) = run("abcd", 0, "2x");
+        assert_eq!(ed.text(), "cd");
+        type_keys(&mut vi, &mut ed, "P");
+        assert_eq!(ed.text(), "abcd");
+        assert_eq!(ed.cursor(), 1);
+        // xp swaps two characters
+        assert_eq!(run("abc", 0, "xp").1.text(), "bac");
+        // x stops at the line end
+        assert_eq!(run("ab\ncd", 1, "5x").1.text(), "a\ncd");
+    }
+    
+    #[test]
+    fn uppercase_register_appends() {
+        let (mut vi, mut ed) = run("one two", 0, "\"ayw");
+        assert_eq!(register(&vi, 'a'), "one ");
+        type_keys(&mut vi, &mut ed, "w\"Ayw");
+        assert_eq!(register(&vi, 'a'), "one two");
+        type_keys(&mut vi, &mut ed, "$\"ap");
+        assert_eq!(ed.text(), "one twoone two");
+    }
+    
+    #[test]
+    fn black_hole_register_keeps_unnamed() {
+        let (vi, ed) = run("one two", 0, "yw\"_dw");
+        assert_eq!(ed.text(), "two");
+        assert_eq!(register(&vi, '"'), "one ");
+        assert!(!vi.registers.contains_key(&'_'));
+    }
+    
+    #[test]
+    fn visual_delete_and_yank() {
+        let (vi, ed) = run("hello world", 0, "ved");
+        assert_eq!(ed.text(), " world");
+        assert_eq!(vi.mode, ViMode::Normal);
+        let (vi, ed) = run("hello world", 6, "vey");
+        assert_eq!(ed.text(), "hello world");
+        assert_eq!(register(&vi, '"'), "world");
+        assert_eq!((vi.mode, ed.cursor()), (ViMode::Normal, 6));
+        let (vi, ed) = run("one\ntwo\nthree", 0, "Vjd");
+        assert_eq!(ed.text(), "three");
+        assert_eq!(register(&vi, '"'), "one\ntwo");
+    }
+    
+    #[test]
+    fn counted_motions() {
+        assert_eq!(run("abcdef", 0, "3l").1.cursor(), 3);
+        // Stops on the last character, not past it
+        assert_eq!(run("abc\ndef", 0, "5l").1.cursor(), 2);
+        assert_eq!(run("abcdef", 5, "2h").1.cursor(), 3);
+        assert_eq!(run("a b c d", 0, "3w").1.cursor(), 6);
+        assert_eq!(run("a b c d", 6, "2b").1.cursor(), 2);
+        assert_eq!(run("one\ntwo\nthree", 1, "2j").1.cursor(), 9);
+        assert_eq!(run("one\ntwo\nthree", 1, "G").1.cursor(), 8);
+        assert_eq!(run("one\ntwo\nthree", 9, "gg").1.cursor(), 0);
+        // More lines than there are does not move
+        assert_eq!(run("one\ntwo", 1, "5j").1.cursor(), 1);
+    }
+}
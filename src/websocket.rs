@@ -10,11 +10,12 @@ use std::thread;
 use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
-use crate::types::{ApiCommand, ChatMessage, Contact, SimplexEvent, MessageStatus};
+use crate::thumbnail::Thumbnail;
+use crate::types::{ApiCommand, ChatMessage, Contact, SimplexEvent, LinkPreview, MessageStatus, SearchHit, VoiceClip, Group, Invitation, User, ContactPrefs, Timer, ContactRequest};
 
 pub fn spawn(
     event_tx: mpsc::Sender<SimplexEvent>,
-    cmd_rx: mpsc::Receiver<String>,
+    cmd_rx: mpsc::Receiver<ApiCommand>,
 ) {
     thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -22,7 +23,7 @@ pub fn spawn(
     });
 }
 
-async fn run_websocket(event_tx: mpsc::Sender<SimplexEvent>, cmd_rx: mpsc::Receiver<String>) {
+async fn run_websocket(event_tx: mpsc::Sender<SimplexEvent>, cmd_rx: mpsc::Receiver<ApiCommand>) {
     loop {
         match connect_async("ws://127.0.0.1:5225").await {
             Ok((ws, _)) => {
@@ -36,10 +37,15 @@ async fn run_websocket(event_tx: mpsc::Sender<SimplexEvent>, cmd_rx: mpsc::Recei
                 let cmd2 = ApiCommand::with_id("addr", "/sa");
                 let _ = write.send(Message::Text(serde_json::to_string(&cmd2).unwrap().into())).await;
                 
+                let cmd3 = ApiCommand::with_id("init", "/groups");
+                let _ = write.send(Message::Text(serde_json::to_string(&cmd3).unwrap().into())).await;
+                
+                let cmd4 = ApiCommand::with_id("init", "/user");
+                let _ = write.send(Message::Text(serde_json::to_string(&cmd4).unwrap().into())).await;
+                
                 loop {
                     // Check for commands to send
-                    while let Ok(cmd) = cmd_rx.try_recv() {
-                        let api = ApiCommand::new(&cmd);
+                    while let Ok(api) = cmd_rx.try_recv() {
                         if write.send(Message::Text(serde_json::to_string(&api).unwrap().into())).await.is_err() {
                             break;
                         }
@@ -77,7 +83,108 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
     let resp = json.get("resp").unwrap_or(json);
     let resp_type = resp.get("type").and_then(|t| t.as_str()).unwrap_or("");
     
+    // Commands typed on the command line get their response shown as is
+    if let Some(corr_id) = json.get("corrId").and_then(|c| c.as_str()).filter(|c| c.starts_with("raw-")) {
+        let _ = event_tx.send(SimplexEvent::CommandResponse { corr_id: corr_id.to_string(), response: resp.clone() });
+    }
+    
+    // Search results must not replace the open chat's history
+    match (json.get("corrId").and_then(|c| c.as_str()), resp_type) {
+        (Some("search-chat"), "apiChat") => {
+            let chat = resp.get("chat");
+            let info = chat.and_then(|c| c.get("chatInfo"));
+            let hits = chat.and_then(|c| c.get("chatItems")).and_then(|v| v.as_array())
+                .map(|items| items.iter().filter_map(|ci| search_hit(ci, info?)).collect())
+                .unwrap_or_default();
+            let _ = event_tx.send(SimplexEvent::SearchResults { global: false, hits });
+            return;
+        }
+        // Older backends reject the tag commands; tags are then kept locally
+        (Some("tags"), "chatCmdError" | "chatError" | "cmdError") => {
+            let _ = event_tx.send(SimplexEvent::ChatTags(None));
+            return;
+        }
+        (Some("invitations"), "apiChats") => {
+            let invitations = resp.get("chats").and_then(|v| v.as_array())
+                .map(|chats| chats.iter()
+                    .filter_map(|c| parse_invitation(c.get("chatInfo")?.get("contactConnection")?, None))
+                    .collect())
+                .unwrap_or_default();
+            let _ = event_tx.send(SimplexEvent::Invitations(invitations));
+            return;
+        }
+        (Some("requests"), "apiChats") => {
+            let requests = resp.get("chats").and_then(|v| v.as_array())
+                .map(|chats| chats.iter().filter_map(|c| parse_contact_request(c.get("chatInfo")?.get("contactRequest")?)).collect())
+                .unwrap_or_default();
+            let _ = event_tx.send(SimplexEvent::ContactRequests(requests));
+            return;
+        }
+        (Some("incognito"), "contactInfo") => {
+            let contact_id = resp.get("contact").and_then(|c| c.get("contactId")).and_then(|i| i.as_i64());
+            if let (Some(contact_id), Some(name)) = (contact_id, incognito_name(resp)) {
+                let _ = event_tx.send(SimplexEvent::IncognitoProfile { contact_id, name });
+            }
+            return;
+        }
+        (Some("users"), "cmdOk" | "userPrivacy" | "userProfileUpdated" | "userProfileNoChange") => {
+            let _ = event_tx.send(SimplexEvent::UsersChanged);
+            return;
+        }
+        (Some("search-all"), "chatItems") => {
+            let hits = resp.get("chatItems").and_then(|v| v.as_array())
+                .map(|items| items.iter().filter_map(|i| search_hit(i.get("chatItem")?, i.get("chatInfo")?)).collect())
+                .unwrap_or_default();
+            let _ = event_tx.send(SimplexEvent::SearchResults { global: true, hits });
+            return;
+        }
+        _ => {}
+    }
+    
     match resp_type {
+        "activeUser" => {
+            let user = resp.get("user");
+            if let Some(id) = user.and_then(|u| u.get("userId")).and_then(|i| i.as_i64()) {
+                let name = user.and_then(|u| u.get("localDisplayName")).and_then(|n| n.as_str()).unwrap_or_default().to_string();
+                let mut profile = user.and_then(|u| u.get("profile")).cloned().unwrap_or_default();
+                if let Some(prefs) = user.and_then(|u| u.get("fullPreferences")) {
+                    profile["preferences"] = prefs.clone();
+                }
+                let _ = event_tx.send(SimplexEvent::ActiveUser { id, name, profile });
+            }
+        }
+        
+        "usersList" => {
+            let users = resp.get("users").and_then(|u| u.as_array())
+                .map(|arr| arr.iter().filter_map(parse_user).collect())
+                .unwrap_or_default();
+            let _ = event_tx.send(SimplexEvent::Users(users));
+        }
+        
+        "chatTags" | "tagsUpdated" => {
+            let tags = resp.get("userTags").and_then(|t| serde_json::from_value(t.clone()).ok()).unwrap_or_default();
+            let _ = event_tx.send(SimplexEvent::ChatTags(Some(tags)));
+        }
+        
+        "contactCode" => {
+            let code = resp.get("connectionCode").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+            if let Some(contact) = resp.get("contact").and_then(parse_contact) {
+                let _ = event_tx.send(SimplexEvent::SecurityCode { contact, code });
+            }
+        }
+        
+        "connectionVerified" => {
+            let verified = resp.get("verified").and_then(|v| v.as_bool()).unwrap_or(false);
+            let expected = resp.get("expectedCode").and_then(|c| c.as_str()).unwrap_or_default().to_string();
+            let _ = event_tx.send(SimplexEvent::CodeVerified { verified, expected });
+        }
+        
+        "contactAliasUpdated" | "contactPrefsUpdated" => {
+            if let Some(contact) = resp.get("toContact").and_then(parse_contact) {
+                let _ = event_tx.send(SimplexEvent::ContactUpdated(contact));
+            }
+        }
+        
         "contactsList" => {
             if let Some(arr) = resp.get("contacts").and_then(|v| v.as_array()) {
                 let contacts = parse_contacts(arr);
@@ -85,6 +192,22 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
             }
         }
         
+        "groupsList" => {
+            if let Some(arr) = resp.get("groups").and_then(|v| v.as_array()) {
+                // Entries are [groupInfo, summary] pairs in newer versions
+                let groups = arr.iter()
+                    .map(|g| g.get(0).unwrap_or(g))
+                    .filter_map(|g| Some(Group {
+                        id: g.get("groupId").and_then(|i| i.as_i64())?,
+                        name: g.get("localDisplayName").and_then(|n| n.as_str())?.to_string(),
+                        tag_ids: tag_ids(g),
+                        settings: g.get("chatSettings").and_then(|s| serde_json::from_value(s.clone()).ok()).unwrap_or_default(),
+                    }))
+                    .collect();
+                let _ = event_tx.send(SimplexEvent::Groups(groups));
+            }
+        }
+        
         "chatItems" => {
             if let Some(items) = resp.get("chatItems").and_then(|v| v.as_array()) {
                 let messages = parse_chat_history(items);
@@ -104,48 +227,19 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
                     
                     if let Some(ci) = item.get("chatItem") {
                         let dir = ci.get("chatDir").and_then(|d| d.get("type")).and_then(|t| t.as_str()).unwrap_or("");
-                        let content_type = ci.get("content").and_then(|c| c.get("type")).and_then(|t| t.as_str()).unwrap_or("");
                         
                         if dir == "directSnd" {
                             let status = get_item_status(Some(ci));
                             let _ = event_tx.send(SimplexEvent::MessageUpdate { status });
                             
-                            // Gesendete Datei anzeigen
-                            if content_type == "sndMsgContent" || content_type == "file" {
-                                if let Some(file) = ci.get("file") {
-                                    let file_name = file.get("fileName").and_then(|f| f.as_str()).unwrap_or("file");
-                                    let file_size = file.get("fileSize").and_then(|s| s.as_u64()).unwrap_or(0);
-                                    let _ = event_tx.send(SimplexEvent::NewMessage {
-                                        sender: "You".to_string(),
-                                        text: format!("📎 {} ({} bytes)", file_name, file_size),
-                                    });
+                            // Text is shown locally on send, files and voice only arrive here
+                            if ci.get("file").is_some() {
+                                if let Some(message) = parse_chat_item(ci, "You") {
+                                    let _ = event_tx.send(SimplexEvent::NewMessage { sender: "You".to_string(), message });
                                 }
                             }
-                        } else {
-                            // Empfangene Nachricht oder Datei
-                            if let Some(text) = ci.get("content")
-                                .and_then(|c| c.get("msgContent"))
-                                .and_then(|mc| mc.get("text"))
-                                .and_then(|t| t.as_str()) 
-                            {
-                                if !text.is_empty() {
-                                    let _ = event_tx.send(SimplexEvent::NewMessage { 
-                                        sender: contact_name.clone(), 
-                                        text: text.to_string() 
-                                    });
-                                }
-                            }
-                            
-                            // Empfangene Datei
-                            if let Some(file) = ci.get("file") {
-                                let file_name = file.get("fileName").and_then(|f| f.as_str()).unwrap_or("file");
-                                let file_size = file.get("fileSize").and_then(|s| s.as_u64()).unwrap_or(0);
-                                let file_id = file.get("fileId").and_then(|f| f.as_u64()).unwrap_or(0);
-                                let _ = event_tx.send(SimplexEvent::NewMessage {
-                                    sender: contact_name,
-                                    text: format!("📎 {} ({} bytes) [/fr {} ./]", file_name, file_size, file_id),
-                                });
-                            }
+                        } else if let Some(message) = parse_chat_item(ci, &contact_name) {
+                            let _ = event_tx.send(SimplexEvent::NewMessage { sender: contact_name, message });
                         }
                     }
                 }
@@ -194,8 +288,22 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
         }
         
         "invitation" => {
-            if let Some(link) = resp.get("connReqInvitation").and_then(|l| l.as_str()) {
-                let _ = event_tx.send(SimplexEvent::InviteLink(link.to_string()));
+            let link = resp.get("connLinkInvitation").and_then(short_or_full_link)
+                .or_else(|| resp.get("connReqInvitation").and_then(|l| l.as_str()));
+            if let Some(invitation) = resp.get("connection").and_then(|c| parse_invitation(c, link)) {
+                let _ = event_tx.send(SimplexEvent::Invitation(invitation));
+            }
+        }
+        
+        "connectionIncognitoUpdated" => {
+            if let Some(invitation) = resp.get("toConnection").and_then(|c| parse_invitation(c, None)) {
+                let _ = event_tx.send(SimplexEvent::InvitationUpdated(invitation));
+            }
+        }
+        
+        "contactConnectionDeleted" => {
+            if let Some(id) = resp.get("connection").and_then(|c| c.get("pccConnId")).and_then(|i| i.as_i64()) {
+                let _ = event_tx.send(SimplexEvent::InvitationDeleted(id));
             }
         }
         
@@ -203,6 +311,23 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
             let _ = event_tx.send(SimplexEvent::Status("Invitation sent!".into()));
         }
         
+        "chatItemsDeleted" => {
+            let deletions = resp.get("chatItemDeletions").and_then(|d| d.as_array()).map(Vec::as_slice).unwrap_or_default();
+            let mut by_chat: Vec<(String, Vec<i64>)> = vec![];
+            for deleted in deletions.iter().filter_map(|d| d.get("deletedChatItem")) {
+                let chat = deleted.get("chatInfo").and_then(|i| i.get("contact"))
+                    .and_then(|c| c.get("localDisplayName")).and_then(|n| n.as_str());
+                let (Some(chat), Some(id)) = (chat, deleted.get("chatItem").and_then(item_id)) else { continue };
+                match by_chat.iter_mut().find(|(c, _)| c == chat) {
+                    Some((_, ids)) => ids.push(id),
+                    None => by_chat.push((chat.to_string(), vec![id])),
+                }
+            }
+            for (chat, ids) in by_chat {
+                let _ = event_tx.send(SimplexEvent::ItemsDeleted { chat, ids });
+            }
+        }
+        
         "contactConnecting" => {
             let _ = event_tx.send(SimplexEvent::Status("Connecting...".into()));
         }
@@ -213,8 +338,9 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
         }
         
         "receivedContactRequest" => {
-            let name = resp.get("contactRequest").and_then(|cr| cr.get("localDisplayName")).and_then(|n| n.as_str()).unwrap_or("Someone");
-            let _ = event_tx.send(SimplexEvent::ContactRequest(name.to_string()));
+            if let Some(request) = resp.get("contactRequest").and_then(parse_contact_request) {
+                let _ = event_tx.send(SimplexEvent::IncomingRequest(request));
+            }
         }
         
         "chatCmdError" | "chatError" | "cmdError" => {
@@ -232,19 +358,12 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
                 .unwrap_or("Unknown")
                 .to_string();
             
-            let bio = contact
-                .and_then(|c| c.get("profile"))
-                .and_then(|p| p.get("shortDescr"))
-                .and_then(|b| b.as_str())
-                .unwrap_or("")
-                .to_string();
-            
-            let address = contact
-                .and_then(|c| c.get("profile"))
-                .and_then(|p| p.get("contactLink"))
-                .and_then(|l| l.as_str())
-                .unwrap_or("")
-                .to_string();
+            let parsed = contact.and_then(parse_contact);
+            let verified = parsed.as_ref().is_some_and(|c| c.verified);
+            let prefs = parsed.as_ref().map(|c| c.prefs.clone()).unwrap_or_default();
+            let alias = parsed.as_ref().map(|c| c.alias.clone()).unwrap_or_default();
+            let profile = parsed.map(|c| c.profile).unwrap_or_default();
+            let avatar = profile.image.as_deref().and_then(|i| Thumbnail::from_data_uri(i, 12, 6));
             
             let receiving_server = conn_stats
                 .and_then(|cs| cs.get("rcvServers"))
@@ -297,13 +416,17 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
             
             let info_data = crate::types::ContactInfoData {
                 name: name.clone(),
-                bio,
-                address,
+                alias,
+                profile,
+                avatar,
                 receiving_server,
                 sending_server,
                 created_at,
                 updated_at,
                 pq_encryption,
+                verified,
+                incognito_name: incognito_name(resp),
+                prefs,
                 connection_status,
                 chat_version,
             };
@@ -317,6 +440,14 @@ fn process_message(json: &serde_json::Value, event_tx: &mpsc::Sender<SimplexEven
                 .to_string();
             let _ = event_tx.send(SimplexEvent::ContactDeleted(name));
         }
+        "rcvFileComplete" => {
+            let file = resp.get("chatItem").and_then(|ci| ci.get("chatItem")).and_then(|ci| ci.get("file"));
+            let file_id = file.and_then(|f| f.get("fileId")).and_then(|i| i.as_u64());
+            let path = file.and_then(file_path);
+            if let (Some(file_id), Some(path)) = (file_id, path) {
+                let _ = event_tx.send(SimplexEvent::FileReceived { file_id, path });
+            }
+        }
         "chatCleared" => {
             let name = resp.get("chatInfo")
                 .and_then(|ci| ci.get("contact"))
@@ -369,52 +500,210 @@ fn get_item_status(chat_item: Option<&serde_json::Value>) -> MessageStatus {
 }
 
 fn parse_contacts(arr: &[serde_json::Value]) -> Vec<Contact> {
-    arr.iter().filter_map(|c| {
-        let name = c.get("localDisplayName").and_then(|n| n.as_str())?;
-        Some(Contact::new(name.to_string()))
-    }).collect()
+    arr.iter().filter_map(parse_contact).collect()
+}
+
+fn parse_contact(c: &serde_json::Value) -> Option<Contact> {
+    let id = c.get("contactId").and_then(|i| i.as_i64())?;
+    let name = c.get("localDisplayName").and_then(|n| n.as_str())?;
+    let mut contact = Contact::new(id, name.to_string());
+    contact.tag_ids = tag_ids(c);
+    if let Some(settings) = c.get("chatSettings").and_then(|s| serde_json::from_value(s.clone()).ok()) {
+        contact.settings = settings;
+    }
+    if let Some(profile) = c.get("profile").and_then(|p| serde_json::from_value(p.clone()).ok()) {
+        contact.profile = profile;
+    }
+    contact.verified = c.get("activeConn").and_then(|a| a.get("connectionCode")).is_some_and(|code| !code.is_null());
+    contact.incognito = c.get("activeConn").and_then(|a| a.get("customUserProfileId")).is_some_and(|p| !p.is_null());
+    contact.prefs = ContactPrefs {
+        user: c.get("userPreferences").cloned().unwrap_or_default(),
+        merged: c.get("mergedPreferences").cloned().unwrap_or_default(),
+    };
+    contact.alias = c.get("localAlias").and_then(|a| a.as_str()).unwrap_or_default().to_string();
+    Some(contact)
+}
+
+fn parse_contact_request(cr: &serde_json::Value) -> Option<ContactRequest> {
+    Some(ContactRequest {
+        id: cr.get("contactRequestId").and_then(|i| i.as_i64())?,
+        name: cr.get("localDisplayName").and_then(|n| n.as_str())?.to_string(),
+        display_name: cr.get("profile").and_then(|p| p.get("displayName")).and_then(|n| n.as_str()).unwrap_or_default().to_string(),
+    })
+}
+
+/// One entry of `/users`: the user and its unread count
+fn parse_user(info: &serde_json::Value) -> Option<User> {
+    let user = info.get("user")?;
+    Some(User {
+        id: user.get("userId").and_then(|i| i.as_i64())?,
+        name: user.get("localDisplayName").and_then(|n| n.as_str())?.to_string(),
+        active: user.get("activeUser").and_then(|a| a.as_bool()).unwrap_or(false),
+        hidden: user.get("viewPwdHash").is_some_and(|h| !h.is_null()),
+        muted: user.get("showNtfs").and_then(|s| s.as_bool()) == Some(false),
+        unread: info.get("unreadCount").and_then(|u| u.as_u64()).unwrap_or(0) as usize,
+        profile: user.get("profile").cloned().unwrap_or_default(),
+    })
+}
+
+/// A pending connection we initiated with a one-time link; connections
+/// started from someone else's link have none and are skipped. `link`
+/// stands in for older backends that leave it out of the connection
+fn parse_invitation(conn: &serde_json::Value, link: Option<&str>) -> Option<Invitation> {
+    if conn.get("initiated").and_then(|i| i.as_bool()) == Some(false) { return None; }
+    let link = conn.get("connLinkInv").and_then(short_or_full_link)
+        .or_else(|| conn.get("connReqInv").and_then(|l| l.as_str()))
+        .or(link)?;
+    let created_at = conn.get("createdAt").and_then(|t| t.as_str())
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    Some(Invitation {
+        conn_id: conn.get("pccConnId").and_then(|i| i.as_i64())?,
+        link: link.to_string(),
+        incognito: conn.get("customUserProfileId").is_some_and(|p| !p.is_null()),
+        created_at,
+    })
+}
+
+fn short_or_full_link(link: &serde_json::Value) -> Option<&str> {
+    link.get("connShortLink").and_then(|l| l.as_str())
+        .or_else(|| link.get("connFullLink").and_then(|l| l.as_str()))
+}
+
+/// Display name of the random profile in a `contactInfo` response
+fn incognito_name(resp: &serde_json::Value) -> Option<String> {
+    resp.get("customUserProfile").and_then(|p| p.get("displayName")).and_then(|n| n.as_str()).map(str::to_string)
+}
+
+fn tag_ids(chat: &serde_json::Value) -> Vec<i64> {
+    chat.get("chatTags").and_then(|t| t.as_array())
+        .map(|ids| ids.iter().filter_map(|i| i.as_i64()).collect())
+        .unwrap_or_default()
+}
+
+fn file_path(file: &serde_json::Value) -> Option<String> {
+    file.get("fileSource").and_then(|s| s.get("filePath")).and_then(|p| p.as_str()).map(|p| p.to_string())
+}
+
+fn item_time(ci: &serde_json::Value) -> String {
+    ci.get("meta")
+        .and_then(|m| m.get("itemTs"))
+        .and_then(|t| t.as_str())
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+        .unwrap_or_default()
+}
+
+fn item_id(ci: &serde_json::Value) -> Option<i64> {
+    ci.get("meta").and_then(|m| m.get("itemId")).and_then(|i| i.as_i64())
+}
+
+fn search_hit(ci: &serde_json::Value, chat_info: &serde_json::Value) -> Option<SearchHit> {
+    let name = |v: Option<&serde_json::Value>| v.and_then(|v| v.get("localDisplayName")).and_then(|n| n.as_str()).map(str::to_string);
+    let group = chat_info.get("type").and_then(|t| t.as_str()) == Some("group");
+    let chat = if group { name(chat_info.get("groupInfo")) } else { name(chat_info.get("contact")) }?;
+    let dir = ci.get("chatDir");
+    let sender = match dir.and_then(|d| d.get("type")).and_then(|t| t.as_str()) {
+        Some("directSnd" | "groupSnd") => "You".to_string(),
+        Some("groupRcv") => name(dir.and_then(|d| d.get("groupMember"))).unwrap_or_default(),
+        _ => chat.clone(),
+    };
+    let text = ci.get("content").and_then(|c| c.get("msgContent")).and_then(|m| m.get("text"))
+        .or_else(|| ci.get("meta").and_then(|m| m.get("itemText")))
+        .and_then(|t| t.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let time = ci.get("meta")
+        .and_then(|m| m.get("itemTs"))
+        .and_then(|t| t.as_str())
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    Some(SearchHit { chat, group, item_id: item_id(ci)?, time, sender, text })
+}
+
+fn parse_chat_item(ci: &serde_json::Value, sender: &str) -> Option<ChatMessage> {
+    let dir = ci.get("chatDir").and_then(|d| d.get("type")).and_then(|t| t.as_str()).unwrap_or("");
+    let mine = dir == "directSnd";
+    let msg_content = ci.get("content").and_then(|c| c.get("msgContent"));
+    let file = ci.get("file");
+    
+    let voice = msg_content
+        .filter(|mc| mc.get("type").and_then(|t| t.as_str()) == Some("voice"))
+        .map(|mc| VoiceClip {
+            duration: mc.get("duration").and_then(|d| d.as_u64()).unwrap_or(0),
+            file_id: file.and_then(|f| f.get("fileId")).and_then(|i| i.as_u64()),
+            path: file.and_then(file_path),
+        });
+    
+    let link = msg_content
+        .filter(|mc| mc.get("type").and_then(|t| t.as_str()) == Some("link"))
+        .and_then(|mc| mc.get("preview"))
+        .map(|p| {
+            let field = |k: &str| p.get(k).and_then(|v| v.as_str()).unwrap_or("").to_string();
+            let image = Some(field("image")).filter(|i| !i.is_empty());
+            LinkPreview::new(field("uri"), field("title"), field("description"), image)
+        });
+    
+    // Text-Nachricht
+    let text = msg_content
+        .and_then(|mc| mc.get("text"))
+        .and_then(|t| t.as_str())
+        .filter(|t| !t.is_empty())
+        .map(|s| s.to_string());
+    
+    // Datei (Sprachnachrichten zeigen ihre eigene Wiedergabe-Zeile)
+    let file_info = file.filter(|_| voice.is_none()).map(|f| {
+        let name = f.get("fileName").and_then(|n| n.as_str()).unwrap_or("file");
+        let size = f.get("fileSize").and_then(|s| s.as_u64()).unwrap_or(0);
+        let id = f.get("fileId").and_then(|i| i.as_u64()).unwrap_or(0);
+        if mine {
+            format!("📎 {} ({} bytes)", name, size)
+        } else {
+            format!("📎 {} ({} bytes) [/fr {} ./]", name, size, id)
+        }
+    });
+    
+    // Kombiniere Text und/oder Datei
+    let content = match (text, file_info) {
+        (Some(t), Some(f)) => format!("{}\n{}", t, f),
+        (Some(t), None) => t,
+        (None, Some(f)) => f,
+        (None, None) if voice.is_some() => String::new(),
+        (None, None) => return None,
+    };
+    
+    Some(ChatMessage { 
+        id: item_id(ci),
+        sender: if mine { "You" } else { sender }.into(), 
+        content, 
+        time: item_time(ci), 
+        mine, 
+        status: if mine { get_item_status(Some(ci)) } else { MessageStatus::Delivered },
+        voice,
+        link,
+        timer: item_timer(ci),
+    })
+}
+
+fn item_timer(ci: &serde_json::Value) -> Option<Timer> {
+    let timed = ci.get("meta")?.get("itemTimed")?;
+    Some(Timer {
+        ttl: timed.get("ttl")?.as_i64()?,
+        delete_at: timed.get("deleteAt").and_then(|t| t.as_str())
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&chrono::Local)),
+    })
 }
 
 fn parse_chat_history(items: &[serde_json::Value]) -> Vec<ChatMessage> {
     items.iter().filter_map(|item| {
-        let ci = item.get("chatItem")?;
-        let dir = ci.get("chatDir").and_then(|d| d.get("type")).and_then(|t| t.as_str()).unwrap_or("");
-        let time = ci.get("meta").and_then(|m| m.get("itemTs")).and_then(|t| t.as_str()).and_then(|t| t.split('T').nth(1)).map(|t| t.chars().take(5).collect()).unwrap_or_default();
-        let mine = dir == "directSnd";
-        
-        // Text-Nachricht
-        let text = ci.get("content")
-            .and_then(|c| c.get("msgContent"))
-            .and_then(|mc| mc.get("text"))
-            .and_then(|t| t.as_str())
-            .map(|s| s.to_string());
-        
-        // Datei
-        let file_info = ci.get("file").map(|f| {
-            let name = f.get("fileName").and_then(|n| n.as_str()).unwrap_or("file");
-            let size = f.get("fileSize").and_then(|s| s.as_u64()).unwrap_or(0);
-            let id = f.get("fileId").and_then(|i| i.as_u64()).unwrap_or(0);
-            if mine {
-                format!("📎 {} ({} bytes)", name, size)
-            } else {
-                format!("📎 {} ({} bytes) [/fr {} ./]", name, size, id)
-            }
-        });
-        
-        // Kombiniere Text und/oder Datei
-        let content = match (text, file_info) {
-            (Some(t), Some(f)) => format!("{}\n{}", t, f),
-            (Some(t), None) => t,
-            (None, Some(f)) => f,
-            (None, None) => return None,
-        };
-        
-        Some(ChatMessage { 
-            sender: if mine { "You" } else { "Contact" }.into(), 
-            content, 
-            time, 
-            mine, 
-            status: if mine { get_item_status(Some(ci)) } else { MessageStatus::Delivered } 
-        })
+        let sender = item.get("chatInfo")
+            .and_then(|ci| ci.get("contact"))
+            .and_then(|c| c.get("localDisplayName"))
+            .and_then(|n| n.as_str())
+            .unwrap_or("Contact");
+        parse_chat_item(item.get("chatItem")?, sender)
     }).collect()
-}
\ No newline at end of file
+}